@@ -0,0 +1,20 @@
+-- back to arrays of 10 ids, only what is still waiting to be removed is kept
+CREATE TABLE play_tweet_chunks(
+    id BIGSERIAL PRIMARY KEY,
+    user_id UUID references user_preference(user_id) ON DELETE CASCADE,
+    tweet_type tweet_type,
+    tweet_ids text[]
+);
+
+INSERT INTO play_tweet_chunks (user_id, tweet_type, tweet_ids)
+SELECT user_id, tweet_type, array_agg(tweet_id ORDER BY id) FROM (
+    SELECT id, user_id, tweet_type, tweet_id, (row_number() OVER (PARTITION BY user_id, tweet_type ORDER BY id) - 1) / 10 AS chunk
+    FROM play_tweets WHERE status <> 'done'
+) items
+GROUP BY user_id, tweet_type, chunk
+ORDER BY min(id);
+
+DROP TABLE play_tweets;
+DROP TYPE play_status;
+ALTER TABLE play_tweet_chunks RENAME TO play_tweets;
+ALTER SEQUENCE play_tweet_chunks_id_seq RENAME TO play_tweets_id_seq;
@@ -0,0 +1,36 @@
+-- one row per tweet, retweet or like queued for removal, instead of arrays of 10 ids
+CREATE TYPE play_status AS ENUM ('pending', 'processing', 'done', 'failed');
+
+CREATE TABLE play_tweet_items(
+    id BIGSERIAL PRIMARY KEY,
+    user_id UUID NOT NULL references user_preference(user_id) ON DELETE CASCADE,
+    tweet_id TEXT NOT NULL,
+    tweet_type tweet_type NOT NULL,
+    -- when the tweet was posted, unknown for ids that were only sent to /remove
+    created_at TIMESTAMPTZ,
+    -- sha1 of the text, so a tweet posted again under a new id can be found
+    text_hash TEXT,
+    -- the tweet a retweet points to
+    source_tweet_id TEXT,
+    -- public_metrics as Twitter returned them
+    metrics JSONB,
+    status play_status NOT NULL DEFAULT 'pending',
+    last_error TEXT,
+    queued_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (user_id, tweet_id, tweet_type)
+);
+
+-- rows without a user or a type were never going to be removed
+INSERT INTO play_tweet_items (user_id, tweet_id, tweet_type)
+SELECT user_id, unnest(tweet_ids), tweet_type FROM play_tweets
+WHERE user_id IS NOT NULL AND tweet_type IS NOT NULL
+ORDER BY id
+ON CONFLICT DO NOTHING;
+
+DROP TABLE play_tweets;
+ALTER TABLE play_tweet_items RENAME TO play_tweets;
+ALTER SEQUENCE play_tweet_items_id_seq RENAME TO play_tweets_id_seq;
+
+CREATE INDEX play_tweets_user_status ON play_tweets (user_id, status);
+CREATE INDEX play_tweets_user_created_at ON play_tweets (user_id, created_at);
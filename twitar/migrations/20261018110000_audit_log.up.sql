@@ -0,0 +1,27 @@
+-- every action taken on a user's account, appended to and never changed
+CREATE TABLE audit_log(
+    id BIGSERIAL PRIMARY KEY,
+    -- no reference to user_preference, the trail outlives the user
+    user_id UUID NOT NULL,
+    action TEXT NOT NULL,
+    outcome TEXT NOT NULL CHECK (outcome IN ('success', 'failure', 'deferred')),
+    -- what the action was taken on, e.g. a tweet id
+    target TEXT,
+    detail JSONB,
+    request_id TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX audit_log_user_created_at ON audit_log (user_id, created_at);
+
+CREATE FUNCTION audit_log_append_only() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION 'audit_log is append-only, % is not allowed', TG_OP;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_log_no_update_or_delete BEFORE UPDATE OR DELETE ON audit_log
+FOR EACH ROW EXECUTE PROCEDURE audit_log_append_only();
+
+CREATE TRIGGER audit_log_no_truncate BEFORE TRUNCATE ON audit_log
+FOR EACH STATEMENT EXECUTE PROCEDURE audit_log_append_only();
@@ -0,0 +1,19 @@
+-- what a tweet, retweet or like held right before it was removed, kept once Twitter no longer has it
+CREATE TABLE tweet_archive(
+    id BIGSERIAL PRIMARY KEY,
+    user_id UUID NOT NULL references user_preference(user_id) ON DELETE CASCADE,
+    tweet_id TEXT NOT NULL,
+    tweet_type tweet_type NOT NULL,
+    text TEXT NOT NULL,
+    author_id TEXT,
+    entities JSONB,
+    -- public_metrics as Twitter returned them
+    metrics JSONB,
+    -- the attached media with their urls
+    media JSONB,
+    referenced_tweets JSONB,
+    -- when the tweet was posted
+    created_at TIMESTAMPTZ,
+    archived_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (user_id, tweet_id, tweet_type)
+);
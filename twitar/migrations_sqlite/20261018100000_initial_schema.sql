@@ -0,0 +1,44 @@
+-- the schema the Postgres migrations end up with, for the sqlite storage. Uuids are stored as blobs,
+-- enums as text and timestamps as ISO 8601 text
+CREATE TABLE user_preference(
+    user_id BLOB PRIMARY KEY NOT NULL,
+    v1_active BOOLEAN NOT NULL,
+    v2_active BOOLEAN NOT NULL
+);
+
+CREATE TABLE auth_one(
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id BLOB UNIQUE NOT NULL REFERENCES user_preference(user_id) ON DELETE CASCADE,
+    twitter_user_id TEXT UNIQUE,
+    oauth_token TEXT NOT NULL,
+    oauth_secret TEXT NOT NULL,
+    oauth_verifier TEXT
+);
+
+CREATE TABLE auth_two(
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id BLOB UNIQUE NOT NULL REFERENCES user_preference(user_id) ON DELETE CASCADE,
+    twitter_user_id TEXT UNIQUE,
+    pkce TEXT,
+    access_token TEXT,
+    refresh_token TEXT
+);
+
+CREATE TABLE play_tweets(
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id BLOB NOT NULL REFERENCES user_preference(user_id) ON DELETE CASCADE,
+    tweet_id TEXT NOT NULL,
+    tweet_type TEXT NOT NULL CHECK (tweet_type IN ('rts', 'likes', 'tweets')),
+    created_at TEXT,
+    text_hash TEXT,
+    source_tweet_id TEXT,
+    metrics TEXT,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'processing', 'done', 'failed')),
+    last_error TEXT,
+    queued_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (user_id, tweet_id, tweet_type)
+);
+
+CREATE INDEX play_tweets_user_status ON play_tweets (user_id, status);
+CREATE INDEX play_tweets_user_created_at ON play_tweets (user_id, created_at);
@@ -0,0 +1,26 @@
+-- every action taken on a user's account, appended to and never changed
+CREATE TABLE audit_log(
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    -- no reference to user_preference, the trail outlives the user
+    user_id BLOB NOT NULL,
+    action TEXT NOT NULL,
+    outcome TEXT NOT NULL CHECK (outcome IN ('success', 'failure', 'deferred')),
+    target TEXT,
+    -- JSON
+    detail TEXT,
+    request_id TEXT NOT NULL,
+    -- written by twitar as RFC 3339, so ranges compare the same way they are bound
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX audit_log_user_created_at ON audit_log (user_id, created_at);
+
+CREATE TRIGGER audit_log_no_update BEFORE UPDATE ON audit_log
+BEGIN
+    SELECT RAISE(ABORT, 'audit_log is append-only, UPDATE is not allowed');
+END;
+
+CREATE TRIGGER audit_log_no_delete BEFORE DELETE ON audit_log
+BEGIN
+    SELECT RAISE(ABORT, 'audit_log is append-only, DELETE is not allowed');
+END;
@@ -0,0 +1,17 @@
+-- what a tweet, retweet or like held right before it was removed, kept once Twitter no longer has it
+CREATE TABLE tweet_archive(
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id BLOB NOT NULL REFERENCES user_preference(user_id) ON DELETE CASCADE,
+    tweet_id TEXT NOT NULL,
+    tweet_type TEXT NOT NULL CHECK (tweet_type IN ('rts', 'likes', 'tweets')),
+    text TEXT NOT NULL,
+    author_id TEXT,
+    -- JSON
+    entities TEXT,
+    metrics TEXT,
+    media TEXT,
+    referenced_tweets TEXT,
+    created_at TEXT,
+    archived_at TEXT NOT NULL,
+    UNIQUE (user_id, tweet_id, tweet_type)
+);
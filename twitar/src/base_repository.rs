@@ -1,2 +1,6 @@
-pub mod commons;
-pub mod db;
\ No newline at end of file
+pub mod db;
+pub mod memory;
+pub mod postgres;
+pub mod repository;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
@@ -1,21 +1,24 @@
-use anyhow::Context;
-use sqlx::{Error::RowNotFound, Pool, Postgres, Transaction};
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{Error::RowNotFound, Pool, Postgres, Row};
 use uuid::Uuid;
-use crate::helpers::db_helper::{AllTweetIds, TweetIds, TweetType};
-use crate::helpers::{response::TResult};
+use crate::helpers::{archive::ArchivedTweet, audit::{AuditEntry, AuditEvent, AuditQuery}, credentials::CredentialCache, db_helper::{PlayStatus, PlayTweet, TweetType}};
+use crate::startup::server::CurrentUser;
+use crate::helpers::{metrics::observe_query, response::TResult, telemetry::REDACTED};
 use crate::errors::response::TError;
 
 #[derive(Debug)]
 pub struct DB;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuthUser {
     pub user_id: Uuid,
     pub v1_active: bool,
     pub v2_active: bool,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct V2User {
     pub id: i32,
     pub user_id: Uuid,
@@ -25,7 +28,7 @@ pub struct V2User {
     pub refresh_token: Option<String>,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct V1User {
     pub id: i32,
     pub user_id: Uuid,
@@ -35,173 +38,431 @@ pub struct V1User {
     pub oauth_verifier: Option<String>,
 }
 
-
-impl DB {
-    pub async fn add_v2_user(pool: &Pool<Postgres>, user_id: Uuid) {
-        let user = sqlx::query!(r#"INSERT INTO auth_two (user_id) VALUES ($1) RETURNING user_id"#, user_id).fetch_one(pool).await;
-
-        if let Err(e) = user {
-            println!("THE EXPERIENCED ERROR {:#?}", e);
-        }
-        // 
+// the credentials are left out, so a user can be logged as it is
+impl fmt::Debug for V2User {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("V2User")
+            .field("id", &self.id)
+            .field("user_id", &self.user_id)
+            .field("twitter_user_id", &self.twitter_user_id)
+            .field("pkce", &self.pkce.as_ref().map(|_| REDACTED))
+            .field("access_token", &self.access_token.as_ref().map(|_| REDACTED))
+            .field("refresh_token", &self.refresh_token.as_ref().map(|_| REDACTED))
+            .finish()
     }
+}
 
-    pub async fn add_v1_user(pool: &Pool<Postgres>, user_id: Uuid) {
-        let user = sqlx::query!(
-            r#"INSERT INTO auth_one (user_id) VALUES ($1) RETURNING user_id"#, user_id)
-            .fetch_one(pool).await;
-
-        if let Err(_e) = user {
-            // 
-        }
-        // 
+impl fmt::Debug for V1User {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("V1User")
+            .field("id", &self.id)
+            .field("user_id", &self.user_id)
+            .field("twitter_user_id", &self.twitter_user_id)
+            .field("oauth_token", &REDACTED)
+            .field("oauth_secret", &REDACTED)
+            .field("oauth_verifier", &self.oauth_verifier.as_ref().map(|_| REDACTED))
+            .finish()
     }
+}
 
-    pub async fn update_pkce(pool: &Pool<Postgres>, pkce: &str, user_id: Uuid) -> TResult<()> {
-        sqlx::query(r#"UPDATE auth_two SET pkce=$1 WHERE user_id=$2 RETURNING *"#)
-            .bind(pkce)
-            .bind(user_id)
-            .execute(&*pool).await?;
 
-        Ok(())
+impl DB {
+    pub async fn add_v2_user(pool: &Pool<Postgres>, user_id: Uuid) -> TResult<()> {
+        observe_query("add_v2_user", async {
+            sqlx::query!(r#"INSERT INTO auth_two (user_id) VALUES ($1) RETURNING user_id"#, user_id).fetch_one(pool).await?;
+
+            Ok(())
+        }).await
     }
 
-    pub async fn insert_tweet_ids<'a>(pool: &Pool<Postgres>, user_id: Uuid, all_tweets: AllTweetIds<'a>) -> TResult<()> {
-        let mut transaction = pool.begin().await.context("Unable to acquire db pool connection")?;
+    pub async fn add_v1_user(pool: &Pool<Postgres>, user_id: Uuid) -> TResult<()> {
+        observe_query("add_v1_user", async {
+            sqlx::query!(r#"INSERT INTO auth_one (user_id) VALUES ($1) RETURNING user_id"#, user_id).fetch_one(pool).await?;
 
-        let original_tweets = all_tweets.get_tweets();
-        DB::save_ids(&mut transaction, original_tweets, user_id, TweetType::Tweets).await?;
-        let likes = all_tweets.get_likes();
-        DB::save_ids(&mut transaction, likes, user_id, TweetType::Likes).await?;
-        let rts = all_tweets.get_rts();
-        DB::save_ids(&mut transaction, rts, user_id, TweetType::Rts).await?;
+            Ok(())
+        }).await
+    }
 
-        transaction.commit().await.context("Failed to commit SQL transaction to save all tweet ids")?;
+    pub async fn update_pkce(pool: &Pool<Postgres>, cache: &CredentialCache, pkce: &str, user_id: Uuid) -> TResult<()> {
+        observe_query("update_pkce", async {
+            sqlx::query(r#"UPDATE auth_two SET pkce=$1 WHERE user_id=$2 RETURNING *"#)
+                .bind(pkce)
+                .bind(user_id)
+                .execute(&*pool).await?;
 
+            cache.invalidate(user_id).await;
+            Ok(())
+        }).await
+    }
 
-        Ok(())
+    /// Queues the items for removal. An item already queued keeps its row, takes the metadata it was given
+    /// and is pending again unless it was removed, so syncing or requeueing twice changes nothing
+    pub async fn insert_tweet_ids(pool: &Pool<Postgres>, user_id: Uuid, items: &[PlayTweet]) -> TResult<()> {
+        observe_query("insert_tweet_ids", async {
+            // jsonb[] has no binding, the metrics are sent as text and cast back
+            let tweet_ids = items.iter().map(|item| item.tweet_id.as_str()).collect::<Vec<_>>();
+            let tweet_types = items.iter().map(|item| item.tweet_type.to_string()).collect::<Vec<_>>();
+            let created_at = items.iter().map(|item| item.created_at).collect::<Vec<_>>();
+            let text_hashes = items.iter().map(|item| item.text_hash.as_deref()).collect::<Vec<_>>();
+            let source_tweet_ids = items.iter().map(|item| item.source_tweet_id.as_deref()).collect::<Vec<_>>();
+            let metrics = items.iter().map(|item| item.metrics.as_ref().map(Value::to_string)).collect::<Vec<_>>();
+
+            // an item given twice would be updated twice by the same statement, the last one given is kept
+            sqlx::query(
+                r#"INSERT INTO play_tweets (user_id, tweet_id, tweet_type, created_at, text_hash, source_tweet_id, metrics)
+                SELECT DISTINCT ON (tweet_id, tweet_type) $1, tweet_id, tweet_type::tweet_type, created_at, text_hash, source_tweet_id, metrics::jsonb
+                FROM UNNEST($2::text[], $3::text[], $4::timestamptz[], $5::text[], $6::text[], $7::text[])
+                    WITH ORDINALITY AS items(tweet_id, tweet_type, created_at, text_hash, source_tweet_id, metrics, position)
+                ORDER BY tweet_id, tweet_type, position DESC
+                ON CONFLICT (user_id, tweet_id, tweet_type) DO UPDATE SET
+                    created_at = COALESCE(EXCLUDED.created_at, play_tweets.created_at),
+                    text_hash = COALESCE(EXCLUDED.text_hash, play_tweets.text_hash),
+                    source_tweet_id = COALESCE(EXCLUDED.source_tweet_id, play_tweets.source_tweet_id),
+                    metrics = COALESCE(EXCLUDED.metrics, play_tweets.metrics),
+                    status = CASE WHEN play_tweets.status = 'done' THEN play_tweets.status ELSE 'pending' END,
+                    updated_at = now()"#
+            )
+                .bind(user_id)
+                .bind(tweet_ids)
+                .bind(tweet_types)
+                .bind(created_at)
+                .bind(text_hashes)
+                .bind(source_tweet_ids)
+                .bind(metrics)
+                .execute(pool).await?;
+
+            Ok(())
+        }).await
     }
 
-    async fn save_ids<'a>(transaction: &mut Transaction<'_, Postgres>, ids: &TweetIds<'a>, user_id: Uuid, tweet_type: TweetType) -> TResult<()>{
-        for id_vec in ids {
-            let the_ids: Vec<&str> = id_vec.iter().map( |x| {x.as_str()}).collect();
-            sqlx::query(r#"INSERT INTO play_tweets (user_id, tweet_type, tweet_ids) VALUES ($1, $2, $3)"#)
+    /// Moves queued items along, e.g. to `done` once Twitter removed them. Ids that were never queued are left out
+    pub async fn set_status(pool: &Pool<Postgres>, user_id: Uuid, ids: &[(String, TweetType)], status: PlayStatus, error: Option<&str>) -> TResult<()> {
+        observe_query("set_status", async {
+            let (tweet_ids, tweet_types): (Vec<&str>, Vec<String>) = ids.iter().map(|(id, t)| (id.as_str(), t.to_string())).unzip();
+
+            sqlx::query(
+                r#"UPDATE play_tweets SET status = $1, last_error = $2, updated_at = now()
+                FROM UNNEST($3::text[], $4::text[]) AS items(tweet_id, tweet_type)
+                WHERE play_tweets.user_id = $5 AND play_tweets.tweet_id = items.tweet_id AND play_tweets.tweet_type = items.tweet_type::tweet_type"#
+            )
+                .bind(status)
+                .bind(error)
+                .bind(tweet_ids)
+                .bind(tweet_types)
                 .bind(user_id)
-                .bind(tweet_type.to_string())
-                .bind(the_ids)
-                .execute(&mut *transaction).await?;
-        }
+                .execute(pool).await?;
 
-        Ok(())
+            Ok(())
+        }).await
     }
 
     pub async fn user_exists(pool: &Pool<Postgres>, user_id: Uuid) -> TResult<Option<AuthUser>> {
-        let user = sqlx::query_as!(
-            AuthUser, 
-            r#"SELECT * FROM user_preference WHERE (user_id = $1)"#, user_id
-        )
-            .fetch_one(pool)
-            .await;
-
-        if let Err(e) = user {
-            return match e {
-                RowNotFound => Ok(None),
-                _ => {Err(TError::DatabaseError(e))}
+        observe_query("user_exists", async {
+            let user = sqlx::query_as!(
+                AuthUser, 
+                r#"SELECT * FROM user_preference WHERE (user_id = $1)"#, user_id
+            )
+                .fetch_one(pool)
+                .await;
+
+            if let Err(e) = user {
+                return match e {
+                    RowNotFound => Ok(None),
+                    _ => {Err(TError::DatabaseError(e))}
+                }
             }
-        }
 
-        Ok(Some(user.unwrap()))
+            Ok(Some(user.unwrap()))
+        }).await
     }
 
     pub async fn v2_user(pool: &Pool<Postgres>, user_id: Uuid) -> TResult<Option<V2User>> {
-        let user = sqlx::query_as!(
-            V2User, 
-            r#"SELECT * FROM auth_two WHERE (user_id = $1)"#, user_id
-        )
-            .fetch_one(pool)
-            .await;
-
-        if let Err(e) = user {
-            return match e {
-                RowNotFound => Ok(None),
-                _ => {Err(TError::DatabaseError(e))}
+        observe_query("v2_user", async {
+            let user = sqlx::query_as!(
+                V2User, 
+                r#"SELECT * FROM auth_two WHERE (user_id = $1)"#, user_id
+            )
+                .fetch_one(pool)
+                .await;
+
+            if let Err(e) = user {
+                return match e {
+                    RowNotFound => Ok(None),
+                    _ => {Err(TError::DatabaseError(e))}
+                }
             }
-        }
 
-        Ok(Some(user.unwrap()))
+            Ok(Some(user.unwrap()))
+        }).await
     }
 
      pub async fn v1_user(pool: &Pool<Postgres>, user_id: Uuid) -> TResult<Option<V1User>> {
-         let user = sqlx::query_as!(
-             V1User, 
-             r#"SELECT * FROM auth_one WHERE (user_id = $1)"#, user_id
-            ).fetch_one(pool).await;
-
-        if let Err(e) = user {
-            println!("THERE WAS AN ERROR::::::: {:#?}", e);
-            return match e {
-                RowNotFound => Ok(None),
-                _ => {Err(TError::DatabaseError(e))}
+        observe_query("v1_user", async {
+             let user = sqlx::query_as!(
+                 V1User, 
+                 r#"SELECT * FROM auth_one WHERE (user_id = $1)"#, user_id
+                ).fetch_one(pool).await;
+
+            if let Err(e) = user {
+                return match e {
+                    RowNotFound => Ok(None),
+                    _ => {Err(TError::DatabaseError(e))}
+                }
             }
-        }
 
-        println!("THE V1 USER TO OBTAIN:::::::::::::::::::::::::::::::::::___________________::::::::::::::::::::::::::::::::::: {:#?}", user);
+            Ok(Some(user.unwrap()))
+        }).await
+    }
 
-        Ok(Some(user.unwrap()))
+    /// The user with both sets of credentials in one round-trip, for `auth_middleware`
+    pub async fn current_user(pool: &Pool<Postgres>, user_id: Uuid) -> TResult<Option<CurrentUser>> {
+        observe_query("current_user", async {
+            let row = sqlx::query(
+                r#"SELECT p.v1_active, p.v2_active,
+                    one.id AS one_id, one.twitter_user_id AS one_twitter_user_id, one.oauth_token, one.oauth_secret, one.oauth_verifier,
+                    two.id AS two_id, two.twitter_user_id AS two_twitter_user_id, two.pkce, two.access_token, two.refresh_token
+                FROM user_preference p
+                JOIN auth_one one ON one.user_id = p.user_id
+                JOIN auth_two two ON two.user_id = p.user_id
+                WHERE p.user_id = $1"#
+            )
+                .bind(user_id)
+                .fetch_optional(pool).await?;
+
+            let row = match row {
+                Some(row) => row,
+                None => return Ok(None),
+            };
+
+            let basic = AuthUser { user_id, v1_active: row.try_get("v1_active")?, v2_active: row.try_get("v2_active")? };
+
+            let v1_user = V1User {
+                id: row.try_get("one_id")?,
+                user_id,
+                twitter_user_id: row.try_get("one_twitter_user_id")?,
+                oauth_token: row.try_get("oauth_token")?,
+                oauth_secret: row.try_get("oauth_secret")?,
+                oauth_verifier: row.try_get("oauth_verifier")?,
+            };
+
+            let v2_user = V2User {
+                id: row.try_get("two_id")?,
+                user_id,
+                twitter_user_id: row.try_get("two_twitter_user_id")?,
+                pkce: row.try_get("pkce")?,
+                access_token: row.try_get("access_token")?,
+                refresh_token: row.try_get("refresh_token")?,
+            };
+
+            Ok(Some(CurrentUser::new(basic, v1_user, v2_user)))
+        }).await
     }
 
-    pub async fn update_secets(pool: &Pool<Postgres>, access_token: String, refresh_token: String, user_id: Uuid) -> TResult<()> {
-        sqlx::query(r#"UPDATE auth_two SET access_token=$1, refresh_token=$2 WHERE user_id=$3 RETURNING *"#)
-            .bind(access_token)
-            .bind(refresh_token)
-            .bind(user_id)
-            .execute(&*pool).await?;
+    pub async fn update_secets(pool: &Pool<Postgres>, cache: &CredentialCache, access_token: String, refresh_token: String, user_id: Uuid) -> TResult<()> {
+        observe_query("update_secets", async {
+            sqlx::query(r#"UPDATE auth_two SET access_token=$1, refresh_token=$2 WHERE user_id=$3 RETURNING *"#)
+                .bind(access_token)
+                .bind(refresh_token)
+                .bind(user_id)
+                .execute(&*pool).await?;
 
-        Ok(())
+            cache.invalidate(user_id).await;
+            Ok(())
+        }).await
     }
 
-    pub async fn update_v1_secets(pool: &Pool<Postgres>, oauth_token: String, oauth_secret: String, user_id: Uuid) -> TResult<()> {
-        sqlx::query(r#"UPDATE auth_one SET oauth_token=$1, oauth_secret=$2 WHERE user_id=$3 RETURNING *"#)
-            .bind(oauth_token)
-            .bind(oauth_secret)
-            .bind(user_id)
-            .execute(&*pool).await?;
+    pub async fn update_v1_secets(pool: &Pool<Postgres>, cache: &CredentialCache, oauth_token: String, oauth_secret: String, user_id: Uuid) -> TResult<()> {
+        observe_query("update_v1_secets", async {
+            sqlx::query(r#"UPDATE auth_one SET oauth_token=$1, oauth_secret=$2 WHERE user_id=$3 RETURNING *"#)
+                .bind(oauth_token)
+                .bind(oauth_secret)
+                .bind(user_id)
+                .execute(&*pool).await?;
 
-        Ok(())
+            cache.invalidate(user_id).await;
+            Ok(())
+        }).await
     }
 
 
-    pub async fn add_oauth_verifier(pool: &Pool<Postgres>, oauth_verifier: &str, user_id: Uuid) -> TResult<()>{
-        sqlx::query(r#"UPDATE auth_one SET oauth_verifier=$1 WHERE user_id=$2 RETURNING *"#)
-            .bind(oauth_verifier)
-            .bind(user_id)
-            .execute(&*pool).await?;
+    pub async fn add_oauth_verifier(pool: &Pool<Postgres>, cache: &CredentialCache, oauth_verifier: &str, user_id: Uuid) -> TResult<()>{
+        observe_query("add_oauth_verifier", async {
+            sqlx::query(r#"UPDATE auth_one SET oauth_verifier=$1 WHERE user_id=$2 RETURNING *"#)
+                .bind(oauth_verifier)
+                .bind(user_id)
+                .execute(&*pool).await?;
 
-        Ok(())
+            cache.invalidate(user_id).await;
+            Ok(())
+        }).await
     }
 
-     pub async fn create_v1_secets(pool: &Pool<Postgres>, user_id: Uuid, oauth_token: String, oauth_secret: String) -> TResult<()> {
-         if DB::v1_user(pool, user_id).await?.is_some() {
-             sqlx::query(r#"DELETE FROM auth_one WHERE user_id=$1"#).bind(user_id).execute(&*pool).await?;
-         }
+     pub async fn create_v1_secets(pool: &Pool<Postgres>, cache: &CredentialCache, user_id: Uuid, oauth_token: String, oauth_secret: String) -> TResult<()> {
+        observe_query("create_v1_secets", async {
+             if DB::v1_user(pool, user_id).await?.is_some() {
+                 sqlx::query(r#"DELETE FROM auth_one WHERE user_id=$1"#).bind(user_id).execute(&*pool).await?;
+             }
 
-        sqlx::query(r#"INSERT INTO auth_one (user_id, oauth_token, oauth_secret) VALUES ($1, $2, $3) RETURNING user_id"#)
-            .bind(user_id)
-            .bind(oauth_token)
-            .bind(oauth_secret)
-            .execute(&*pool).await?;
+            sqlx::query(r#"INSERT INTO auth_one (user_id, oauth_token, oauth_secret) VALUES ($1, $2, $3) RETURNING user_id"#)
+                .bind(user_id)
+                .bind(oauth_token)
+                .bind(oauth_secret)
+                .execute(&*pool).await?;
 
-        Ok(())
+            cache.invalidate(user_id).await;
+            Ok(())
+        }).await
     }
 
     // let user = sqlx::query!(r#"INSERT INTO auth_two (user_id) VALUES ($1) RETURNING user_id"#, user_id).fetch_one(pool).await;
 
-    pub async fn update_twitter_id(pool: &Pool<Postgres>, twitter_user_id: &str, user_id: Uuid) -> TResult<()> {
-        sqlx::query(r#"UPDATE auth_two SET twitter_user_id=$1 WHERE user_id=$2"#)
-            .bind(twitter_user_id)
-            .bind(user_id)
-            .execute(&*pool).await?;
-            
-        Ok(())
+    pub async fn update_twitter_id(pool: &Pool<Postgres>, cache: &CredentialCache, twitter_user_id: &str, user_id: Uuid) -> TResult<()> {
+        observe_query("update_twitter_id", async {
+            sqlx::query(r#"UPDATE auth_two SET twitter_user_id=$1 WHERE user_id=$2"#)
+                .bind(twitter_user_id)
+                .bind(user_id)
+                .execute(&*pool).await?;
+
+            cache.invalidate(user_id).await;
+            Ok(())
+        }).await
+    }
+
+    /// Keeps what the items held before they are removed. Archiving an item again replaces its snapshot
+    pub async fn archive_tweets(pool: &Pool<Postgres>, user_id: Uuid, items: &[ArchivedTweet]) -> TResult<()> {
+        observe_query("archive_tweets", async {
+            // as in `insert_tweet_ids`, the json values are sent as text
+            let tweet_ids = items.iter().map(|item| item.tweet_id.as_str()).collect::<Vec<_>>();
+            let tweet_types = items.iter().map(|item| item.tweet_type.to_string()).collect::<Vec<_>>();
+            let texts = items.iter().map(|item| item.text.as_str()).collect::<Vec<_>>();
+            let author_ids = items.iter().map(|item| item.author_id.as_deref()).collect::<Vec<_>>();
+            let entities = items.iter().map(|item| item.entities.as_ref().map(Value::to_string)).collect::<Vec<_>>();
+            let metrics = items.iter().map(|item| item.metrics.as_ref().map(Value::to_string)).collect::<Vec<_>>();
+            let media = items.iter().map(|item| item.media.as_ref().map(Value::to_string)).collect::<Vec<_>>();
+            let referenced_tweets = items.iter().map(|item| item.referenced_tweets.as_ref().map(Value::to_string)).collect::<Vec<_>>();
+            let created_at = items.iter().map(|item| item.created_at).collect::<Vec<_>>();
+            let archived_at = items.iter().map(|item| item.archived_at).collect::<Vec<_>>();
+
+            sqlx::query(
+                r#"INSERT INTO tweet_archive (user_id, tweet_id, tweet_type, text, author_id, entities, metrics, media, referenced_tweets, created_at, archived_at)
+                SELECT DISTINCT ON (tweet_id, tweet_type) $1, tweet_id, tweet_type::tweet_type, text, author_id, entities::jsonb, metrics::jsonb, media::jsonb, referenced_tweets::jsonb, created_at, archived_at
+                FROM UNNEST($2::text[], $3::text[], $4::text[], $5::text[], $6::text[], $7::text[], $8::text[], $9::text[], $10::timestamptz[], $11::timestamptz[])
+                    WITH ORDINALITY AS items(tweet_id, tweet_type, text, author_id, entities, metrics, media, referenced_tweets, created_at, archived_at, position)
+                ORDER BY tweet_id, tweet_type, position DESC
+                ON CONFLICT (user_id, tweet_id, tweet_type) DO UPDATE SET
+                    text = EXCLUDED.text, author_id = EXCLUDED.author_id, entities = EXCLUDED.entities, metrics = EXCLUDED.metrics,
+                    media = EXCLUDED.media, referenced_tweets = EXCLUDED.referenced_tweets, created_at = EXCLUDED.created_at,
+                    archived_at = EXCLUDED.archived_at"#
+            )
+                .bind(user_id)
+                .bind(tweet_ids)
+                .bind(tweet_types)
+                .bind(texts)
+                .bind(author_ids)
+                .bind(entities)
+                .bind(metrics)
+                .bind(media)
+                .bind(referenced_tweets)
+                .bind(created_at)
+                .bind(archived_at)
+                .execute(pool).await?;
+
+            Ok(())
+        }).await
     }
-}
\ No newline at end of file
+
+    pub async fn archived_tweets(pool: &Pool<Postgres>, user_id: Uuid) -> TResult<Vec<ArchivedTweet>> {
+        observe_query("archived_tweets", async {
+            let rows = sqlx::query("SELECT * FROM tweet_archive WHERE user_id = $1 ORDER BY id")
+                .bind(user_id)
+                .fetch_all(pool).await?;
+
+            rows.iter().map(|row| {
+                let item = ArchivedTweet {
+                    tweet_id: row.try_get("tweet_id")?,
+                    tweet_type: row.try_get("tweet_type")?,
+                    text: row.try_get("text")?,
+                    author_id: row.try_get("author_id")?,
+                    entities: row.try_get("entities")?,
+                    metrics: row.try_get("metrics")?,
+                    media: row.try_get("media")?,
+                    referenced_tweets: row.try_get("referenced_tweets")?,
+                    created_at: row.try_get("created_at")?,
+                    archived_at: row.try_get("archived_at")?,
+                };
+
+                Ok(item)
+            }).collect()
+        }).await
+    }
+
+    /// Appends the events to `audit_log` in a single statement, so a removal run is logged whole or not at all.
+    /// Their ids follow the order they were given in, which is the order pages are read in
+    pub async fn record_audit(pool: &Pool<Postgres>, events: &[AuditEvent]) -> TResult<()> {
+        observe_query("record_audit", async {
+            let user_ids = events.iter().map(|event| event.user_id).collect::<Vec<_>>();
+            let actions = events.iter().map(|event| event.action.to_string()).collect::<Vec<_>>();
+            let outcomes = events.iter().map(|event| event.outcome.to_string()).collect::<Vec<_>>();
+            let targets = events.iter().map(|event| event.target.as_deref()).collect::<Vec<_>>();
+            // as in `insert_tweet_ids`, the json values are sent as text
+            let details = events.iter().map(|event| event.detail.as_ref().map(Value::to_string)).collect::<Vec<_>>();
+            let request_ids = events.iter().map(|event| event.request_id.as_str()).collect::<Vec<_>>();
+
+            sqlx::query(
+                r#"INSERT INTO audit_log (user_id, action, outcome, target, detail, request_id)
+                SELECT user_id, action, outcome, target, detail::jsonb, request_id
+                FROM UNNEST($1::uuid[], $2::text[], $3::text[], $4::text[], $5::text[], $6::text[])
+                    WITH ORDINALITY AS events(user_id, action, outcome, target, detail, request_id, position)
+                ORDER BY position"#
+            )
+                .bind(user_ids)
+                .bind(actions)
+                .bind(outcomes)
+                .bind(targets)
+                .bind(details)
+                .bind(request_ids)
+                .execute(pool).await?;
+
+            Ok(())
+        }).await
+    }
+
+    /// The entries `query` matches, ordered by `id` so a page ends where the next one starts
+    pub async fn audit_log(pool: &Pool<Postgres>, query: &AuditQuery) -> TResult<Vec<AuditEntry>> {
+        observe_query("audit_log", async {
+            let actions = query.actions.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+
+            let rows = sqlx::query(
+                r#"SELECT * FROM audit_log
+                WHERE user_id = $1
+                    AND (cardinality($2::text[]) = 0 OR action = ANY($2))
+                    AND ($3::timestamptz IS NULL OR created_at >= $3)
+                    AND ($4::timestamptz IS NULL OR created_at < $4)
+                    AND ($5::bigint IS NULL OR id > $5)
+                ORDER BY id LIMIT $6"#
+            )
+                .bind(query.user_id)
+                .bind(actions)
+                .bind(query.from)
+                .bind(query.to)
+                .bind(query.after)
+                .bind(query.limit)
+                .fetch_all(pool).await?;
+
+            rows.iter().map(|row| {
+                let entry = AuditEntry {
+                    id: row.try_get("id")?,
+                    user_id: row.try_get("user_id")?,
+                    action: row.try_get::<String, _>("action")?.parse().map_err(|e: String| anyhow::anyhow!(e))?,
+                    outcome: row.try_get::<String, _>("outcome")?.parse().map_err(|e: String| anyhow::anyhow!(e))?,
+                    target: row.try_get("target")?,
+                    detail: row.try_get("detail")?,
+                    request_id: row.try_get("request_id")?,
+                    created_at: row.try_get("created_at")?,
+                };
+
+                Ok(entry)
+            }).collect()
+        }).await
+    }
+}
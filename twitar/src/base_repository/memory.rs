@@ -0,0 +1,277 @@
+use std::{collections::HashMap, sync::{Arc, Mutex}};
+use async_trait::async_trait;
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::base_repository::db::{AuthUser, V1User, V2User};
+use crate::base_repository::repository::{ArchiveRepository, AuditRepository, CredentialRepository, TweetRepository, UserRepository};
+use crate::helpers::{archive::ArchivedTweet, audit::{AuditEntry, AuditEvent, AuditPage, AuditQuery}, db_helper::{PlayStatus, PlayTweet, TweetType}, response::TResult};
+use crate::startup::server::CurrentUser;
+
+#[cfg(test)]
+#[path = "./memory.test.rs"]
+mod memory_test;
+
+
+/// A row of `play_tweets`
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueuedTweet {
+    pub user_id: Uuid,
+    pub item: PlayTweet,
+    pub status: PlayStatus,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct Tables {
+    users: HashMap<Uuid, AuthUser>,
+    v1_users: HashMap<Uuid, V1User>,
+    v2_users: HashMap<Uuid, V2User>,
+    tweets: Vec<QueuedTweet>,
+    archive: Vec<(Uuid, ArchivedTweet)>,
+    audit_log: Vec<AuditEntry>,
+    /// The last `id` given to a row of `auth_one` or `auth_two`
+    last_id: i32,
+}
+
+impl Tables {
+    fn next_id(&mut self) -> i32 {
+        self.last_id += 1;
+        self.last_id
+    }
+}
+
+
+/// The same repositories as `PgStore`, within this process. Nothing survives a restart
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    tables: Arc<Mutex<Tables>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a user who went through both OAuth flows, as the OAuth controllers would have
+    pub fn add_user(&self, user: CurrentUser) {
+        let mut tables = self.tables.lock().unwrap();
+        let user_id = user.basic.user_id;
+
+        tables.users.insert(user_id, user.basic);
+        tables.v1_users.insert(user_id, user.v1_user);
+        tables.v2_users.insert(user_id, user.v2_user);
+    }
+
+    /// The items queued for the user, in the order they were first queued
+    pub fn queued(&self, user_id: Uuid) -> Vec<QueuedTweet> {
+        self.tables.lock().unwrap().tweets.iter().filter(|t| t.user_id == user_id).cloned().collect()
+    }
+}
+
+
+#[async_trait]
+impl UserRepository for MemoryStore {
+    async fn add_v1_user(&self, user_id: Uuid) -> TResult<()> {
+        let mut tables = self.tables.lock().unwrap();
+        let id = tables.next_id();
+
+        tables.v1_users.entry(user_id).or_insert(V1User {
+            id, user_id, twitter_user_id: None, oauth_token: String::new(), oauth_secret: String::new(), oauth_verifier: None,
+        });
+
+        Ok(())
+    }
+
+    async fn add_v2_user(&self, user_id: Uuid) -> TResult<()> {
+        let mut tables = self.tables.lock().unwrap();
+        let id = tables.next_id();
+
+        tables.v2_users.entry(user_id).or_insert(V2User {
+            id, user_id, twitter_user_id: None, pkce: None, access_token: None, refresh_token: None,
+        });
+
+        Ok(())
+    }
+
+    async fn user_exists(&self, user_id: Uuid) -> TResult<Option<AuthUser>> {
+        Ok(self.tables.lock().unwrap().users.get(&user_id).cloned())
+    }
+
+    async fn current_user(&self, user_id: Uuid) -> TResult<Option<CurrentUser>> {
+        let tables = self.tables.lock().unwrap();
+
+        let user = match (tables.users.get(&user_id), tables.v1_users.get(&user_id), tables.v2_users.get(&user_id)) {
+            (Some(basic), Some(v1_user), Some(v2_user)) => Some(CurrentUser::new(basic.clone(), v1_user.clone(), v2_user.clone())),
+            _ => None,
+        };
+
+        Ok(user)
+    }
+}
+
+
+#[async_trait]
+impl CredentialRepository for MemoryStore {
+    async fn v1_user(&self, user_id: Uuid) -> TResult<Option<V1User>> {
+        Ok(self.tables.lock().unwrap().v1_users.get(&user_id).cloned())
+    }
+
+    async fn v2_user(&self, user_id: Uuid) -> TResult<Option<V2User>> {
+        Ok(self.tables.lock().unwrap().v2_users.get(&user_id).cloned())
+    }
+
+    async fn update_pkce(&self, pkce: &str, user_id: Uuid) -> TResult<()> {
+        if let Some(user) = self.tables.lock().unwrap().v2_users.get_mut(&user_id) {
+            user.pkce = Some(pkce.to_string());
+        }
+
+        Ok(())
+    }
+
+    async fn update_secets(&self, access_token: String, refresh_token: String, user_id: Uuid) -> TResult<()> {
+        if let Some(user) = self.tables.lock().unwrap().v2_users.get_mut(&user_id) {
+            user.access_token = Some(access_token);
+            user.refresh_token = Some(refresh_token);
+        }
+
+        Ok(())
+    }
+
+    async fn update_v1_secets(&self, oauth_token: String, oauth_secret: String, user_id: Uuid) -> TResult<()> {
+        if let Some(user) = self.tables.lock().unwrap().v1_users.get_mut(&user_id) {
+            user.oauth_token = oauth_token;
+            user.oauth_secret = oauth_secret;
+        }
+
+        Ok(())
+    }
+
+    async fn add_oauth_verifier(&self, oauth_verifier: &str, user_id: Uuid) -> TResult<()> {
+        if let Some(user) = self.tables.lock().unwrap().v1_users.get_mut(&user_id) {
+            user.oauth_verifier = Some(oauth_verifier.to_string());
+        }
+
+        Ok(())
+    }
+
+    async fn create_v1_secets(&self, user_id: Uuid, oauth_token: String, oauth_secret: String) -> TResult<()> {
+        let mut tables = self.tables.lock().unwrap();
+        let id = tables.next_id();
+
+        tables.v1_users.insert(user_id, V1User { id, user_id, twitter_user_id: None, oauth_token, oauth_secret, oauth_verifier: None });
+        Ok(())
+    }
+
+    async fn update_twitter_id(&self, twitter_user_id: &str, user_id: Uuid) -> TResult<()> {
+        if let Some(user) = self.tables.lock().unwrap().v2_users.get_mut(&user_id) {
+            user.twitter_user_id = Some(twitter_user_id.to_string());
+        }
+
+        Ok(())
+    }
+}
+
+
+#[async_trait]
+impl TweetRepository for MemoryStore {
+    async fn insert_tweet_ids(&self, user_id: Uuid, items: &[PlayTweet]) -> TResult<()> {
+        let mut tables = self.tables.lock().unwrap();
+
+        for item in items {
+            let queued = tables.tweets.iter_mut()
+                .find(|t| t.user_id == user_id && t.item.tweet_id == item.tweet_id && t.item.tweet_type == item.tweet_type);
+
+            match queued {
+                Some(queued) => {
+                    let known = &mut queued.item;
+                    known.created_at = item.created_at.or(known.created_at);
+                    known.text_hash = item.text_hash.clone().or_else(|| known.text_hash.take());
+                    known.source_tweet_id = item.source_tweet_id.clone().or_else(|| known.source_tweet_id.take());
+                    known.metrics = item.metrics.clone().or_else(|| known.metrics.take());
+
+                    if queued.status != PlayStatus::Done {
+                        queued.status = PlayStatus::Pending;
+                    }
+                }
+                None => tables.tweets.push(QueuedTweet { user_id, item: item.clone(), status: PlayStatus::Pending, last_error: None }),
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn set_status(&self, user_id: Uuid, ids: &[(String, TweetType)], status: PlayStatus, error: Option<&str>) -> TResult<()> {
+        let mut tables = self.tables.lock().unwrap();
+
+        for queued in tables.tweets.iter_mut().filter(|t| t.user_id == user_id) {
+            if ids.iter().any(|(id, tweet_type)| *id == queued.item.tweet_id && *tweet_type == queued.item.tweet_type) {
+                queued.status = status;
+                queued.last_error = error.map(|e| e.to_string());
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn pending_count(&self) -> TResult<i64> {
+        Ok(self.tables.lock().unwrap().tweets.iter().filter(|t| t.status == PlayStatus::Pending).count() as i64)
+    }
+}
+
+
+#[async_trait]
+impl ArchiveRepository for MemoryStore {
+    async fn archive(&self, user_id: Uuid, items: &[ArchivedTweet]) -> TResult<()> {
+        let mut tables = self.tables.lock().unwrap();
+
+        for item in items {
+            let archived = tables.archive.iter_mut()
+                .find(|(id, a)| *id == user_id && a.tweet_id == item.tweet_id && a.tweet_type == item.tweet_type);
+
+            match archived {
+                Some((_, archived)) => *archived = item.clone(),
+                None => tables.archive.push((user_id, item.clone())),
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn archived(&self, user_id: Uuid) -> TResult<Vec<ArchivedTweet>> {
+        let tables = self.tables.lock().unwrap();
+        Ok(tables.archive.iter().filter(|(id, _)| *id == user_id).map(|(_, item)| item.clone()).collect())
+    }
+}
+
+
+#[async_trait]
+impl AuditRepository for MemoryStore {
+    async fn record(&self, events: &[AuditEvent]) -> TResult<()> {
+        let mut tables = self.tables.lock().unwrap();
+
+        for event in events.iter().cloned() {
+            let id = tables.audit_log.len() as i64 + 1;
+
+            tables.audit_log.push(AuditEntry {
+                id,
+                user_id: event.user_id,
+                action: event.action,
+                outcome: event.outcome,
+                target: event.target,
+                detail: event.detail,
+                request_id: event.request_id,
+                created_at: Utc::now(),
+            });
+        }
+
+        Ok(())
+    }
+
+    async fn find(&self, query: &AuditQuery) -> TResult<AuditPage> {
+        let tables = self.tables.lock().unwrap();
+        let entries = tables.audit_log.iter().filter(|e| query.matches(e)).take(query.limit as usize).cloned().collect();
+
+        Ok(AuditPage::new(query, entries))
+    }
+}
@@ -0,0 +1,36 @@
+#[cfg(test)]
+mod test_memory {
+    use async_trait::async_trait;
+    use uuid::Uuid;
+
+    use crate::base_repository::db::{AuthUser, V1User, V2User};
+    use crate::base_repository::memory::MemoryStore;
+    use crate::base_repository::repository::repository_test::{repository_suite, Harness};
+    use crate::helpers::db_helper::{PlayStatus, PlayTweet};
+    use crate::startup::server::CurrentUser;
+
+    #[async_trait]
+    impl Harness for MemoryStore {
+        async fn with_user() -> (Self, Uuid) {
+            let store = MemoryStore::new();
+            let user_id = Uuid::new_v4();
+
+            let basic = AuthUser { user_id, v1_active: true, v2_active: true };
+            let v1_user = V1User {
+                id: 1, user_id, twitter_user_id: None, oauth_token: "oauth-token".into(), oauth_secret: "oauth-secret".into(), oauth_verifier: None,
+            };
+            let v2_user = V2User {
+                id: 2, user_id, twitter_user_id: None, pkce: None, access_token: Some("access".into()), refresh_token: Some("refresh".into()),
+            };
+            store.add_user(CurrentUser::new(basic, v1_user, v2_user));
+
+            (store, user_id)
+        }
+
+        async fn queued(&self, user_id: Uuid) -> Vec<(PlayTweet, PlayStatus, Option<String>)> {
+            MemoryStore::queued(self, user_id).into_iter().map(|queued| (queued.item, queued.status, queued.last_error)).collect()
+        }
+    }
+
+    repository_suite!(crate::base_repository::memory::MemoryStore);
+}
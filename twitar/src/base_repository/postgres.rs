@@ -0,0 +1,129 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::base_repository::db::{AuthUser, V1User, V2User, DB};
+use crate::base_repository::repository::{ArchiveRepository, AuditRepository, CredentialRepository, TweetRepository, UserRepository};
+use crate::helpers::{archive::ArchivedTweet, audit::{AuditEvent, AuditPage, AuditQuery}, credentials::CredentialCache, db_helper::{PlayStatus, PlayTweet, TweetType}, response::TResult};
+use crate::helpers::metrics::observe_query;
+use crate::startup::server::CurrentUser;
+
+
+/// The repositories over the `DB` queries. Users are read through the credential cache, which every credential write invalidates
+#[derive(Debug, Clone)]
+pub struct PgStore {
+    pool: PgPool,
+    cache: CredentialCache,
+}
+
+impl PgStore {
+    pub fn new(pool: PgPool, cache: CredentialCache) -> Self {
+        Self { pool, cache }
+    }
+}
+
+
+#[async_trait]
+impl UserRepository for PgStore {
+    async fn add_v1_user(&self, user_id: Uuid) -> TResult<()> {
+        DB::add_v1_user(&self.pool, user_id).await
+    }
+
+    async fn add_v2_user(&self, user_id: Uuid) -> TResult<()> {
+        DB::add_v2_user(&self.pool, user_id).await
+    }
+
+    async fn user_exists(&self, user_id: Uuid) -> TResult<Option<AuthUser>> {
+        DB::user_exists(&self.pool, user_id).await
+    }
+
+    async fn current_user(&self, user_id: Uuid) -> TResult<Option<CurrentUser>> {
+        self.cache.current_user(&self.pool, user_id).await
+    }
+}
+
+
+#[async_trait]
+impl CredentialRepository for PgStore {
+    async fn v1_user(&self, user_id: Uuid) -> TResult<Option<V1User>> {
+        DB::v1_user(&self.pool, user_id).await
+    }
+
+    async fn v2_user(&self, user_id: Uuid) -> TResult<Option<V2User>> {
+        DB::v2_user(&self.pool, user_id).await
+    }
+
+    async fn update_pkce(&self, pkce: &str, user_id: Uuid) -> TResult<()> {
+        DB::update_pkce(&self.pool, &self.cache, pkce, user_id).await
+    }
+
+    async fn update_secets(&self, access_token: String, refresh_token: String, user_id: Uuid) -> TResult<()> {
+        DB::update_secets(&self.pool, &self.cache, access_token, refresh_token, user_id).await
+    }
+
+    async fn update_v1_secets(&self, oauth_token: String, oauth_secret: String, user_id: Uuid) -> TResult<()> {
+        DB::update_v1_secets(&self.pool, &self.cache, oauth_token, oauth_secret, user_id).await
+    }
+
+    async fn add_oauth_verifier(&self, oauth_verifier: &str, user_id: Uuid) -> TResult<()> {
+        DB::add_oauth_verifier(&self.pool, &self.cache, oauth_verifier, user_id).await
+    }
+
+    async fn create_v1_secets(&self, user_id: Uuid, oauth_token: String, oauth_secret: String) -> TResult<()> {
+        DB::create_v1_secets(&self.pool, &self.cache, user_id, oauth_token, oauth_secret).await
+    }
+
+    async fn update_twitter_id(&self, twitter_user_id: &str, user_id: Uuid) -> TResult<()> {
+        DB::update_twitter_id(&self.pool, &self.cache, twitter_user_id, user_id).await
+    }
+
+    async fn invalidate(&self, user_id: Uuid) {
+        self.cache.invalidate(user_id).await
+    }
+}
+
+
+#[async_trait]
+impl TweetRepository for PgStore {
+    async fn insert_tweet_ids(&self, user_id: Uuid, items: &[PlayTweet]) -> TResult<()> {
+        DB::insert_tweet_ids(&self.pool, user_id, items).await
+    }
+
+    async fn set_status(&self, user_id: Uuid, ids: &[(String, TweetType)], status: PlayStatus, error: Option<&str>) -> TResult<()> {
+        DB::set_status(&self.pool, user_id, ids, status, error).await
+    }
+
+    async fn pending_count(&self) -> TResult<i64> {
+        observe_query("pending_count", async {
+            let count = sqlx::query_scalar("SELECT COUNT(*) FROM play_tweets WHERE status = 'pending'")
+                .fetch_one(&self.pool).await?;
+
+            Ok(count)
+        }).await
+    }
+}
+
+
+#[async_trait]
+impl ArchiveRepository for PgStore {
+    async fn archive(&self, user_id: Uuid, items: &[ArchivedTweet]) -> TResult<()> {
+        DB::archive_tweets(&self.pool, user_id, items).await
+    }
+
+    async fn archived(&self, user_id: Uuid) -> TResult<Vec<ArchivedTweet>> {
+        DB::archived_tweets(&self.pool, user_id).await
+    }
+}
+
+
+#[async_trait]
+impl AuditRepository for PgStore {
+    async fn record(&self, events: &[AuditEvent]) -> TResult<()> {
+        DB::record_audit(&self.pool, events).await
+    }
+
+    async fn find(&self, query: &AuditQuery) -> TResult<AuditPage> {
+        let entries = DB::audit_log(&self.pool, query).await?;
+        Ok(AuditPage::new(query, entries))
+    }
+}
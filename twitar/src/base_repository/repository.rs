@@ -0,0 +1,144 @@
+use std::{fmt, sync::Arc};
+use async_trait::async_trait;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::base_repository::db::{AuthUser, V1User, V2User};
+use crate::helpers::{archive::ArchivedTweet, audit::{AuditEvent, AuditPage, AuditQuery}, db_helper::{PlayStatus, PlayTweet, TweetType}, lease::{Job, Lease}, response::TResult};
+use crate::startup::server::CurrentUser;
+
+/// The behaviour every store owes the controllers, run against each of them with `repository_suite!`
+#[cfg(test)]
+#[path = "./repository.test.rs"]
+pub(crate) mod repository_test;
+
+
+/// Where twitar keeps its data: `postgres`, `memory` for tests and trying it out locally (nothing survives a restart),
+/// or `sqlite` for a single account, when built with the `sqlite` feature
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Storage {
+    #[default]
+    Postgres,
+    Memory,
+    #[cfg(feature = "sqlite")]
+    Sqlite,
+}
+
+
+#[async_trait]
+pub trait UserRepository: fmt::Debug + Send + Sync {
+    async fn add_v1_user(&self, user_id: Uuid) -> TResult<()>;
+
+    async fn add_v2_user(&self, user_id: Uuid) -> TResult<()>;
+
+    async fn user_exists(&self, user_id: Uuid) -> TResult<Option<AuthUser>>;
+
+    /// The user with both sets of credentials, for `auth_middleware`
+    async fn current_user(&self, user_id: Uuid) -> TResult<Option<CurrentUser>>;
+}
+
+
+/// The OAuth1 (`auth_one`) and OAuth2 (`auth_two`) credentials of users
+#[async_trait]
+pub trait CredentialRepository: fmt::Debug + Send + Sync {
+    async fn v1_user(&self, user_id: Uuid) -> TResult<Option<V1User>>;
+
+    async fn v2_user(&self, user_id: Uuid) -> TResult<Option<V2User>>;
+
+    async fn update_pkce(&self, pkce: &str, user_id: Uuid) -> TResult<()>;
+
+    async fn update_secets(&self, access_token: String, refresh_token: String, user_id: Uuid) -> TResult<()>;
+
+    async fn update_v1_secets(&self, oauth_token: String, oauth_secret: String, user_id: Uuid) -> TResult<()>;
+
+    async fn add_oauth_verifier(&self, oauth_verifier: &str, user_id: Uuid) -> TResult<()>;
+
+    /// Replaces the OAuth1 credentials of the user, when they start the flow again
+    async fn create_v1_secets(&self, user_id: Uuid, oauth_token: String, oauth_secret: String) -> TResult<()>;
+
+    async fn update_twitter_id(&self, twitter_user_id: &str, user_id: Uuid) -> TResult<()>;
+
+    /// Drops any copy of the credentials kept outside the store, after they changed on Twitter's side (e.g. a revoke)
+    async fn invalidate(&self, _user_id: Uuid) {}
+}
+
+
+/// The tweets, retweets and likes queued for removal (`play_tweets`)
+#[async_trait]
+pub trait TweetRepository: fmt::Debug + Send + Sync {
+    /// Queues the items, an item already queued is updated in place
+    async fn insert_tweet_ids(&self, user_id: Uuid, items: &[PlayTweet]) -> TResult<()>;
+
+    async fn set_status(&self, user_id: Uuid, ids: &[(String, TweetType)], status: PlayStatus, error: Option<&str>) -> TResult<()>;
+
+    /// Items of every user still waiting to be removed
+    async fn pending_count(&self) -> TResult<i64>;
+}
+
+
+/// What the removed items held (`tweet_archive`), snapshotted before Twitter is asked to remove them
+#[async_trait]
+pub trait ArchiveRepository: fmt::Debug + Send + Sync {
+    /// Keeps the snapshots, a newer snapshot of an item already archived replaces it
+    async fn archive(&self, user_id: Uuid, items: &[ArchivedTweet]) -> TResult<()>;
+
+    /// The archive of the user, in the order the items were first archived
+    async fn archived(&self, user_id: Uuid) -> TResult<Vec<ArchivedTweet>>;
+}
+
+
+/// The actions taken on users' accounts (`audit_log`). Entries are only ever appended, the tables refuse updates and deletes
+#[async_trait]
+pub trait AuditRepository: fmt::Debug + Send + Sync {
+    async fn record(&self, events: &[AuditEvent]) -> TResult<()>;
+
+    /// The page of entries `query` asks for, ordered by `id`
+    async fn find(&self, query: &AuditQuery) -> TResult<AuditPage>;
+}
+
+
+/// Keeps a user to one destructive job at a time
+#[async_trait]
+pub trait JobRepository: fmt::Debug + Send + Sync {
+    /// Fails with `TError::Conflict`, naming the running job, when the user already has one
+    async fn acquire(&self, user_id: Uuid, job: Job) -> TResult<Lease>;
+}
+
+
+/// Every repository the controllers use, reached through `AppState::store`
+#[derive(Debug, Clone)]
+pub struct Store {
+    pub users: Arc<dyn UserRepository>,
+    pub credentials: Arc<dyn CredentialRepository>,
+    pub tweets: Arc<dyn TweetRepository>,
+    pub archive: Arc<dyn ArchiveRepository>,
+    pub audit: Arc<dyn AuditRepository>,
+    pub jobs: Arc<dyn JobRepository>,
+}
+
+impl Store {
+    /// One value serving every repository, e.g. `PgStore` or `MemoryStore`
+    pub fn new<S>(store: S, jobs: Arc<dyn JobRepository>) -> Self
+    where S: UserRepository + CredentialRepository + TweetRepository + ArchiveRepository + AuditRepository + Clone + 'static {
+        Self {
+            users: Arc::new(store.clone()),
+            credentials: Arc::new(store.clone()),
+            tweets: Arc::new(store.clone()),
+            archive: Arc::new(store.clone()),
+            audit: Arc::new(store),
+            jobs,
+        }
+    }
+
+    /// Appends to the audit log. The action already happened on Twitter's side, so a failed write is logged rather than returned
+    pub async fn audit(&self, events: Vec<AuditEvent>) {
+        if events.is_empty() {
+            return;
+        }
+
+        if let Err(e) = self.audit.record(&events).await {
+            tracing::error!(error = %e, events = events.len(), "unable to record the audit events");
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use async_trait::async_trait;
+use chrono::TimeZone;
+use uuid::Uuid;
+
+use crate::base_repository::repository::{ArchiveRepository, AuditRepository, CredentialRepository, TweetRepository, UserRepository};
+use crate::helpers::archive::ArchivedTweet;
+use crate::helpers::audit::{AuditAction, AuditEvent, AuditOutcome, AuditQuery};
+use crate::helpers::db_helper::{PlayStatus, PlayTweet, TweetType};
+
+
+/// What the suite needs of a store beyond the repositories
+#[async_trait]
+pub trait Harness: UserRepository + CredentialRepository + TweetRepository + ArchiveRepository + AuditRepository + Sized {
+    /// An empty store, holding a single user who went through both OAuth flows
+    async fn with_user() -> (Self, Uuid);
+
+    /// The items of the user with their status and last error, in the order they were first queued
+    async fn queued(&self, user_id: Uuid) -> Vec<(PlayTweet, PlayStatus, Option<String>)>;
+}
+
+
+/// One `#[tokio::test]` per scenario of the suite, for the store given
+macro_rules! repository_suite {
+    ($store:ty) => {
+        mod repository_suite {
+            use crate::base_repository::repository::repository_test as suite;
+
+            #[tokio::test]
+            async fn credential_writes_are_seen_by_the_next_read() {
+                suite::credential_writes_are_seen_by_the_next_read::<$store>().await
+            }
+
+            #[tokio::test]
+            async fn queued_items_are_kept_once_and_done_items_stay_done() {
+                suite::queued_items_are_kept_once_and_done_items_stay_done::<$store>().await
+            }
+
+            #[tokio::test]
+            async fn failed_items_keep_their_error_until_queued_again() {
+                suite::failed_items_keep_their_error_until_queued_again::<$store>().await
+            }
+
+            #[tokio::test]
+            async fn metadata_survives_a_round_trip() {
+                suite::metadata_survives_a_round_trip::<$store>().await
+            }
+
+            #[tokio::test]
+            async fn the_audit_log_is_filtered_in_the_order_it_was_recorded() {
+                suite::the_audit_log_is_filtered_in_the_order_it_was_recorded::<$store>().await
+            }
+
+            #[tokio::test]
+            async fn the_audit_log_is_read_page_by_page() {
+                suite::the_audit_log_is_read_page_by_page::<$store>().await
+            }
+
+            #[tokio::test]
+            async fn a_newer_snapshot_replaces_the_archived_one() {
+                suite::a_newer_snapshot_replaces_the_archived_one::<$store>().await
+            }
+        }
+    };
+}
+pub(crate) use repository_suite;
+
+
+pub async fn credential_writes_are_seen_by_the_next_read<S: Harness>() {
+    let (store, user_id) = S::with_user().await;
+
+    store.update_secets("new-access".into(), "new-refresh".into(), user_id).await.unwrap();
+    store.create_v1_secets(user_id, "new-token".into(), "new-secret".into()).await.unwrap();
+    store.update_twitter_id("2244994945", user_id).await.unwrap();
+
+    let current = store.current_user(user_id).await.unwrap().unwrap();
+    assert_eq!(current.v2_user.access_token.as_deref(), Some("new-access"));
+    assert_eq!(current.v2_user.twitter_user_id.as_deref(), Some("2244994945"));
+    assert_eq!(current.v1_user.oauth_token, "new-token");
+    assert!(store.current_user(Uuid::new_v4()).await.unwrap().is_none());
+}
+
+pub async fn queued_items_are_kept_once_and_done_items_stay_done<S: Harness>() {
+    let (store, user_id) = S::with_user().await;
+    let items = vec![PlayTweet::new("1", TweetType::Tweets), PlayTweet::new("1", TweetType::Likes)];
+
+    store.insert_tweet_ids(user_id, &items).await.unwrap();
+    store.set_status(user_id, &[("1".into(), TweetType::Tweets)], PlayStatus::Done, None).await.unwrap();
+    store.insert_tweet_ids(user_id, &items).await.unwrap();
+
+    let queued = store.queued(user_id).await;
+    assert_eq!(queued.len(), 2);
+    assert_eq!((&queued[0].0, queued[0].1), (&items[0], PlayStatus::Done));
+    assert_eq!((&queued[1].0, queued[1].1), (&items[1], PlayStatus::Pending));
+    assert_eq!(store.pending_count().await.unwrap(), 1);
+}
+
+pub async fn failed_items_keep_their_error_until_queued_again<S: Harness>() {
+    let (store, user_id) = S::with_user().await;
+    let items = vec![PlayTweet::new("7", TweetType::Rts)];
+
+    store.insert_tweet_ids(user_id, &items).await.unwrap();
+    store.set_status(user_id, &[("7".into(), TweetType::Rts)], PlayStatus::Failed, Some("Not Found")).await.unwrap();
+    assert_eq!(store.queued(user_id).await[0].2.as_deref(), Some("Not Found"));
+
+    store.insert_tweet_ids(user_id, &items).await.unwrap();
+    assert_eq!(store.queued(user_id).await[0].1, PlayStatus::Pending);
+}
+
+pub async fn metadata_survives_a_round_trip<S: Harness>() {
+    let (store, user_id) = S::with_user().await;
+    let item = PlayTweet {
+        created_at: Some(chrono::Utc.ymd(2022, 4, 6).and_hms(17, 7, 13)),
+        text_hash: Some("6bd32adf806c9f0d202c23dd8c5b07d20ba2dbfa".into()),
+        source_tweet_id: Some("20".into()),
+        metrics: Some(serde_json::json!({ "retweet_count": 3 })),
+        ..PlayTweet::new("21", TweetType::Rts)
+    };
+
+    store.insert_tweet_ids(user_id, std::slice::from_ref(&item)).await.unwrap();
+    // an id sent to /remove later keeps what the timeline sync learnt
+    store.insert_tweet_ids(user_id, &[PlayTweet::new("21", TweetType::Rts)]).await.unwrap();
+
+    assert_eq!(store.queued(user_id).await, vec![(item, PlayStatus::Pending, None)]);
+}
+
+pub async fn the_audit_log_is_filtered_in_the_order_it_was_recorded<S: Harness>() {
+    let (store, user_id) = S::with_user().await;
+    let before = chrono::Utc::now() - chrono::Duration::seconds(1);
+
+    store.record(&[
+        AuditEvent::new(user_id, AuditAction::TimelineSync, "first"),
+        AuditEvent::new(Uuid::new_v4(), AuditAction::Delete, "first").target("3"),
+    ]).await.unwrap();
+    store.record(&[
+        AuditEvent::new(user_id, AuditAction::Delete, "second").target("1").outcome(AuditOutcome::Deferred),
+        AuditEvent::new(user_id, AuditAction::Unlike, "second").target("2").failed("Not Found"),
+    ]).await.unwrap();
+
+    let entries = store.find(&AuditQuery::new(user_id)).await.unwrap().entries;
+    assert_eq!(entries.iter().map(|e| e.action).collect::<Vec<_>>(), vec![AuditAction::TimelineSync, AuditAction::Delete, AuditAction::Unlike]);
+    assert!(entries.windows(2).all(|pair| pair[0].id < pair[1].id));
+    assert_eq!(entries[1].outcome, AuditOutcome::Deferred);
+    assert_eq!(store.find(&AuditQuery::new(user_id).limit(1)).await.unwrap().entries.len(), 1);
+
+    let unlikes = store.find(&AuditQuery::new(user_id).actions(vec![AuditAction::Unlike]).between(Some(before), None)).await.unwrap().entries;
+    assert_eq!(unlikes.len(), 1);
+    assert_eq!(unlikes[0].target.as_deref(), Some("2"));
+    assert_eq!(unlikes[0].detail, Some(serde_json::json!({ "error": "Not Found" })));
+    assert!(store.find(&AuditQuery::new(user_id).between(None, Some(before))).await.unwrap().entries.is_empty());
+}
+
+pub async fn the_audit_log_is_read_page_by_page<S: Harness>() {
+    let (store, user_id) = S::with_user().await;
+    let events = (0..5).map(|n| AuditEvent::new(user_id, AuditAction::Delete, "request").target(n.to_string())).collect::<Vec<_>>();
+    store.record(&events).await.unwrap();
+
+    let mut targets = vec![];
+    let mut query = AuditQuery::new(user_id).limit(2);
+    loop {
+        let page = store.find(&query).await.unwrap();
+        targets.extend(page.entries.into_iter().filter_map(|entry| entry.target));
+
+        match page.next {
+            Some(next) => query = query.after(Some(next)),
+            None => break,
+        }
+    }
+
+    assert_eq!(targets, vec!["0", "1", "2", "3", "4"]);
+}
+
+pub async fn a_newer_snapshot_replaces_the_archived_one<S: Harness>() {
+    let (store, user_id) = S::with_user().await;
+    let snapshot = |text: &str| ArchivedTweet {
+        tweet_id: "21".into(),
+        tweet_type: TweetType::Rts,
+        text: text.into(),
+        author_id: Some("2244994945".into()),
+        entities: None,
+        metrics: Some(serde_json::json!({ "retweet_count": 3 })),
+        media: None,
+        referenced_tweets: Some(serde_json::json!([{ "type": "retweeted", "id": "20" }])),
+        created_at: Some(chrono::Utc.ymd(2022, 4, 6).and_hms(17, 7, 13)),
+        archived_at: chrono::Utc.ymd(2026, 10, 18).and_hms(9, 0, 0),
+    };
+
+    store.archive(user_id, &[snapshot("RT @TwitterDev: first")]).await.unwrap();
+    store.archive(user_id, &[snapshot("RT @TwitterDev: edited")]).await.unwrap();
+
+    assert_eq!(store.archived(user_id).await.unwrap(), vec![snapshot("RT @TwitterDev: edited")]);
+    assert!(store.archived(Uuid::new_v4()).await.unwrap().is_empty());
+}
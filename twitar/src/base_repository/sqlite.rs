@@ -0,0 +1,445 @@
+use async_trait::async_trait;
+use chrono::{DateTime, SecondsFormat, Utc};
+use sqlx::{Row, SqlitePool};
+use sqlx::migrate::{MigrateError, Migrator};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions, SqliteRow};
+use uuid::Uuid;
+
+use crate::base_repository::db::{AuthUser, V1User, V2User};
+use crate::base_repository::repository::{ArchiveRepository, AuditRepository, CredentialRepository, TweetRepository, UserRepository};
+use crate::errors::response::TError;
+use crate::helpers::{archive::ArchivedTweet, audit::{AuditEntry, AuditEvent, AuditPage, AuditQuery}, db_helper::{PlayStatus, PlayTweet, TweetType}, metrics::observe_query, response::TResult};
+use crate::startup::{migrations, server::CurrentUser};
+
+#[cfg(test)]
+#[path = "./sqlite.test.rs"]
+mod sqlite_test;
+
+
+/// The `migrations_sqlite/` folder, the sqlite schema is kept apart from the Postgres one
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations_sqlite");
+
+
+/// The repositories over a single sqlite file, for running twitar for one account without Postgres or Redis
+#[derive(Debug, Clone)]
+pub struct SqliteStore {
+    pool: SqlitePool,
+}
+
+impl SqliteStore {
+    /// Connects on first use, the file is created when it does not exist yet
+    pub fn new(options: SqliteConnectOptions) -> Self {
+        let pool = SqlitePoolOptions::new().connect_lazy_with(options.create_if_missing(true));
+        Self { pool }
+    }
+
+    /// Applies the migrations the file is missing, before the server starts
+    pub async fn migrate(options: SqliteConnectOptions) -> Result<(), MigrateError> {
+        let pool = SqlitePool::connect_with(options.create_if_missing(true)).await?;
+        let result = migrations::up(&pool).await;
+        pool.close().await;
+
+        result
+    }
+
+    /// The items of the user, in the order they were first queued
+    pub async fn queued(&self, user_id: Uuid) -> TResult<Vec<(PlayTweet, PlayStatus)>> {
+        let rows = sqlx::query("SELECT * FROM play_tweets WHERE user_id = ? ORDER BY id").bind(user_id).fetch_all(&self.pool).await?;
+
+        rows.iter().map(|row| {
+            let status = match row.try_get::<String, _>("status")?.as_str() {
+                "processing" => PlayStatus::Processing,
+                "done" => PlayStatus::Done,
+                "failed" => PlayStatus::Failed,
+                _ => PlayStatus::Pending,
+            };
+
+            let item = PlayTweet {
+                tweet_id: row.try_get("tweet_id")?,
+                tweet_type: tweet_type(&row.try_get::<String, _>("tweet_type")?)?,
+                created_at: row.try_get("created_at")?,
+                text_hash: row.try_get("text_hash")?,
+                source_tweet_id: row.try_get("source_tweet_id")?,
+                metrics: json_column(row, "metrics")?,
+            };
+
+            Ok((item, status))
+        }).collect()
+    }
+}
+
+
+fn tweet_type(value: &str) -> TResult<TweetType> {
+    match value {
+        "tweets" => Ok(TweetType::Tweets),
+        "rts" => Ok(TweetType::Rts),
+        "likes" => Ok(TweetType::Likes),
+        other => Err(TError::UnexpectedError(anyhow::anyhow!("{:?} is not a tweet type", other))),
+    }
+}
+
+/// A fixed width, so the text in `audit_log.created_at` sorts and compares as the time it holds
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+fn audit_entry(row: &SqliteRow) -> TResult<AuditEntry> {
+    let created_at = DateTime::parse_from_rfc3339(&row.try_get::<String, _>("created_at")?).map_err(anyhow::Error::from)?;
+    Ok(AuditEntry {
+        id: row.try_get("id")?,
+        user_id: row.try_get("user_id")?,
+        action: row.try_get::<String, _>("action")?.parse().map_err(|e: String| anyhow::anyhow!(e))?,
+        outcome: row.try_get::<String, _>("outcome")?.parse().map_err(|e: String| anyhow::anyhow!(e))?,
+        target: row.try_get("target")?,
+        detail: json_column(row, "detail")?,
+        request_id: row.try_get("request_id")?,
+        created_at: created_at.with_timezone(&Utc),
+    })
+}
+
+/// A JSON column, stored as text
+fn json_column(row: &SqliteRow, column: &str) -> TResult<Option<serde_json::Value>> {
+    Ok(row.try_get::<Option<String>, _>(column)?.map(|value| serde_json::from_str(&value)).transpose()?)
+}
+
+fn archived_tweet(row: &SqliteRow) -> TResult<ArchivedTweet> {
+    Ok(ArchivedTweet {
+        tweet_id: row.try_get("tweet_id")?,
+        tweet_type: tweet_type(&row.try_get::<String, _>("tweet_type")?)?,
+        text: row.try_get("text")?,
+        author_id: row.try_get("author_id")?,
+        entities: json_column(row, "entities")?,
+        metrics: json_column(row, "metrics")?,
+        media: json_column(row, "media")?,
+        referenced_tweets: json_column(row, "referenced_tweets")?,
+        created_at: row.try_get("created_at")?,
+        archived_at: row.try_get("archived_at")?,
+    })
+}
+
+fn v1_user(row: &SqliteRow) -> TResult<V1User> {
+    Ok(V1User {
+        id: row.try_get("id")?,
+        user_id: row.try_get("user_id")?,
+        twitter_user_id: row.try_get("twitter_user_id")?,
+        oauth_token: row.try_get("oauth_token")?,
+        oauth_secret: row.try_get("oauth_secret")?,
+        oauth_verifier: row.try_get("oauth_verifier")?,
+    })
+}
+
+fn v2_user(row: &SqliteRow) -> TResult<V2User> {
+    Ok(V2User {
+        id: row.try_get("id")?,
+        user_id: row.try_get("user_id")?,
+        twitter_user_id: row.try_get("twitter_user_id")?,
+        pkce: row.try_get("pkce")?,
+        access_token: row.try_get("access_token")?,
+        refresh_token: row.try_get("refresh_token")?,
+    })
+}
+
+
+#[async_trait]
+impl UserRepository for SqliteStore {
+    async fn add_v1_user(&self, user_id: Uuid) -> TResult<()> {
+        observe_query("add_v1_user", async {
+            // the tokens are only known once the OAuth1 flow is done
+            sqlx::query("INSERT INTO auth_one (user_id, oauth_token, oauth_secret) VALUES (?, '', '')").bind(user_id).execute(&self.pool).await?;
+
+            Ok(())
+        }).await
+    }
+
+    async fn add_v2_user(&self, user_id: Uuid) -> TResult<()> {
+        observe_query("add_v2_user", async {
+            sqlx::query("INSERT INTO auth_two (user_id) VALUES (?)").bind(user_id).execute(&self.pool).await?;
+
+            Ok(())
+        }).await
+    }
+
+    async fn user_exists(&self, user_id: Uuid) -> TResult<Option<AuthUser>> {
+        observe_query("user_exists", async {
+            let row = sqlx::query("SELECT user_id, v1_active, v2_active FROM user_preference WHERE user_id = ?")
+                .bind(user_id)
+                .fetch_optional(&self.pool).await?;
+
+            match row {
+                Some(row) => Ok(Some(AuthUser { user_id, v1_active: row.try_get("v1_active")?, v2_active: row.try_get("v2_active")? })),
+                None => Ok(None),
+            }
+        }).await
+    }
+
+    async fn current_user(&self, user_id: Uuid) -> TResult<Option<CurrentUser>> {
+        observe_query("current_user", async {
+            let basic = match self.user_exists(user_id).await? {
+                Some(basic) => basic,
+                None => return Ok(None),
+            };
+
+            match (self.v1_user(user_id).await?, self.v2_user(user_id).await?) {
+                (Some(v1_user), Some(v2_user)) => Ok(Some(CurrentUser::new(basic, v1_user, v2_user))),
+                _ => Ok(None),
+            }
+        }).await
+    }
+}
+
+
+#[async_trait]
+impl CredentialRepository for SqliteStore {
+    async fn v1_user(&self, user_id: Uuid) -> TResult<Option<V1User>> {
+        observe_query("v1_user", async {
+            let row = sqlx::query("SELECT * FROM auth_one WHERE user_id = ?").bind(user_id).fetch_optional(&self.pool).await?;
+            row.as_ref().map(v1_user).transpose()
+        }).await
+    }
+
+    async fn v2_user(&self, user_id: Uuid) -> TResult<Option<V2User>> {
+        observe_query("v2_user", async {
+            let row = sqlx::query("SELECT * FROM auth_two WHERE user_id = ?").bind(user_id).fetch_optional(&self.pool).await?;
+            row.as_ref().map(v2_user).transpose()
+        }).await
+    }
+
+    async fn update_pkce(&self, pkce: &str, user_id: Uuid) -> TResult<()> {
+        observe_query("update_pkce", async {
+            sqlx::query("UPDATE auth_two SET pkce = ? WHERE user_id = ?")
+                .bind(pkce).bind(user_id)
+                .execute(&self.pool).await?;
+
+            Ok(())
+        }).await
+    }
+
+    async fn update_secets(&self, access_token: String, refresh_token: String, user_id: Uuid) -> TResult<()> {
+        observe_query("update_secets", async {
+            sqlx::query("UPDATE auth_two SET access_token = ?, refresh_token = ? WHERE user_id = ?")
+                .bind(access_token).bind(refresh_token).bind(user_id)
+                .execute(&self.pool).await?;
+
+            Ok(())
+        }).await
+    }
+
+    async fn update_v1_secets(&self, oauth_token: String, oauth_secret: String, user_id: Uuid) -> TResult<()> {
+        observe_query("update_v1_secets", async {
+            sqlx::query("UPDATE auth_one SET oauth_token = ?, oauth_secret = ? WHERE user_id = ?")
+                .bind(oauth_token).bind(oauth_secret).bind(user_id)
+                .execute(&self.pool).await?;
+
+            Ok(())
+        }).await
+    }
+
+    async fn add_oauth_verifier(&self, oauth_verifier: &str, user_id: Uuid) -> TResult<()> {
+        observe_query("add_oauth_verifier", async {
+            sqlx::query("UPDATE auth_one SET oauth_verifier = ? WHERE user_id = ?")
+                .bind(oauth_verifier).bind(user_id)
+                .execute(&self.pool).await?;
+
+            Ok(())
+        }).await
+    }
+
+    async fn create_v1_secets(&self, user_id: Uuid, oauth_token: String, oauth_secret: String) -> TResult<()> {
+        observe_query("create_v1_secets", async {
+            let mut transaction = self.pool.begin().await?;
+
+            sqlx::query("DELETE FROM auth_one WHERE user_id = ?").bind(user_id).execute(&mut transaction).await?;
+            sqlx::query("INSERT INTO auth_one (user_id, oauth_token, oauth_secret) VALUES (?, ?, ?)")
+                .bind(user_id).bind(oauth_token).bind(oauth_secret)
+                .execute(&mut transaction).await?;
+
+            transaction.commit().await?;
+            Ok(())
+        }).await
+    }
+
+    async fn update_twitter_id(&self, twitter_user_id: &str, user_id: Uuid) -> TResult<()> {
+        observe_query("update_twitter_id", async {
+            sqlx::query("UPDATE auth_two SET twitter_user_id = ? WHERE user_id = ?")
+                .bind(twitter_user_id).bind(user_id)
+                .execute(&self.pool).await?;
+
+            Ok(())
+        }).await
+    }
+}
+
+
+#[async_trait]
+impl TweetRepository for SqliteStore {
+    async fn insert_tweet_ids(&self, user_id: Uuid, items: &[PlayTweet]) -> TResult<()> {
+        observe_query("insert_tweet_ids", async {
+            let mut transaction = self.pool.begin().await?;
+
+            for item in items {
+                sqlx::query(
+                    r#"INSERT INTO play_tweets (user_id, tweet_id, tweet_type, created_at, text_hash, source_tweet_id, metrics)
+                    VALUES (?, ?, ?, ?, ?, ?, ?)
+                    ON CONFLICT (user_id, tweet_id, tweet_type) DO UPDATE SET
+                        created_at = COALESCE(excluded.created_at, play_tweets.created_at),
+                        text_hash = COALESCE(excluded.text_hash, play_tweets.text_hash),
+                        source_tweet_id = COALESCE(excluded.source_tweet_id, play_tweets.source_tweet_id),
+                        metrics = COALESCE(excluded.metrics, play_tweets.metrics),
+                        status = CASE WHEN play_tweets.status = 'done' THEN play_tweets.status ELSE 'pending' END,
+                        updated_at = CURRENT_TIMESTAMP"#
+                )
+                    .bind(user_id)
+                    .bind(&item.tweet_id)
+                    .bind(item.tweet_type.to_string())
+                    .bind(item.created_at)
+                    .bind(&item.text_hash)
+                    .bind(&item.source_tweet_id)
+                    .bind(item.metrics.as_ref().map(|m| m.to_string()))
+                    .execute(&mut transaction).await?;
+            }
+
+            transaction.commit().await?;
+            Ok(())
+        }).await
+    }
+
+    async fn set_status(&self, user_id: Uuid, ids: &[(String, TweetType)], status: PlayStatus, error: Option<&str>) -> TResult<()> {
+        observe_query("set_status", async {
+            let mut transaction = self.pool.begin().await?;
+
+            for (tweet_id, tweet_type) in ids {
+                sqlx::query(
+                    r#"UPDATE play_tweets SET status = ?, last_error = ?, updated_at = CURRENT_TIMESTAMP
+                    WHERE user_id = ? AND tweet_id = ? AND tweet_type = ?"#
+                )
+                    .bind(status.to_string())
+                    .bind(error)
+                    .bind(user_id)
+                    .bind(tweet_id)
+                    .bind(tweet_type.to_string())
+                    .execute(&mut transaction).await?;
+            }
+
+            transaction.commit().await?;
+            Ok(())
+        }).await
+    }
+
+    async fn pending_count(&self) -> TResult<i64> {
+        observe_query("pending_count", async {
+            let count = sqlx::query_scalar("SELECT COUNT(*) FROM play_tweets WHERE status = 'pending'")
+                .fetch_one(&self.pool).await?;
+
+            Ok(count)
+        }).await
+    }
+}
+
+
+#[async_trait]
+impl ArchiveRepository for SqliteStore {
+    async fn archive(&self, user_id: Uuid, items: &[ArchivedTweet]) -> TResult<()> {
+        observe_query("archive_tweets", async {
+            let mut transaction = self.pool.begin().await?;
+            let json = |value: &Option<serde_json::Value>| value.as_ref().map(|v| v.to_string());
+
+            for item in items {
+                sqlx::query(
+                    r#"INSERT INTO tweet_archive (user_id, tweet_id, tweet_type, text, author_id, entities, metrics, media, referenced_tweets, created_at, archived_at)
+                    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                    ON CONFLICT (user_id, tweet_id, tweet_type) DO UPDATE SET
+                        text = excluded.text, author_id = excluded.author_id, entities = excluded.entities, metrics = excluded.metrics,
+                        media = excluded.media, referenced_tweets = excluded.referenced_tweets, created_at = excluded.created_at,
+                        archived_at = excluded.archived_at"#
+                )
+                    .bind(user_id)
+                    .bind(&item.tweet_id)
+                    .bind(item.tweet_type.to_string())
+                    .bind(&item.text)
+                    .bind(&item.author_id)
+                    .bind(json(&item.entities))
+                    .bind(json(&item.metrics))
+                    .bind(json(&item.media))
+                    .bind(json(&item.referenced_tweets))
+                    .bind(item.created_at)
+                    .bind(item.archived_at)
+                    .execute(&mut transaction).await?;
+            }
+
+            transaction.commit().await?;
+            Ok(())
+        }).await
+    }
+
+    async fn archived(&self, user_id: Uuid) -> TResult<Vec<ArchivedTweet>> {
+        observe_query("archived_tweets", async {
+            let rows = sqlx::query("SELECT * FROM tweet_archive WHERE user_id = ? ORDER BY id").bind(user_id).fetch_all(&self.pool).await?;
+            rows.iter().map(archived_tweet).collect()
+        }).await
+    }
+}
+
+
+#[async_trait]
+impl AuditRepository for SqliteStore {
+    async fn record(&self, events: &[AuditEvent]) -> TResult<()> {
+        observe_query("record_audit", async {
+            let mut transaction = self.pool.begin().await?;
+            let created_at = timestamp(Utc::now());
+
+            for event in events {
+                sqlx::query(
+                    r#"INSERT INTO audit_log (user_id, action, outcome, target, detail, request_id, created_at)
+                    VALUES (?, ?, ?, ?, ?, ?, ?)"#
+                )
+                    .bind(event.user_id)
+                    .bind(event.action.to_string())
+                    .bind(event.outcome.to_string())
+                    .bind(&event.target)
+                    .bind(event.detail.as_ref().map(|d| d.to_string()))
+                    .bind(&event.request_id)
+                    .bind(&created_at)
+                    .execute(&mut transaction).await?;
+            }
+
+            transaction.commit().await?;
+            Ok(())
+        }).await
+    }
+
+    async fn find(&self, query: &AuditQuery) -> TResult<AuditPage> {
+        observe_query("audit_log", async {
+            let actions = match query.actions.len() {
+                0 => String::new(),
+                n => format!("AND action IN ({})", vec!["?"; n].join(", ")),
+            };
+
+            let sql = format!(
+                r#"SELECT * FROM audit_log
+                WHERE user_id = ? {}
+                    AND (? IS NULL OR created_at >= ?)
+                    AND (? IS NULL OR created_at < ?)
+                    AND (? IS NULL OR id > ?)
+                ORDER BY id LIMIT ?"#,
+                actions
+            );
+
+            let (from, to) = (query.from.map(timestamp), query.to.map(timestamp));
+            let mut statement = sqlx::query(&sql).bind(query.user_id);
+
+            for action in &query.actions {
+                statement = statement.bind(action.to_string());
+            }
+
+            let rows = statement
+                .bind(from.clone()).bind(from)
+                .bind(to.clone()).bind(to)
+                .bind(query.after).bind(query.after)
+                .bind(query.limit)
+                .fetch_all(&self.pool).await?;
+
+            let entries = rows.iter().map(audit_entry).collect::<TResult<Vec<_>>>()?;
+            Ok(AuditPage::new(query, entries))
+        }).await
+    }
+}
@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod test_sqlite {
+    use std::str::FromStr;
+    use async_trait::async_trait;
+    use sqlx::Row;
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+    use uuid::Uuid;
+
+    use crate::base_repository::repository::{AuditRepository, CredentialRepository, UserRepository, repository_test::{repository_suite, Harness}};
+    use crate::base_repository::sqlite::SqliteStore;
+    use crate::helpers::audit::{AuditAction, AuditEvent};
+    use crate::helpers::db_helper::{PlayStatus, PlayTweet};
+    use crate::startup::migrations;
+
+    #[async_trait]
+    impl Harness for SqliteStore {
+        async fn with_user() -> (Self, Uuid) {
+            // every connection to `:memory:` opens a database of its own, so the pool keeps a single one for good
+            let pool = SqlitePoolOptions::new().max_connections(1).idle_timeout(None).max_lifetime(None)
+                .connect_with(SqliteConnectOptions::from_str("sqlite::memory:").unwrap()).await.unwrap();
+            migrations::up(&pool).await.unwrap();
+
+            let store = SqliteStore { pool };
+            let user_id = Uuid::new_v4();
+
+            sqlx::query("INSERT INTO user_preference (user_id, v1_active, v2_active) VALUES (?, true, true)")
+                .bind(user_id).execute(&store.pool).await.unwrap();
+            sqlx::query("INSERT INTO auth_one (user_id, oauth_token, oauth_secret) VALUES (?, 'oauth-token', 'oauth-secret')")
+                .bind(user_id).execute(&store.pool).await.unwrap();
+            sqlx::query("INSERT INTO auth_two (user_id, access_token, refresh_token) VALUES (?, 'access', 'refresh')")
+                .bind(user_id).execute(&store.pool).await.unwrap();
+
+            (store, user_id)
+        }
+
+        async fn queued(&self, user_id: Uuid) -> Vec<(PlayTweet, PlayStatus, Option<String>)> {
+            let errors = sqlx::query("SELECT last_error FROM play_tweets WHERE user_id = ? ORDER BY id")
+                .bind(user_id).fetch_all(&self.pool).await.unwrap();
+
+            SqliteStore::queued(self, user_id).await.unwrap().into_iter()
+                .zip(errors.iter().map(|row| row.get("last_error")))
+                .map(|((item, status), error)| (item, status, error))
+                .collect()
+        }
+    }
+
+    repository_suite!(crate::base_repository::sqlite::SqliteStore);
+
+    #[tokio::test]
+    async fn new_users_get_empty_credentials() {
+        let (store, _) = SqliteStore::with_user().await;
+        let user_id = Uuid::new_v4();
+        sqlx::query("INSERT INTO user_preference (user_id, v1_active, v2_active) VALUES (?, false, false)").bind(user_id).execute(&store.pool).await.unwrap();
+
+        store.add_v1_user(user_id).await.unwrap();
+        store.add_v2_user(user_id).await.unwrap();
+
+        assert_eq!(store.v1_user(user_id).await.unwrap().unwrap().oauth_token, "");
+        assert!(store.v2_user(user_id).await.unwrap().unwrap().access_token.is_none());
+    }
+
+    #[tokio::test]
+    async fn the_audit_log_cannot_be_rewritten() {
+        let (store, user_id) = SqliteStore::with_user().await;
+        store.record(&[AuditEvent::new(user_id, AuditAction::Delete, "request").target("1")]).await.unwrap();
+
+        assert!(sqlx::query("UPDATE audit_log SET outcome = 'failure'").execute(&store.pool).await.is_err());
+        assert!(sqlx::query("DELETE FROM audit_log").execute(&store.pool).await.is_err());
+    }
+}
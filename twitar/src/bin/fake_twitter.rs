@@ -0,0 +1,20 @@
+use std::net::TcpListener;
+use dotenv::dotenv;
+
+use twitar::stubs::{http::address, FakeTwitter};
+
+/// The fake api listens where `TWITTER_API` points, so twitar talks to it instead of api.twitter.com
+#[tokio::main]
+async fn main() {
+    dotenv().ok();
+
+    let twitter_api = std::env::var("TWITTER_API").unwrap_or_else(|_| "http://127.0.0.1:8181".into());
+    let addr = address(&twitter_api).expect("TWITTER_API must be an http://<ip>:<port> url");
+
+    let listener = TcpListener::bind(addr).expect("Failed to bind the fake Twitter api");
+    println!("Fake Twitter api listening on {}", twitter_api);
+
+    if let Err(e) = FakeTwitter::new().serve(listener).await {
+        eprintln!("fake twitter error: {}", e)
+    }
+}
@@ -1,4 +0,0 @@
-pub mod settings;
-pub mod db_settings;
-pub mod application_settings;
-pub mod variables;
\ No newline at end of file
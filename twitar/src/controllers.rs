@@ -2,6 +2,7 @@ pub mod timeline;
 mod not_found;
 mod authorize_bot;
 mod health_check;
+mod metrics;
 pub mod handle_redirect;
 mod revoke_token;
 mod refresh_token;
@@ -9,14 +10,19 @@ mod user_lookup;
 mod destroy;
 mod oauth_flow;
 mod scheduled_tweets;
+mod audit;
+mod archive;
 
 pub use not_found::not_found;
 pub use authorize_bot::authorize_bot;
-pub use health_check::health_check;
+pub use health_check::{health_check, readiness};
+pub use metrics::metrics;
 pub use handle_redirect::handle_redirect;
 pub use revoke_token::revoke_token;
 pub use refresh_token::refresh_token;
 pub use user_lookup::user_lookup;
 pub use timeline::get_timeline;
 pub use destroy::handle_delete;
-pub use oauth_flow::request_token;
\ No newline at end of file
+pub use oauth_flow::request_token;
+pub use audit::audit_log;
+pub use archive::archived_tweets;
\ No newline at end of file
@@ -0,0 +1,15 @@
+use hyper::StatusCode;
+
+use crate::helpers::response::{ApiBody, ResponseBuilder, TResult};
+use crate::startup::server::AppState;
+
+
+/// What the removed tweets, retweets and likes of the user held, in the order they were archived
+pub async fn archived_tweets(app_state: AppState) -> TResult<ApiBody> {
+    let AppState { user, store, .. } = app_state;
+    let user_id = user.unwrap().basic.user_id;
+
+    let archived = store.archive.archived(user_id).await?;
+
+    ResponseBuilder::new("Ok".into(), Some(archived), StatusCode::OK.as_u16()).reply()
+}
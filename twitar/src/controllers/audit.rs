@@ -0,0 +1,60 @@
+use chrono::{DateTime, Utc};
+use hyper::{header::HeaderValue, Body, Response, StatusCode};
+use twitar_macro::{Extract, Validate};
+
+use crate::helpers::{
+    audit::{to_csv, to_jsonl, AuditAction, AuditPage, AuditQuery, ExportFormat},
+    request::extract_query, response::{ApiBody, ResponseBuilder, TResult},
+};
+use crate::startup::server::AppState;
+
+
+/// `action` is a comma separated list, `from` and `to` are RFC 3339 times e.g. `2026-10-18T10:00:00Z`.
+/// `after` is the `x-next-after` header of the previous page
+#[derive(Debug, Extract, Validate)]
+#[extract(query)]
+struct AuditParams {
+    action: Option<Vec<AuditAction>>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    format: Option<ExportFormat>,
+    limit: Option<i64>,
+    after: Option<i64>,
+}
+
+
+/// What was done to the user's account, oldest first, as JSON or as a csv/jsonl file to download.
+/// A full page names the `after` of the next one in `x-next-after`
+pub async fn audit_log(app_state: AppState) -> TResult<ApiBody> {
+    let AppState { req, user, store, .. } = app_state;
+    let user_id = user.unwrap().basic.user_id;
+    let AuditParams { action, from, to, format, limit, after } = extract_query(&req)?;
+
+    let mut query = AuditQuery::new(user_id).actions(action.unwrap_or_default()).between(from, to).after(after);
+
+    if let Some(limit) = limit {
+        query = query.limit(limit);
+    }
+
+    let AuditPage { entries, next } = store.audit.find(&query).await?;
+
+    let (format, body) = match format.unwrap_or(ExportFormat::Json) {
+        ExportFormat::Json => (ExportFormat::Json, None),
+        ExportFormat::Csv => (ExportFormat::Csv, Some(to_csv(&entries))),
+        ExportFormat::Jsonl => (ExportFormat::Jsonl, Some(to_jsonl(&entries))),
+    };
+
+    let mut response = match body {
+        None => ResponseBuilder::new("Ok".into(), Some(entries), StatusCode::OK.as_u16()).reply()?,
+        Some(body) => Response::builder().status(StatusCode::OK)
+            .header("content-type", format.content_type())
+            .header("content-disposition", format!("attachment; filename=\"audit-{}.{}\"", user_id, format.extension()))
+            .body(Body::from(body)).unwrap(),
+    };
+
+    if let Some(next) = next {
+        response.headers_mut().insert("x-next-after", HeaderValue::from(next));
+    }
+
+    Ok(response)
+}
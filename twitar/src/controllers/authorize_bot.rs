@@ -1,26 +1,29 @@
 use hyper::{Method, Body, Response};
+use serde_json::json;
 
-use crate::base_repository::db::DB;
 use crate::startup::server::AppState;
 use crate::helpers::response::ApiBody;
 use crate::helpers::{
+    audit::{AuditAction, AuditEvent},
     response::{TResult},
     gen_pkce::Pkce,
     scope::Scope,
     keyval::KeyVal,
 };
-use crate::configurations::{variables::SettingsVars};
+use crate::settings::app::AppSettings;
 use crate::middlewares::request_builder::RequestBuilder;
 
 
 pub async fn authorize_bot(app_state: AppState) -> TResult<ApiBody> {
-    let SettingsVars {client_id, callback_url, state, ..} = app_state.env_vars;    
+    let AppSettings {client_id, callback_url, state_code: state, authorize_url, ..} = app_state.settings.app.clone();
     
     let pkce: String = Pkce::new().to_string();
     let scopes = vec![Scope::ReadTweet, Scope::ReadUsers, Scope::ReadFollows, Scope::WriteFollows, 
     Scope::OfflineAccess, Scope::WriteTweet, Scope::WriteLike, Scope::ReadLike];
     
-    DB::update_pkce(&app_state.db_pool, &pkce, app_state.user.unwrap().v2_user.user_id).await?;
+    let user_id = app_state.user.unwrap().v2_user.user_id;
+    app_state.store.credentials.update_pkce(&pkce, user_id).await?;
+    app_state.store.audit(vec![AuditEvent::new(user_id, AuditAction::OauthStart, &app_state.request_id).detail(json!({ "oauth": "2.0" }))]).await;
 
     // use uuid::Uuid;
 
@@ -35,12 +38,10 @@ pub async fn authorize_bot(app_state: AppState) -> TResult<ApiBody> {
             ("code_challenge_method".to_string(), "plain".to_string()),
         ]);
 
-    let request = RequestBuilder::new(Method::GET, "https://twitter.com/i/oauth2/authorize".into())
+    let request = RequestBuilder::new(Method::GET, authorize_url)
         .add_query_params(query_params)
         .build_request();
 
-    println!("THE REQUEST {:#?}", request);
-
     let response_body= Response::builder().status(302)
         .header("Location", request.uri().to_string())
         .body(Body::from(request.uri().to_string())).unwrap();
@@ -1,115 +1,120 @@
-use std::{collections::HashMap};
-use hyper::{Body, Request, Method};
+use std::collections::HashMap;
+use hyper::{Body, Request, Method, StatusCode};
 use futures::{stream, StreamExt};
-use serde_json::Value;
+use serde_json::json;
 use tokio;
+use tracing::Instrument;
+use twitar_macro::{Extract, Validate};
 
 use crate::{
     helpers::{
         response::{
-            TResult, ApiBody, ResponseBuilder, make_request
+            TResult, ApiBody, ResponseBuilder
         }, signature::{
             OAuth, OAuthAddons
-        }, keypair::KeyPair
-    }, middlewares::request_builder::{RequestBuilder, AuthType}, configurations::variables::SettingsVars, startup::server::AppState, base_repository::db::{V2User, V1User}
+        }, keypair::KeyPair, request::extract_body
+    }, middlewares::request_builder::{RequestBuilder, AuthType}, settings::app::AppSettings, startup::server::AppState, base_repository::db::{V2User, V1User}
 };
-use crate::helpers::db_helper::{TweetType};
-
-type Ids = HashMap<String, Vec<String>>;
-
-
-const MAX_SINGLE_DELETES: usize = 50;
+use crate::helpers::archive::snapshot;
+use crate::helpers::audit::{AuditEvent, AuditOutcome};
+use crate::helpers::db_helper::{PlayStatus, PlayTweet, TweetType};
+use crate::helpers::lease::Job;
+use crate::helpers::metrics::METRICS;
+use crate::helpers::rate_limit::BudgetOwner;
+use crate::errors::response::TError;
 
 #[derive(Debug, Clone)]
 struct PostIds(Vec<(String, TweetType)>);
 
 
-// FIND A BETTER WAY TO HANDLE THIS PARSING, SO THE CODE IS MORE READABLE AND EASIER TO FOLLOW
-impl PostIds {
-    pub fn parse(s: Ids) -> Self {
-        let received_keys = s.keys().cloned().collect::<Vec<String>>();
-        let expected_keys = [TweetType::Rts, TweetType::Tweets, TweetType::Likes];
-
-        if received_keys.contains(&TweetType::Rts.to_string()) && received_keys.contains(&&TweetType::Tweets.to_string()) {
-            let total_rts = s.get(&TweetType::Rts.to_string()).unwrap().len();
-            let total_tweets = s.get(&TweetType::Tweets.to_string()).unwrap().len();
-            let total_likes = s.get(&TweetType::Likes.to_string()).unwrap().len();
-
-
-            if *[total_rts, total_tweets, total_likes].iter().max().unwrap() > MAX_SINGLE_DELETES {
-                panic!("Total tweets or rts cannot be more than 50")
-            }
+/// Ids to remove, at most 50 of each kind can be sent in a single request
+#[derive(Debug, Extract, Validate)]
+#[extract(body)]
+struct DeleteBody {
+    #[validate(numeric, unique, max_items = 50)]
+    rts: Vec<String>,
+    #[validate(numeric, unique, max_items = 50)]
+    tweets: Vec<String>,
+    #[validate(numeric, unique, max_items = 50)]
+    likes: Option<Vec<String>>,
+}
 
-            let mut all_ids: Vec<(String, TweetType)> = vec![];
+impl From<DeleteBody> for PostIds {
+    fn from(body: DeleteBody) -> Self {
+        let DeleteBody { rts, tweets, likes } = body;
 
-            for key in expected_keys {
-                let ids = s.get(&key.to_string()).unwrap();
+        let with_type = |ids: Vec<String>, tweet_type: TweetType| ids.into_iter().map(move |id| (id, tweet_type));
 
-                let duplicates = ids.iter()
-                    .find(|x| ids.iter().filter(|y| x == y).count() >= 2);
+        let all_ids = with_type(rts, TweetType::Rts)
+            .chain(with_type(tweets, TweetType::Tweets))
+            .chain(with_type(likes.unwrap_or_default(), TweetType::Likes))
+            .collect();
 
-                let empty_string = ids.iter().find(|x| x.len() < 1);
+        Self(all_ids)
+    }
+}
 
-                let not_number = ids.iter().find(|id| id.parse::<u64>().is_err());
 
-                if duplicates.is_some() || empty_string.is_some() || not_number.is_some() {
-                    panic!("{} must be an array of ids or an empty array", key)
-                }
+/// Twitter calls a single removal makes at once
+const MAX_PARALLEL_DELETES: usize = 10;
 
-                let ids = s.get(&key.to_string()).unwrap()
-                    .iter().map(|k| (k.clone(), key)).collect::<Vec<(String, TweetType)>>();
 
-                all_ids.extend(ids);
+/// Removals of a user run one at a time, with the timeline sync, so they cannot spend the same budget twice.
+/// The removal runs in a task of its own, so a request that times out still requeues and audits its ids
+pub async fn handle_delete(app_state: AppState) -> TResult<ApiBody> {
+    let user_id = app_state.user.as_ref().unwrap().basic.user_id;
+    let lease = app_state.store.jobs.acquire(user_id, Job::Remove).await?;
 
-            }
+    let removal = tokio::spawn(async move {
+        let response = remove(app_state).await;
+        lease.release().await;
 
-            return Self(all_ids)
-        }
+        response
+    }.in_current_span());
 
-        panic!("request object must contain rts and tweets with an array of string as values")
-    }
+    removal.await.map_err(|e| TError::UnexpectedError(anyhow::anyhow!("removal task failed: {}", e)))?
 }
 
-
 // rename this module to destory which then contains destory RTs and destory Posts
-pub async fn handle_delete(app_state: AppState) -> TResult<ApiBody> {
-    let AppState {env_vars, req, hyper, user, ..} = app_state;
-    let SettingsVars { twitter_url, api_key, api_key_secret, .. } = env_vars;
+async fn remove(app_state: AppState) -> TResult<ApiBody> {
+    let AppState {settings, req, hyper, user, store, shutdown, rate_limits, request_id, ..} = app_state;
+    let AppSettings { twitter_api: twitter_url, api_key, api_key_secret, client_id, .. } = settings.app.clone();
     let user = user.unwrap();
 
-    let V2User {access_token, twitter_user_id, ..} = user.v2_user;
+    let V2User {access_token, twitter_user_id, user_id, ..} = user.v2_user;
     let V1User {oauth_token, oauth_secret, ..} = user.v1_user;
-    let twitter_user_id = twitter_user_id.unwrap();
-
-    // let mut con = redis.get_async_connection().await?;
-    // let access_token: String = redis::cmd("GET").arg(&["access_token"]).query_async(&mut con).await?;
-
+    let twitter_user_id = twitter_user_id.ok_or(TError::Unauthenticated("Twitter user id has not been looked up"))?;
+    let access_token = access_token.ok_or(TError::Unauthenticated("OAuth2 is not connected"))?;
 
     // req body for the ids must be a vector of strings(id of tweets)
-    let req_body = req.into_body();
-    let  byte_body = hyper::body::to_bytes(req_body).await?.to_owned();
-    let body: Ids = serde_json::from_slice(&byte_body).unwrap();
+    let body: DeleteBody = extract_body(req).await?;
 
-    let post_ids = PostIds::parse(body).0;
-    let parallel_requests = post_ids.len();
+    let post_ids = PostIds::from(body).0;
+
+    // what the items hold is kept before Twitter is asked to remove any of them, a failed lookup removes nothing
+    let owner = BudgetOwner::new(&client_id, &twitter_user_id);
+    let archived = snapshot(hyper.clone(), &twitter_url, &access_token, rate_limits.clone(), &owner, &post_ids).await?;
+    store.archive.archive(user_id, &archived).await?;
 
     let oauth_token = KeyPair::new(oauth_token, oauth_secret);
     let consumer = KeyPair::new(api_key, api_key_secret);
     
-    let bodies = stream::iter(post_ids)
+    // once shutdown starts no new id is picked up, the calls in flight still finish
+    let bodies = stream::iter(post_ids.clone())
+    .take_until(shutdown.clone().triggered())
     .map(|id: (String, TweetType)| {
         let client = hyper.clone();
-        let token = access_token.as_ref().unwrap().clone();
+        let rate_limits = rate_limits.clone();
+        let token = access_token.clone();
         
         let twitter_url = twitter_url.clone();
-        let mut request: Option<Request<Body>> = None;
         
         // At the moment, twitter uses OAuth1.0 and 2.0 for Delete Tweets while it only uses 1.0 Authentication for its Unretweets which is a v2 endpoint
         // I really love the OAuth2.0 implementation but need backup code for OAuth1.0 (to handle undo retweets)
-        match id.1 {
+        let request: Request<Body> = match id.1 {
             TweetType::Tweets => {
-                request = Some(RequestBuilder::new(Method::DELETE, format!("{}/2/tweets/{}", twitter_url, id.0))
-                    .with_auth(AuthType::Bearer, token).build_request());
+                RequestBuilder::new(Method::DELETE, format!("{}/2/tweets/{}", twitter_url, id.0))
+                    .with_auth(AuthType::Bearer, token).build_request()
             }
             TweetType::Rts => {
                 // this would have been best, but we can't use this, because it requires using the source_tweet_id which is the original tweets id, that would have required an extra lookup
@@ -117,36 +122,102 @@ pub async fn handle_delete(app_state: AppState) -> TResult<ApiBody> {
                 //     .with_auth(AuthType::Bearer, token).build_request());
                  let base_url = format!("{}/1.1/statuses/unretweet/{}.json", twitter_url, id.0);
                     let signature = OAuth::new(consumer.clone(), Some(oauth_token.clone()), OAuthAddons::None, Method::POST).generate_signature(base_url.clone());
-                    request = Some(RequestBuilder::new(Method::POST, base_url)
-                        .with_auth(AuthType::OAuth, signature.to_string()).build_request());
+                    RequestBuilder::new(Method::POST, base_url)
+                        .with_auth(AuthType::OAuth, signature.to_string()).build_request()
             }
             TweetType::Likes => {
-                request = Some(RequestBuilder::new(Method::DELETE, format!("{}/2/users/{}/likes/{}", twitter_url, twitter_user_id, id.0))
-                    .with_auth(AuthType::Bearer, token).build_request());
+                RequestBuilder::new(Method::DELETE, format!("{}/2/users/{}/likes/{}", twitter_url, twitter_user_id, id.0))
+                    .with_auth(AuthType::Bearer, token).build_request()
             }
         };
 
 
-        tokio::spawn(async move {
-                let response = make_request(request.unwrap(), client).await.unwrap();
-                response.1
-            })
-        }).buffer_unordered(parallel_requests);
+        let owner = owner.clone();
+        let tweet_type = id.1;
+
+        // the id stays outside the task, so a task that panics is still reported against it
+        let task = tokio::spawn(async move {
+                // a spent budget, possibly spent by another replica, is not asked of Twitter
+                let response = rate_limits.send(&owner, request, client).await;
+                METRICS.observe_job(&format!("remove_{}", tweet_type), &response);
 
-    bodies
-        .for_each(|res| async {
+                response
+            });
+
+        async move { (id, task.await) }
+        }).buffer_unordered(MAX_PARALLEL_DELETES);
+
+    let attempted = bodies
+        .filter_map(|res| async {
             match res {
-                Ok(body) => {
-                    let body: Value = serde_json::from_slice(&body).unwrap();
+                (id, Ok(Err(TError::RateLimit(_)))) => {
+                    // left for later with the ids that were not attempted
+                    tracing::debug!(id = %id.0, tweet_type = %id.1, "rate limited");
+                    None
+                }
+                (id, Ok(Ok(_))) => {
                     // The success body in responsebuilder should include the deleted ids?
-                    println!("THE BODY {:#?}", body)
+                    tracing::debug!(id = %id.0, tweet_type = %id.1, "removed");
+                    Some((id, None))
                 }
-                Err(e) => {
+                (id, Ok(Err(e))) => {
                     // includes failed ids in the responsebuilder body?
-                    eprintln!("ERROR {:#?}", e)
+                    tracing::warn!(id = %id.0, tweet_type = %id.1, error = %e, "unable to remove");
+                    Some((id, Some(e.to_string())))
+                }
+                (id, Err(e)) => {
+                    // the task may have reached Twitter before panicking, so it is not retried
+                    tracing::error!(id = %id.0, tweet_type = %id.1, error = %e, "removal task failed");
+                    Some((id, Some(format!("removal task failed: {}", e))))
                 }
             }
-        }).await;
+        }).collect::<Vec<_>>().await;
+
+    let unprocessed = post_ids.into_iter().filter(|id| !attempted.iter().any(|(attempted, _)| attempted == id)).collect::<Vec<_>>();
+
+    // Twitter already acted on these, so they are logged before the queue is touched
+    let event = |(tweet_id, tweet_type): &(String, TweetType)| AuditEvent::new(user_id, (*tweet_type).into(), &request_id).target(tweet_id.as_str());
+    let deferred_reason = match shutdown.is_triggered() {
+        true => "shutting_down",
+        false => "rate_limited",
+    };
+
+    let events = attempted.iter()
+        .map(|(id, error)| match error {
+            Some(error) => event(id).failed(error),
+            None => event(id),
+        })
+        .chain(unprocessed.iter().map(|id| event(id).outcome(AuditOutcome::Deferred).detail(json!({ "reason": deferred_reason }))))
+        .collect();
+    store.audit(events).await;
+
+    // the ids that were queued by a timeline sync leave the queue
+    let removed = attempted.iter().filter(|(_, error)| error.is_none()).map(|(id, _)| id.clone()).collect::<Vec<_>>();
+    store.tweets.set_status(user_id, &removed, PlayStatus::Done, None).await?;
+
+    // failures mostly share their error, so one update is made per error rather than per id
+    let mut failed: HashMap<&str, Vec<(String, TweetType)>> = HashMap::new();
+    for (id, error) in attempted.iter() {
+        if let Some(error) = error {
+            failed.entry(error.as_str()).or_default().push(id.clone());
+        }
+    }
+    for (error, ids) in failed {
+        store.tweets.set_status(user_id, &ids, PlayStatus::Failed, Some(error)).await?;
+    }
+
+    if !unprocessed.is_empty() {
+        // back to the queue, so they are removed once the server is up again
+        let items = unprocessed.iter().map(|(id, tweet_type)| PlayTweet::new(id.clone(), *tweet_type)).collect::<Vec<_>>();
+        store.tweets.insert_tweet_ids(user_id, &items).await?;
 
-    return ResponseBuilder::new("Ok".into(), Some(""), 200).reply();
-}
\ No newline at end of file
+        let detail = format!("{} ids were queued to be removed later", unprocessed.len());
+
+        match shutdown.is_triggered() {
+            true => ResponseBuilder::new("Shutting down".into(), Some(detail), StatusCode::SERVICE_UNAVAILABLE.as_u16()).reply(),
+            false => ResponseBuilder::new("Rate limit reached".into(), Some(detail), StatusCode::TOO_MANY_REQUESTS.as_u16()).reply(),
+        }
+    } else {
+        ResponseBuilder::new("Ok".into(), Some(""), 200).reply()
+    }
+}
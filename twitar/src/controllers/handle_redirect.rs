@@ -1,19 +1,19 @@
 use http::Method;
 use hyper::{StatusCode};
-use sqlx::{Pool, Postgres};
+use serde_json::json;
 use uuid::Uuid;
 use crate::{helpers::{
-    response::{TResult, ApiBody, make_request, ResponseBuilder}, 
-    request::{HyperClient}, keyval::KeyVal, commons::GrantType}, 
-    configurations::variables::SettingsVars, errors::response::{TError}, middlewares::request_builder::{RequestBuilder, AuthType}, 
-    interceptors::handle_request::{Interceptor, V2TokensType}, startup::server::{AppState}, base_repository::db::{V2User, DB, V1User}
+    audit::{AuditAction, AuditEvent}, response::{TResult, ApiBody, make_request, ResponseBuilder}, 
+    transport::HttpClient, keyval::KeyVal, commons::GrantType}, 
+    settings::app::AppSettings, errors::response::{TError}, middlewares::request_builder::{RequestBuilder, AuthType}, 
+    interceptors::handle_request::{Interceptor, V2TokensType}, startup::server::{AppState}, base_repository::{db::{V2User, V1User}, repository::CredentialRepository}
 };
 
 
-async fn access_token(hyper_client: HyperClient, pool: &Pool<Postgres>, _user: &V2User, auth_code: String) -> Result<(), TError> {
-    let SettingsVars{client_id, callback_url, client_secret, twitter_url, ..} = SettingsVars::new();
+async fn access_token(hyper_client: HttpClient, credentials: &dyn CredentialRepository, app: &AppSettings, auth_code: String) -> Result<Uuid, TError> {
+    let AppSettings{client_id, callback_url, client_secret, twitter_api: twitter_url, ..} = app.clone();
     // let V2User {pkce, user_id, ..} = user.v2_user;
-    let user = DB::v2_user(&pool, Uuid::parse_str("1b97475c-4ba1-4ccf-8a62-35baf9ff1075")?).await?;
+    let user = credentials.v2_user(Uuid::parse_str("1b97475c-4ba1-4ccf-8a62-35baf9ff1075")?).await?;
     let V2User {pkce, user_id, ..} = user.unwrap();
     
     let req_body = KeyVal::new().add_list_keyval(vec![
@@ -33,19 +33,19 @@ async fn access_token(hyper_client: HyperClient, pool: &Pool<Postgres>, _user: &
     let res = Interceptor::intercept(make_request(request, hyper_client.clone()).await);
 
     if let Some(map) = Interceptor::v2_tokens(res) {
-        DB::update_secets(pool, map.get(V2TokensType::Access), map.get(V2TokensType::Refresh), user_id).await?;
-        return Ok(())
+        credentials.update_secets(map.get(V2TokensType::Access), map.get(V2TokensType::Refresh), user_id).await?;
+        return Ok(user_id)
     }
 
     return Err(TError::InvalidCredentialError("Required keys are not present".into()))
 }
 
 
-// req: Request<hyper::Body>, hyper_client: HyperClient, redis_client: RedisClient
+// req: Request<hyper::Body>, hyper_client: HttpClient, redis_client: RedisClient
 pub async fn handle_redirect(app_state: AppState) -> TResult<ApiBody> {
     // since this endpoint would be called by the frontend, the <USER> data would be available in the request header. Please note, change the callback URL on twitter developers to the frontend_url
-    let AppState {redis, hyper, db_pool, req, env_vars, user, ..} = app_state;
-    let SettingsVars{state, api_key, twitter_url, ..} = env_vars;
+    let AppState {hyper, req, settings, store, request_id, ..} = app_state;
+    let AppSettings{state_code: state, api_key, twitter_api: twitter_url, ..} = settings.app.clone();
 
     
     // let mut conn = redis.get_async_connection().await?;
@@ -58,14 +58,14 @@ pub async fn handle_redirect(app_state: AppState) -> TResult<ApiBody> {
             // USER WOULD THE PROVIDED BY THE FRONTEND ONCE THIS IS CONNECTED!!!::::
             //  let V1User { oauth_token, user_id, ..} = &user.as_ref().unwrap().v1_user;
 
-            let v1_user = DB::v1_user(&db_pool, Uuid::parse_str("1b97475c-4ba1-4ccf-8a62-35baf9ff1075")?).await?;
+            let v1_user = store.credentials.v1_user(Uuid::parse_str("1b97475c-4ba1-4ccf-8a62-35baf9ff1075")?).await?;
             // let user = DB::v1_user(pool, user_id).await?;
              let V1User { oauth_token, user_id, ..} = v1_user.unwrap();
 
 
             if k.validate("oauth_token".into(),oauth_token.clone()) {
                 let verifier = k.get("oauth_verifier").unwrap();
-                DB::add_oauth_verifier(&db_pool, verifier, user_id.to_owned()).await?;
+                store.credentials.add_oauth_verifier(verifier, user_id.to_owned()).await?;
 
                 let target = format!("{}/oauth/access_token", twitter_url);
 
@@ -86,7 +86,8 @@ pub async fn handle_redirect(app_state: AppState) -> TResult<ApiBody> {
                         let token = map.get("oauth_token").unwrap().to_string();
                         let secret = map.get("oauth_token_secret").unwrap().to_string();
                         
-                        DB::update_v1_secets(&db_pool, token, secret, user_id.to_owned()).await?;
+                        store.credentials.update_v1_secets(token, secret, user_id.to_owned()).await?;
+                        store.audit(vec![AuditEvent::new(user_id, AuditAction::OauthLink, &request_id).detail(json!({ "oauth": "1.0a" }))]).await;
                         
                         return ResponseBuilder::new("Access Granted".into(), Some(""), StatusCode::OK.as_u16()).reply();
                     }
@@ -102,7 +103,8 @@ pub async fn handle_redirect(app_state: AppState) -> TResult<ApiBody> {
             if let Some(dict) = is_v2_callback {
                 if query_params.validate("state".into(), state) {
                     let code = dict.get("code").unwrap().to_string();
-                    access_token(hyper.clone(), &db_pool, &user.unwrap().v2_user, code).await?;
+                    let user_id = access_token(hyper.clone(), store.credentials.as_ref(), &settings.app, code).await?;
+                    store.audit(vec![AuditEvent::new(user_id, AuditAction::OauthLink, &request_id).detail(json!({ "oauth": "2.0" }))]).await;
 
                     return ResponseBuilder::new("Access Granted".into(), Some(""), StatusCode::OK.as_u16()).reply();
                 }
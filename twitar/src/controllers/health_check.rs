@@ -1,8 +1,111 @@
-use hyper::{StatusCode};
+use std::{collections::BTreeMap, future::Future, time::{Duration, Instant}};
+use hyper::{Body, Method, Request, StatusCode};
+use serde::Serialize;
 
 use crate::helpers::response::{TResult, ApiBody, ResponseBuilder};
+use crate::base_repository::repository::Storage;
+use crate::startup::{migrations::{self, MigrationState}, server::AppState};
 
 
+/// How long a dependency gets to answer before it is reported as down
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+
+#[derive(Debug, Serialize)]
+struct Probe {
+    up: bool,
+    latency_ms: u128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Readiness {
+    ready: bool,
+    draining: bool,
+    components: BTreeMap<&'static str, Probe>,
+}
+
+
+/// Times `check`, which reports why the dependency is not usable
+async fn probe<F>(check: F) -> Probe where F: Future<Output = Result<(), String>> {
+    let start = Instant::now();
+
+    let result = match tokio::time::timeout(PROBE_TIMEOUT, check).await {
+        Ok(result) => result,
+        Err(_) => Err(format!("no answer within {}s", PROBE_TIMEOUT.as_secs())),
+    };
+
+    Probe { up: result.is_ok(), latency_ms: start.elapsed().as_millis(), detail: result.err() }
+}
+
+
+/// Liveness (`/healthz`): the process is up and serving, whatever the state of its dependencies
 pub async fn health_check() -> TResult<ApiBody> {
     ResponseBuilder::new("Ok".into(), Some(""), StatusCode::OK.as_u16()).reply()
-}
\ No newline at end of file
+}
+
+/// Readiness: Postgres (with every migration applied) when it is the storage, Redis when it is configured and, when
+/// `app.probe_twitter` is set, the Twitter api can be reached, and the server is not draining
+pub async fn readiness(app_state: AppState) -> TResult<ApiBody> {
+    let AppState { db_pool, redis, hyper, settings, shutdown, .. } = app_state;
+
+    // nothing is kept in Postgres with the other storages
+    let postgres = async {
+        if settings.app.storage != Storage::Postgres {
+            return None;
+        }
+
+        Some(probe(async {
+            sqlx::query("SELECT 1").execute(&db_pool).await.map_err(|e| e.to_string())?;
+
+            let pending = migrations::status(&db_pool).await.map_err(|e| e.to_string())?
+                .into_iter().filter(|m| m.state != MigrationState::Applied).count();
+
+            match pending {
+                0 => Ok(()),
+                n => Err(format!("{} migrations are not applied", n)),
+            }
+        }).await)
+    };
+
+    let redis = async {
+        let redis = redis?;
+
+        Some(probe(async {
+            let mut con = redis.get_async_connection().await.map_err(|e| e.to_string())?;
+            redis::cmd("PING").query_async::<_, String>(&mut con).await.map(|_| ()).map_err(|e| e.to_string())
+        }).await)
+    };
+
+    let (postgres, redis) = futures::join!(postgres, redis);
+    let mut components = BTreeMap::new();
+
+    for (name, probe) in [("postgres", postgres), ("redis", redis)] {
+        if let Some(probe) = probe {
+            components.insert(name, probe);
+        }
+    }
+
+    if settings.app.probe_twitter {
+        // any answer will do, an error status still means Twitter is reachable
+        let twitter = probe(async {
+            let request = Request::builder().method(Method::HEAD).uri(settings.app.twitter_api.as_str())
+                .body(Body::empty()).map_err(|e| e.to_string())?;
+
+            hyper.send(request).await.map(|_| ()).map_err(|e| e.to_string())
+        }).await;
+
+        components.insert("twitter", twitter);
+    }
+
+    let draining = shutdown.is_triggered();
+    let ready = !draining && components.values().all(|c| c.up);
+
+    let (message, code) = match ready {
+        true => ("Ready", StatusCode::OK),
+        false => ("Not ready", StatusCode::SERVICE_UNAVAILABLE),
+    };
+
+    ResponseBuilder::new(message.into(), Some(Readiness { ready, draining, components }), code.as_u16()).reply()
+}
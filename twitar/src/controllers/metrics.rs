@@ -0,0 +1,21 @@
+use hyper::{Body, Response, StatusCode};
+
+use crate::helpers::{metrics::{METRICS, METRICS_CONTENT_TYPE}, response::{TResult, ApiBody}};
+use crate::startup::server::AppState;
+
+
+/// Prometheus scrapes this. The queue depth and the pool usage are read when it does
+pub async fn metrics(app_state: AppState) -> TResult<ApiBody> {
+    let AppState { db_pool, store, .. } = app_state;
+
+    let queued = store.tweets.pending_count().await?;
+
+    METRICS.deletion_queue.set(queued);
+    METRICS.observe_pool(&db_pool);
+
+    let response = Response::builder().status(StatusCode::OK)
+        .header("content-type", METRICS_CONTENT_TYPE)
+        .body(Body::from(METRICS.render())).unwrap();
+
+    Ok(response)
+}
@@ -1,20 +1,21 @@
 use std::{collections::HashMap};
 use hyper::{Method, Response, Body};
+use serde_json::json;
 use uuid::Uuid;
 
 use crate::{
     helpers::{
-        response::{TResult, ApiBody, ResponseBuilder, make_request}, 
+        audit::{AuditAction, AuditEvent}, response::{TResult, ApiBody, ResponseBuilder, make_request}, 
         signature::{OAuth, OAuthAddons}, keypair::KeyPair, keyval::KeyVal,
-    }, configurations::variables::SettingsVars, middlewares::request_builder::{RequestBuilder, AuthType}, startup::server::AppState, base_repository::db::{DB, V1User},
+    }, settings::app::AppSettings, middlewares::request_builder::{RequestBuilder, AuthType}, startup::server::AppState,
 };
 
 
 
 pub async fn request_token(app_state: AppState) -> TResult<ApiBody> {
-    let AppState {hyper, db_pool, env_vars, ..} = app_state;
+    let AppState {hyper, settings, store, request_id, ..} = app_state;
     // let mut con = redis.get_async_connection().await?;
-    let SettingsVars{api_key, api_key_secret, callback_url, twitter_url, ..} = env_vars;
+    let AppSettings{api_key, api_key_secret, callback_url, twitter_api: twitter_url, ..} = settings.app.clone();
 
     // println!("THE CONTENT {:#?}", user);
     
@@ -35,14 +36,16 @@ pub async fn request_token(app_state: AppState) -> TResult<ApiBody> {
         .build_request();
 
     let res = make_request(request, hyper).await;
+    let event = AuditEvent::new(user_id, AuditAction::OauthStart, &request_id).detail(json!({ "oauth": "1.0a" }));
 
-    if let Err(_e) = res {
+    if let Err(e) = res {
+        tracing::warn!(error = %e, "oauth1 request token was not granted");
+        store.audit(vec![event.failed(e)]).await;
         return ResponseBuilder::new("Error".into(), Some("Could not setup the user"), 403).reply()
     }
 
     let (_header, body) = res.unwrap();
     let body = String::from_utf8_lossy(&body).to_string();
-    println!("THE RESPONSE BODY::::::::: {:#?}", body);
     let oauth: Vec<&str> = body.split("&").collect();
 
     // We can work with the result of this mapping but in order to make our code readable and easy to debug in future it would
@@ -54,33 +57,26 @@ pub async fn request_token(app_state: AppState) -> TResult<ApiBody> {
         return ccc
     }).collect::<Vec<_>>();
 
-    println!("THE OAUTH CREDENTIALS------------------------------------>>>>>>>>>>>>>>>>>>>>>>>>>>|||||||||||||||||||| {:#?}", oauth_credentials);
-
     let mut map = HashMap::new();
     // this is all to make readability easier when we get to persisting these information
     oauth_credentials.iter().for_each(|a| { map.insert(a[0].clone(), a[1].clone()); });
 
-    println!("AFTER THE ITER WITH FOR EACH");
-
     if let Some(val) = map.get("oauth_callback_confirmed") {
-        println!("FIRST IF!!!!");
         if val == "true" {
             // redis::cmd("SET").arg(&["oauth_token", map.get("oauth_token").unwrap()]).query_async(&mut con).await?;
             // redis::cmd("SET").arg(&["oauth_token_secret", map.get("oauth_token_secret").unwrap()]).query_async(&mut con).await?;
-            println!("CONTENT OF THE MAP:::::::::::::::::::::: {:#?}", map);
             let oauth_token = map.get("oauth_token").unwrap().to_string();
             let oauth_secret = map.get("oauth_token_secret").unwrap().to_string();
 
-            DB::create_v1_secets(&db_pool, user_id, oauth_token, oauth_secret).await.unwrap();
+            store.credentials.create_v1_secets(user_id, oauth_token, oauth_secret).await.unwrap();
 
-            println!("persistsed:::::::::::::::::::::::::>>>>>>>>>>>>>>>>>>>>>>>>>>>");
+            tracing::info!(%user_id, "oauth1 request token stored");
+            store.audit(vec![event]).await;
 
             let query_dict = KeyVal::new().add_list_keyval(vec![
                 ("oauth_token".to_string(), map.get("oauth_token").unwrap().into())
             ]);
 
-            println!("THE QUERY DICTIONARY {:#?}", query_dict);
-            
             let request = RequestBuilder::new(Method::GET, format!("{}/oauth/authorize", twitter_url))
                 .add_query_params(query_dict)
                 .build_request();
@@ -89,9 +85,6 @@ pub async fn request_token(app_state: AppState) -> TResult<ApiBody> {
                 .header("Location", request.uri().to_string())
                 .body(Body::from(request.uri().to_string())).unwrap();
 
-
-                println!("ABOUTA REDIRECT::::::::::::::::::::::::::::::::::::::::::::::: {:#?}", redirect_to);
-
             return Ok(redirect_to)
         }
     }
@@ -1,11 +1,11 @@
 use hyper::{Method, StatusCode};
 
-use crate::{helpers::{keyval::KeyVal, response::{make_request, TResult, ApiBody, ResponseBuilder}, commons::GrantType}, 
-configurations::variables::SettingsVars, middlewares::request_builder::{RequestBuilder, AuthType}, interceptors::handle_request::{Interceptor, V2TokensType}, startup::server::AppState, base_repository::db::{V2User, DB}};
+use crate::{helpers::{audit::{AuditAction, AuditEvent}, keyval::KeyVal, metrics::METRICS, response::{make_request, TResult, ApiBody, ResponseBuilder}, commons::GrantType}, 
+settings::app::AppSettings, middlewares::request_builder::{RequestBuilder, AuthType}, interceptors::handle_request::{Interceptor, V2TokensType}, startup::server::AppState, base_repository::db::V2User};
 
 pub async fn refresh_token(app_state: AppState) -> TResult<ApiBody> {
-    let AppState {db_pool, hyper, env_vars, user, ..} = app_state;
-    let SettingsVars {client_id, client_secret, twitter_url, ..} = env_vars;
+    let AppState {hyper, settings, user, store, request_id, ..} = app_state;
+    let AppSettings {client_id, client_secret, twitter_api: twitter_url, ..} = settings.app.clone();
 
     let V2User {refresh_token, user_id, ..} = user.unwrap().v2_user;
     let content = "application/x-www-form-urlencoded";
@@ -26,10 +26,13 @@ pub async fn refresh_token(app_state: AppState) -> TResult<ApiBody> {
     let res = Interceptor::intercept(make_request(request, hyper.clone()).await);
 
     if let Some(map) = Interceptor::v2_tokens(res) {
-        DB::update_secets(&db_pool, map.get(V2TokensType::Access), map.get(V2TokensType::Refresh), user_id).await?;
+        store.credentials.update_secets(map.get(V2TokensType::Access), map.get(V2TokensType::Refresh), user_id).await?;
+        store.audit(vec![AuditEvent::new(user_id, AuditAction::TokenRefresh, &request_id)]).await;
         return ResponseBuilder::new("Refresh token obtained".into(), Some(""), StatusCode::OK.as_u16()).reply();
     }
 
+    METRICS.token_refresh_failures.inc();
+    store.audit(vec![AuditEvent::new(user_id, AuditAction::TokenRefresh, &request_id).failed("Twitter did not return new tokens")]).await;
     return ResponseBuilder::new("Error connecting to your Twitter account".into(), Some(""), 400).reply();
 
 }
\ No newline at end of file
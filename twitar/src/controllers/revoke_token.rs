@@ -1,9 +1,10 @@
 use hyper::{StatusCode, Method};
 use serde::{Serialize, Deserialize};
+use serde_json::json;
 
 use crate::{helpers::{
-    keyval::KeyVal, response::{TResult, ApiBody, make_request, ResponseBuilder}}, 
-    configurations::variables::SettingsVars, middlewares::request_builder::{RequestBuilder, AuthType}, 
+    audit::{AuditAction, AuditEvent}, keyval::KeyVal, response::{TResult, ApiBody, make_request, ResponseBuilder}}, 
+    settings::app::AppSettings, middlewares::request_builder::{RequestBuilder, AuthType}, 
     interceptors::handle_request::Interceptor, startup::server::AppState, base_repository::db::V2User
 };
 
@@ -15,9 +16,9 @@ struct ApiResponse {
 }
 
 pub async fn revoke_token(app_state: AppState) -> TResult<ApiBody> {
-    let AppState { hyper, user, env_vars, ..} = app_state;
-    let SettingsVars{client_id, client_secret, twitter_url, ..} = env_vars;
-    let V2User { access_token, ..} = user.unwrap().v2_user;
+    let AppState { hyper, user, settings, store, request_id, ..} = app_state;
+    let AppSettings{client_id, client_secret, twitter_api: twitter_url, ..} = settings.app.clone();
+    let V2User { access_token, user_id, ..} = user.unwrap().v2_user;
 
     let req_body = KeyVal::new().add_list_keyval(vec![
         ("token".into(), access_token.unwrap()),
@@ -32,12 +33,18 @@ pub async fn revoke_token(app_state: AppState) -> TResult<ApiBody> {
         .with_body(req_body, content_type).build_request();
 
     let res = Interceptor::intercept(make_request(request, hyper).await);
+    let event = AuditEvent::new(user_id, AuditAction::OauthUnlink, &request_id);
 
     if let Err(e) = res {
+        store.audit(vec![event.detail(json!(&e.0)).failed(format!("Twitter answered {}", e.1))]).await;
         return ResponseBuilder::new("Error".into(), Some(e.0), e.1).reply()
     }
     // let body: ApiResponse = serde_json::from_slice(&body)?;
 
+    // the cached copy still carries the revoked token
+    store.credentials.invalidate(user_id).await;
+    store.audit(vec![event]).await;
+
     ResponseBuilder::new("Access revoked".into(), Some(""), StatusCode::OK.as_u16()).reply()
 
 }
\ No newline at end of file
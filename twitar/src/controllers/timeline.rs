@@ -1,137 +1,109 @@
-use std::{collections::HashMap, sync::{Arc, RwLock}};
-use hyper::{StatusCode, Method};
+use std::ops::RangeInclusive;
+use hyper::StatusCode;
+use serde_json::json;
 use futures::{stream, StreamExt};
-use serde::{Serialize, Deserialize};
 use tokio;
 
 use crate::{
-    helpers::{response::{TResult, ApiBody, ResponseBuilder, make_request, TwitterResponseData}}, 
-    middlewares::request_builder::{RequestBuilder, AuthType}, interceptors::handle_request::Interceptor, configurations::variables::SettingsVars, startup::server::AppState, base_repository::db::{DB, V2User}
+    errors::response::TError,
+    helpers::{response::{TResult, ApiBody, ResponseBuilder, TwitterResponse, RECENT_TWEETS_KEPT}, query::{V2Query, TweetField, TIMELINE_RESULTS, LIKED_TWEETS_RESULTS}, paginator::Paginator}, 
+    settings::app::AppSettings, startup::server::AppState, base_repository::db::V2User,
+    models::Tweet,
 };
 
-use crate::helpers::db_helper::{TweetType, AllTweetIds, TweetIds};
+use crate::helpers::audit::{AuditAction, AuditEvent};
+use crate::helpers::db_helper::{PlayTweet, TweetType};
+use crate::helpers::lease::Job;
+use crate::helpers::rate_limit::BudgetOwner;
 
-#[derive(Debug, Serialize, Deserialize)]
-enum TimelineBody {
-    Data(Vec<String>),
-    Meta(HashMap<String, String>)
-}
-
-const MAX_TWEETS: &'static str = "100";
+const MAX_TWEETS: u32 = 100;
 
+/// Stores the ids of the timeline for removal, one sync or removal of a user at a time so no id is queued twice
 pub async fn get_timeline(app_state: AppState) -> TResult<ApiBody> {
-    
-    let AppState {hyper, env_vars, db_pool, user, ..} = app_state;
-    let SettingsVars { twitter_url, ..} = env_vars;
+    let user_id = app_state.user.as_ref().unwrap().basic.user_id;
+    let lease = app_state.store.jobs.acquire(user_id, Job::TimelineSync).await?;
 
-    let V2User { twitter_user_id, access_token, user_id, .. } = user.unwrap().v2_user;
+    let response = sync_timeline(app_state).await;
+    lease.release().await;
 
-    let twitter_user = twitter_user_id.unwrap();
-    
-    let get_url = |path: &'static str| -> RequestBuilder {
-        RequestBuilder::new
-        (Method::GET, format!("{}/2/users/{}/{}", twitter_url, twitter_user, path))
-        .with_auth(AuthType::Bearer, access_token.clone().unwrap())
-    };
+    response
+}
 
-    let requests = vec![TweetType::Tweets, TweetType::Likes];
+async fn sync_timeline(app_state: AppState) -> TResult<ApiBody> {
+    let AppState {hyper, settings, store, user, rate_limits, request_id, ..} = app_state;
+    let AppSettings { twitter_api: twitter_url, client_id, ..} = settings.app.clone();
 
-    let bodies = stream::iter(requests).map(|tweet| {
-        let client = hyper.clone();
+    let V2User { twitter_user_id, access_token, user_id, .. } = user.unwrap().v2_user;
 
-        let req = match tweet {
-            TweetType::Tweets | TweetType::Rts => {
-                get_url("tweets").with_query("max_results", MAX_TWEETS).build_request()
-            }
-            TweetType::Likes => {
-                get_url("liked_tweets").with_query("max_results", MAX_TWEETS).build_request()
-            }
-        };
+    let twitter_user = twitter_user_id.ok_or(TError::Unauthenticated("Twitter user id has not been looked up"))?;
+    let access_token = access_token.ok_or(TError::Unauthenticated("OAuth2 is not connected"))?;
+    let owner = BudgetOwner::new(&client_id, &twitter_user);
+    
+    // referenced_tweets tells retweets apart from tweets that merely start with "RT", the rest is kept with the queued ids
+    let query = V2Query::new()
+        .tweet_fields([TweetField::CreatedAt, TweetField::ReferencedTweets, TweetField::PublicMetrics]);
+
+    let paginate = |path: &'static str, page_sizes: RangeInclusive<u32>| -> Paginator<Tweet> {
+        let url = format!("{}/2/users/{}/{}", twitter_url, twitter_user, path);
+
+        Paginator::new(hyper.clone(), url, access_token.clone())
+            .with_query(query.clone().max_results_range(page_sizes))
+            .page_size(MAX_TWEETS)
+            .max_items(MAX_TWEETS as usize)
+            .with_rate_limiter(rate_limits.clone())
+            .with_budget(owner.clone())
+    };
 
+    let requests = vec![
+        (TweetType::Tweets, paginate("tweets", TIMELINE_RESULTS)),
+        (TweetType::Likes, paginate("liked_tweets", LIKED_TWEETS_RESULTS)),
+    ];
 
+    let bodies = stream::iter(requests).map(|(tweet, paginator)| {
         tokio::spawn(async move {
-            let response = Interceptor::intercept(make_request(req, client).await);
+            // MAX_TWEETS fit in the first page
+            let response = paginator.pages().next().await
+                .unwrap_or_else(|| Ok(TwitterResponse::default()));
             (tweet, response)
         })
     }).buffer_unordered(2);
 
 
 
-    let res_body: Arc<RwLock<HashMap<String, TimelineBody>>> = Arc::new(RwLock::new(HashMap::new()));
-
-    bodies.for_each(|res| async {
-        // let mut response: HashMap<String, TimelineBody> = HashMap::new();
-        match res {
-            Ok(body) => {
-                let dic_body: TwitterResponseData = serde_json::from_value(body.1.unwrap()).unwrap();
-                
-                println!("\n\n THE VEC BODY {:#?} \n\n", dic_body);
-                
-                if body.0 == TweetType::Tweets {
-                    let parsed_body = dic_body.separate_tweets_from_rts(true);
-
-                    let keys = parsed_body.keys().cloned().collect::<Vec<_>>();
-                    let tweets_and_rts = keys.iter()
-                        .map(|k| (k.into(), TimelineBody::Data(parsed_body.get(k).unwrap().to_vec())))
-                        .collect::<HashMap<String, TimelineBody>>();
-
-                    let mut res_body = res_body.write().unwrap();
-                    res_body.extend(tweets_and_rts);
-                    // res_body.get().extend(tweets_and_rts);
-                    // this point should mean inserting it into the db
-                } else {
-                    // If the tweet response is a dictionary, get all the ids of the likes
-                    let like_ids = dic_body.get_ids();
-                    let mut res_body = res_body.write().unwrap();
-                    res_body.insert("likes".into(), TimelineBody::Data(like_ids));
-                }
-
-                let meta = dic_body.parse_metadata();
-                let meta_name = format!("meta_{}", body.0.to_string());
-                let mut res_body = res_body.write().unwrap();
-                res_body.insert(meta_name, TimelineBody::Meta(meta));
-            }
-            Err(e) => {
-                // errors = true;
-                // how do I intend to handle the errors in this case????
-                eprintln!("ERROR LELEYI {:#?}", e);
-            }
-        }
-    }).await;
-
-    let read_all = Arc::try_unwrap(res_body).unwrap().into_inner().unwrap();
-    let mut tweets: TweetIds = vec![];
-    let mut rts: TweetIds = vec![];
-    let mut likes: TweetIds = vec![];
+    let mut queued: Vec<PlayTweet> = vec![];
+    let event = AuditEvent::new(user_id, AuditAction::TimelineSync, &request_id);
 
-    let tweet_types = TweetType::get_all_as_vec();
+    for res in bodies.collect::<Vec<_>>().await {
+        let fetched = res.map_err(|e| TError::UnexpectedError(anyhow::anyhow!("timeline task failed: {}", e)))
+            .and_then(|(tweet_type, response)| response.map(|dic_body| (tweet_type, dic_body)));
 
+        let (tweet_type, dic_body) = match fetched {
+            Ok(fetched) => fetched,
+            // nothing is queued from a sync that could not see the whole timeline
+            Err(e) => {
+                tracing::warn!(error = %e, "unable to fetch the timeline");
+                store.audit(vec![event.failed(&e)]).await;
+                return Err(e);
+            }
+        };
 
-    for tweet_type in tweet_types {
-        let map = &*read_all.get(&tweet_type.to_string()).unwrap();
-            
-        if let TimelineBody::Data(data) = map {
-            let mut ids: Vec<&String> = vec![];
+        tracing::debug!(%tweet_type, "timeline page fetched");
 
-            for index in 0..data.len() {
-                ids.push(&data[index]);
+        queued.extend(match tweet_type {
+            // the most recent tweets are left alone
+            TweetType::Tweets => dic_body.tweets().iter().skip(RECENT_TWEETS_KEPT).map(PlayTweet::from_timeline).collect::<Vec<_>>(),
+            _ => dic_body.tweets().iter().map(|tweet| PlayTweet::from_tweet(tweet, tweet_type)).collect(),
+        });
+    }
 
-                if ids.len() == 10 || index == data.len() - 1 {
-                    match tweet_type {
-                        TweetType::Likes => {likes.push(ids);}
-                        TweetType::Rts => {rts.push(ids)}
-                        TweetType::Tweets => {tweets.push(ids)}
-                    }
+    let event = event.detail(json!({ "queued": queued.len() }));
 
-                    ids = vec![];
-                }
-            }
-        }
+    if let Err(e) = store.tweets.insert_tweet_ids(user_id, &queued).await {
+        store.audit(vec![event.failed(&e)]).await;
+        return Err(e);
     }
 
-    let formatted_ids = AllTweetIds::new(tweets, rts, likes);
-
-    DB::insert_tweet_ids(&db_pool, user_id, formatted_ids).await?;
+    store.audit(vec![event]).await;
 
     ResponseBuilder::new("Ok".into(), Some("Success"), StatusCode::OK.as_u16()).reply()
 }
@@ -1,34 +1,41 @@
 use hyper::{Method, StatusCode};
+use serde_json::json;
+use twitar_macro::{Extract, Validate};
 
 use crate::{helpers::{
-    response::{ResponseBuilder, TResult, ApiBody, make_request, TwitterResponseHashData}, request::req_query}, 
+    audit::{AuditAction, AuditEvent}, rate_limit::BudgetOwner, response::{ResponseBuilder, TResult, ApiBody}, request::extract_query}, 
     middlewares::request_builder::{RequestBuilder, AuthType}, 
-    interceptors::handle_request::Interceptor, configurations::variables::SettingsVars, startup::server::AppState, base_repository::db::{V2User, DB}
+    interceptors::handle_request::Interceptor, settings::app::AppSettings, startup::server::AppState, base_repository::db::V2User,
+    models::User, errors::response::TError,
 };
 
 
+#[derive(Debug, Extract, Validate)]
+struct LookupQuery {
+    /// Twitter handles are at most 15 characters long
+    #[extract(query)]
+    #[validate(length(min = 1, max = 15))]
+    username: String,
+}
+
 // use this endpoint to verify the validity of the username when they want to request for their timeline when using OAuth2.0
 pub async fn user_lookup(app_state: AppState) -> TResult<ApiBody> {
     // todo!() move this to params once route management is migrated to routerify
-    let AppState{req, hyper, user, db_pool, env_vars, ..} = app_state;
-    let SettingsVars {twitter_url, ..} = env_vars;
+    let AppState{req, hyper, user, settings, store, rate_limits, request_id, ..} = app_state;
+    let AppSettings {twitter_api: twitter_url, client_id, ..} = settings.app.clone();
     let V2User { user_id, access_token, ..} = user.unwrap().v2_user;
-    let username = req_query(req.uri().query(), "username").unwrap();
-    let access_token= access_token.unwrap();
+    let LookupQuery { username } = extract_query(&req)?;
+    let access_token = access_token.ok_or(TError::Unauthenticated("OAuth2 is not connected"))?;
  
     let req = RequestBuilder::new(Method::GET, format!("{}/2/users/by/username/{}", twitter_url, username))
         .with_auth(AuthType::Bearer, access_token).build_request();
 
-    let res= Interceptor::intercept(make_request(req, hyper.clone()).await);
-
-    if let Err(e) = res {
-        return ResponseBuilder::new("Error".into(), Some(e.0), e.1).reply();
-    }
-
-    let body: TwitterResponseHashData = serde_json::from_value(res.unwrap()).unwrap();
-    let user = body.into_one_dict();
-    let twitter_user_id = user.get("id").unwrap();
+    // the Twitter id of the user is what this looks up, so the budget is the one of their twitar id
+    let owner = BudgetOwner::new(&client_id, &user_id.to_string());
+    let body = Interceptor::partial::<User>(rate_limits.send(&owner, req, hyper.clone()).await)?;
+    let user = body.data.ok_or_else(|| anyhow::anyhow!("Twitter returned no user for {}", username))?;
 
-    DB::update_twitter_id(&db_pool, twitter_user_id, user_id).await?;
+    store.credentials.update_twitter_id(&user.id, user_id).await?;
+    store.audit(vec![AuditEvent::new(user_id, AuditAction::ConfigChange, &request_id).detail(json!({ "twitter_user_id": user.id }))]).await;
     ResponseBuilder::new("Ok".into(), Some(""), StatusCode::OK.as_u16()).reply()
 }
\ No newline at end of file
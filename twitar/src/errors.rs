@@ -1,2 +1,3 @@
 pub mod response;
-pub mod twitter_errors;
\ No newline at end of file
+pub mod twitter_errors;
+pub mod envelope;
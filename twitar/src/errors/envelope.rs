@@ -0,0 +1,130 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+use hyper::{Body, Response, StatusCode, header::{CONTENT_TYPE, RETRY_AFTER}};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::errors::response::TError;
+use crate::helpers::response::ApiBody;
+
+#[cfg(test)]
+#[path = "./envelope.test.rs"]
+mod envelope_test;
+
+
+pub const X_REQUEST_ID: &str = "x-request-id";
+
+
+/// The body of every error response returned by the api
+#[derive(Debug, Serialize)]
+pub struct ErrorEnvelope {
+    pub status: u16,
+    /// A stable, machine readable name for the error
+    pub code: &'static str,
+    pub message: String,
+    pub request_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<Value>,
+}
+
+
+impl TError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::BadQueryParamsError(_) | Self::UuidError(_) | Self::BadRequest(_) => StatusCode::BAD_REQUEST,
+            Self::ValidationError(_) | Self::Unprocessable(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::InvalidCredentialError(_) | Self::InvalidUserId(_) | Self::Unauthenticated(_) => StatusCode::UNAUTHORIZED,
+            Self::Forbidden(_) => StatusCode::FORBIDDEN,
+            Self::Conflict(_) => StatusCode::CONFLICT,
+            Self::RateLimit(_) => StatusCode::TOO_MANY_REQUESTS,
+            Self::TwitterError(failure) => failure.1.status()
+                .and_then(|status| StatusCode::from_u16(status).ok())
+                .unwrap_or(StatusCode::BAD_GATEWAY),
+            Self::NetworkError(_) | Self::ApiResponseError { .. } | Self::BadStatus(_)
+                | Self::DeserializeError(_) => StatusCode::BAD_GATEWAY,
+            Self::RedisStoreError(_) => StatusCode::SERVICE_UNAVAILABLE,
+            Self::InvalidStatusCode(_) | Self::UnexpectedError(_) | Self::DatabaseError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NetworkError(_) => "network_error",
+            Self::BadQueryParamsError(_) => "bad_query_params",
+            Self::ApiResponseError { .. } => "api_response_error",
+            Self::BadStatus(_) => "upstream_bad_status",
+            Self::DeserializeError(_) => "upstream_bad_body",
+            Self::InvalidCredentialError(_) => "invalid_credentials",
+            Self::InvalidStatusCode(_) => "internal_error",
+            Self::RateLimit(_) => "rate_limited",
+            Self::RedisStoreError(_) => "datastore_unavailable",
+            Self::TwitterError(..) => "twitter_error",
+            Self::UnexpectedError(_) => "internal_error",
+            Self::ValidationError(_) => "validation_error",
+            Self::DatabaseError(_) => "database_error",
+            Self::InvalidUserId(_) => "invalid_user_id",
+            Self::UuidError(_) => "invalid_uuid",
+            Self::Unauthenticated(_) => "unauthenticated",
+            Self::Forbidden(_) => "forbidden",
+            Self::Conflict(_) => "job_in_progress",
+            Self::BadRequest(_) => "bad_request",
+            Self::Unprocessable(_) => "unprocessable_entity",
+        }
+    }
+
+    /// Structured context for the client: the errors Twitter returned or the offending request fields
+    pub fn details(&self) -> Option<Value> {
+        match self {
+            Self::TwitterError(failure) => serde_json::to_value(&failure.1).ok(),
+            Self::BadRequest(e) => serde_json::to_value(e).ok(),
+            Self::Unprocessable(e) => serde_json::to_value(e).ok(),
+            Self::BadStatus(status) => Some(serde_json::json!({ "upstream_status": status.as_u16() })),
+            Self::Conflict(job) => Some(serde_json::json!({ "running_job": job })),
+            _ => None,
+        }
+    }
+
+    /// Seconds the client should wait before retrying
+    pub fn retry_after(&self) -> Option<u64> {
+        match self {
+            Self::RateLimit(reset) => {
+                let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+                Some(reset.map_or(60, |reset| reset.saturating_sub(now).max(1)))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn envelope(&self, request_id: &str) -> ErrorEnvelope {
+        let status = self.status();
+
+        // the internals of server side failures are not meant for the client
+        let message = match status {
+            StatusCode::INTERNAL_SERVER_ERROR => "Internal server error".to_string(),
+            _ => self.to_string(),
+        };
+
+        ErrorEnvelope {
+            status: status.as_u16(),
+            code: self.code(),
+            message,
+            request_id: request_id.to_string(),
+            details: self.details(),
+        }
+    }
+
+    pub fn into_response(self, request_id: &str) -> ApiBody {
+        let envelope = self.envelope(request_id);
+        let body = serde_json::to_string(&envelope).unwrap_or_default();
+
+        let mut response = Response::builder()
+            .status(envelope.status)
+            .header(CONTENT_TYPE, "application/json")
+            .header(X_REQUEST_ID, request_id);
+
+        if let Some(seconds) = self.retry_after() {
+            response = response.header(RETRY_AFTER, seconds);
+        }
+
+        response.body(Body::from(body)).unwrap()
+    }
+}
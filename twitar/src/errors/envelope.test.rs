@@ -0,0 +1,92 @@
+mod test_envelope {
+    use hyper::{StatusCode, header::RETRY_AFTER};
+    use serde_json::Value;
+    use twitar_macro::{ExtractError, Source};
+
+    use crate::errors::{envelope::X_REQUEST_ID, response::{TError, TwitterErrors, TwitterErrorCodes}};
+    use crate::errors::twitter_errors::{TwitterApiError, Problem};
+    use crate::helpers::response::THeaders;
+
+    async fn reply(err: TError) -> (StatusCode, Value, Option<String>) {
+        let response = err.into_response("req-1");
+        let status = response.status();
+        assert_eq!(response.headers().get(X_REQUEST_ID).unwrap(), "req-1");
+
+        let retry_after = response.headers().get(RETRY_AFTER).map(|v| v.to_str().unwrap().to_string());
+        let bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+
+        (status, serde_json::from_slice(&bytes).unwrap(), retry_after)
+    }
+
+    #[tokio::test]
+    async fn maps_errors_to_status_and_code() {
+        let (status, body, _) = reply(TError::InvalidUserId("User does not exist")).await;
+
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["status"], 401);
+        assert_eq!(body["code"], "invalid_user_id");
+        assert_eq!(body["message"], "User does not exist");
+        assert_eq!(body["request_id"], "req-1");
+    }
+
+    #[tokio::test]
+    async fn hides_internal_failures() {
+        let (status, body, _) = reply(TError::DatabaseError(sqlx::Error::PoolTimedOut)).await;
+
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(body["code"], "database_error");
+        assert_eq!(body["message"], "Internal server error");
+    }
+
+    #[tokio::test]
+    async fn keeps_the_errors_returned_by_twitter() {
+        let errors = TwitterErrors { errors: vec![TwitterErrorCodes { message: "No status found with that ID.".into(), code: 144 }] };
+        let (status, body, _) = reply(TError::twitter(THeaders::new(), TwitterApiError::V1(errors))).await;
+
+        assert_eq!(status, StatusCode::BAD_GATEWAY);
+        assert_eq!(body["code"], "twitter_error");
+        assert_eq!(body["details"]["errors"][0]["code"], 144);
+    }
+
+    #[tokio::test]
+    async fn passes_on_expired_credentials() {
+        let problem = Problem {
+            kind: "about:blank".into(), title: "Unauthorized".into(), detail: None, status: Some(401), errors: vec![]
+        };
+        let (status, body, _) = reply(TError::twitter(THeaders::new(), TwitterApiError::V2(problem))).await;
+
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["details"]["title"], "Unauthorized");
+    }
+
+    #[tokio::test]
+    async fn explains_request_errors() {
+        let err = TError::BadRequest(ExtractError::Missing { field: "username", source: Source::Query });
+        let (status, body, _) = reply(err).await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["details"]["field"], "username");
+    }
+
+    #[tokio::test]
+    async fn rate_limits_tell_when_to_retry() {
+        let (status, body, retry_after) = reply(TError::RateLimit(None)).await;
+
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(body["code"], "rate_limited");
+        assert_eq!(retry_after, Some("60".into()));
+
+        let (_, _, retry_after) = reply(TError::RateLimit(Some(1))).await;
+        assert_eq!(retry_after, Some("1".into()));
+    }
+
+    #[tokio::test]
+    async fn names_the_job_holding_the_lease() {
+        let (status, body, _) = reply(TError::Conflict("remove".into())).await;
+
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["code"], "job_in_progress");
+        assert_eq!(body["message"], "remove is already running for this user");
+        assert_eq!(body["details"]["running_job"], "remove");
+    }
+}
@@ -7,6 +7,7 @@ use std::{fmt::{self, Formatter}, collections::HashMap};
 use hyper::{Error as HError};
 use serde_json;
 
+use crate::errors::twitter_errors::TwitterApiError;
 use crate::helpers::response::THeaders;
 
 // pub fn error_chain_fmt(
@@ -43,7 +44,6 @@ impl fmt::Display for TwitterErrors {
             }
 
             write!(f, "{}", e)?;
-            println!("WHAT THE FN LOOKS LIKE {:#?}", e);
         }
 
 
@@ -70,11 +70,11 @@ impl fmt::Display for TwitterErrorCodes {
 #[derive(thiserror::Error, Debug)]
 pub enum TError {
     /// This error is encountered when there is problem deserializing the response body
-    #[error("Network Error: {}", 0)]
+    #[error("Network Error: {0}")]
     NetworkError(#[from] HError),
     #[error("Error parsing query params on uri")]
     BadQueryParamsError(#[from] ParseError),
-    #[error("Error processing request: {}", 0)]
+    #[error("Error processing request: {message}")]
     ApiResponseError{message: &'static str},
     #[error("Error Status: {}", _0)]
     BadStatus(hyper::StatusCode),
@@ -82,14 +82,16 @@ pub enum TError {
     DeserializeError(#[from] serde_json::Error),
     #[error("Values do not match")]
     InvalidCredentialError(String),
-    #[error("Invalid Status code {}", 0)]
+    #[error("Invalid Status code {0}")]
     InvalidStatusCode(#[from] InvalidStatusCode),
-    #[error("Rate Limit exceeded, please try again in")]
-    RateLimit(),
+    /// Holds the unix timestamp (seconds) at which the rate limit window resets, when Twitter provides it
+    #[error("Rate Limit exceeded, please try again later")]
+    RateLimit(Option<u64>),
     #[error("DataStore error")]
     RedisStoreError(#[from] RedisError),
-    #[error("Error returned by Twitter: {1}")]
-    TwitterError(THeaders, TwitterErrors),
+    /// Boxed, so that every `TResult` stays small
+    #[error("Error returned by Twitter: {}", _0.1)]
+    TwitterError(Box<(THeaders, TwitterApiError)>),
     #[error(transparent)]
     UnexpectedError(#[from] anyhow::Error),
     #[error("{0}")]
@@ -103,13 +105,27 @@ pub enum TError {
     #[error("Unauthenticated: {0}")]
     Unauthenticated(&'static str),
     #[error("Unauthorized")]
-    Forbidden(String)
+    Forbidden(String),
+    /// Holds the name of the job already running for the user, e.g. `remove`
+    #[error("{0} is already running for this user")]
+    Conflict(String),
+    #[error("Bad request: {0}")]
+    BadRequest(#[from] twitar_macro::ExtractError),
+    #[error("Invalid request: {0}")]
+    Unprocessable(#[from] twitar_macro::ValidationErrors),
     // #[error("")]
     // PStoreTokenError(#[from] ())
 }
 
 
 
+impl TError {
+    pub fn twitter(headers: THeaders, error: TwitterApiError) -> Self {
+        Self::TwitterError(Box::new((headers, error)))
+    }
+}
+
+
 // impl fmt::Debug for TError {
 //     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
 //         println!("WITHIN THE ERROR CHAIN");
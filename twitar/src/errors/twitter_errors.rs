@@ -1,8 +1,102 @@
-use std::collections::HashMap;
-
+use std::fmt;
 use serde::{Serialize, Deserialize};
+use serde_json::Value;
+
+use crate::errors::response::TwitterErrors;
+
+#[cfg(test)]
+#[path = "./twitter_errors.test.rs"]
+mod twitter_errors_test;
+
+
+/// Twitter's (v1.1) code for an invalid or expired token
+const INVALID_TOKEN_CODE: i32 = 89;
+
+
+/// An RFC 7807 problem, returned by the v2 api when a request fails as a whole
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Problem {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    /// Invalid request problems list the offending parameters here
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<Value>,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.detail {
+            Some(detail) => write!(f, "{}: {}", self.title, detail),
+            None => write!(f, "{}", self.title),
+        }
+    }
+}
+
+
+/// An item the v2 api could not return, sent in `errors` next to the `data` of the items it could
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PartialError {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parameter: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+}
+
+impl fmt::Display for PartialError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.detail.as_ref().unwrap_or(&self.title))
+    }
+}
+
+
+/// The error bodies of both generations of the Twitter api
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TwitterApiError {
+    /// `{"errors": [{"code": 89, "message": ".."}]}`
+    V1(TwitterErrors),
+    /// `{"type": "..", "title": "..", "detail": "..", "status": 401}`
+    V2(Problem),
+    /// `{"errors": [{"type": "..", "title": "..", "resource_id": ".."}]}` without any `data`
+    Partial { errors: Vec<PartialError> },
+}
+
+impl TwitterApiError {
+    /// The HTTP status that best describes the failure to our own clients, when it is more specific than a bad gateway
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Self::V1(errors) if errors.errors.iter().any(|e| e.code == INVALID_TOKEN_CODE) => Some(401),
+            Self::V2(problem) => problem.status.filter(|s| [401, 403, 404].contains(s)),
+            _ => None,
+        }
+    }
+}
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct TwitterResponseError {
-    pub errors: Vec<HashMap<String, String>>
-}
\ No newline at end of file
+impl fmt::Display for TwitterApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::V1(errors) => write!(f, "{}", errors),
+            Self::V2(problem) => write!(f, "{}", problem),
+            Self::Partial { errors } => {
+                let details = errors.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+                write!(f, "{}", details.join(", "))
+            }
+        }
+    }
+}
@@ -0,0 +1,60 @@
+mod test_twitter_errors {
+    use serde_json::json;
+
+    use crate::errors::twitter_errors::{TwitterApiError, Problem};
+
+    #[test]
+    fn parses_v1_errors() {
+        let body = json!({"errors": [{"code": 89, "message": "Invalid or expired token."}]});
+        let err: TwitterApiError = serde_json::from_value(body).unwrap();
+
+        assert!(matches!(err, TwitterApiError::V1(_)));
+        assert_eq!(err.status(), Some(401));
+        assert_eq!(err.to_string(), "89: Invalid or expired token.");
+    }
+
+    #[test]
+    fn parses_v2_problems() {
+        let body = json!({"title": "Unauthorized", "type": "about:blank", "status": 401, "detail": "Unauthorized"});
+        let err: TwitterApiError = serde_json::from_value(body).unwrap();
+
+        assert_eq!(err.status(), Some(401));
+        assert_eq!(err.to_string(), "Unauthorized: Unauthorized");
+    }
+
+    #[test]
+    fn parses_v2_invalid_requests() {
+        let body = json!({
+            "errors": [{"parameters": {"ids": [""]}, "message": "The `ids` query parameter value [] is not valid"}],
+            "title": "Invalid Request",
+            "detail": "One or more parameters to your request was invalid.",
+            "type": "https://api.twitter.com/2/problems/invalid-request"
+        });
+        let err: TwitterApiError = serde_json::from_value(body).unwrap();
+
+        match err {
+            TwitterApiError::V2(Problem { errors, status, .. }) => {
+                assert_eq!(errors.len(), 1);
+                assert_eq!(status, None);
+            }
+            other => panic!("expected a problem, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parses_errors_without_data() {
+        let body = json!({"errors": [{
+            "value": "20", "detail": "Could not find tweet with ids: [20].", "title": "Not Found Error",
+            "resource_type": "tweet", "parameter": "ids", "resource_id": "20",
+            "type": "https://api.twitter.com/2/problems/resource-not-found"
+        }]});
+        let err: TwitterApiError = serde_json::from_value(body).unwrap();
+
+        match &err {
+            TwitterApiError::Partial { errors } => assert_eq!(errors[0].resource_id.as_deref(), Some("20")),
+            other => panic!("expected partial errors, got {:?}", other),
+        }
+        assert_eq!(err.status(), None);
+        assert_eq!(err.to_string(), "Could not find tweet with ids: [20].");
+    }
+}
@@ -1,5 +1,17 @@
 pub mod gen_pkce;
 pub mod scope;
+pub mod query;
+pub mod rate_limit;
+pub mod shared;
+pub mod credentials;
+pub mod lease;
+pub mod audit;
+pub mod archive;
+pub mod metrics;
+pub mod shutdown;
+pub mod telemetry;
+pub mod paginator;
+pub mod transport;
 pub mod response;
 pub mod keypair;
 pub mod request;
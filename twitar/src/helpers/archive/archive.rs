@@ -0,0 +1,117 @@
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::errors::{response::TError, twitter_errors::TwitterApiError};
+use crate::helpers::{
+    db_helper::TweetType, paginator::Paginator, query::{Expansion, MediaField, TweetField, V2Query, MAX_IDS},
+    rate_limit::{BudgetOwner, RateLimiter}, response::{TResult, TwitterResponse}, transport::HttpClient,
+};
+use crate::models::{includes::Includes, Tweet};
+
+#[cfg(test)]
+#[path = "./archive.test.rs"]
+mod archive_test;
+
+
+/// A tweet, retweet or like as it was right before it was removed. It is kept once Twitter no longer has it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ArchivedTweet {
+    pub tweet_id: String,
+    pub tweet_type: TweetType,
+    pub text: String,
+    pub author_id: Option<String>,
+    /// Hashtags, mentions, urls and the like, as Twitter returned them
+    pub entities: Option<Value>,
+    /// `public_metrics` as Twitter returned them
+    pub metrics: Option<Value>,
+    /// The attached media, with their urls
+    pub media: Option<Value>,
+    /// `[{ "type": "retweeted", "id": "20", "tweet": { .. } }]`, `tweet` is left out when Twitter did not return it
+    pub referenced_tweets: Option<Value>,
+    /// When the tweet was posted
+    pub created_at: Option<DateTime<Utc>>,
+    pub archived_at: DateTime<Utc>,
+}
+
+impl ArchivedTweet {
+    pub fn from_lookup(tweet: &Tweet, tweet_type: TweetType, includes: Option<&Includes>) -> Self {
+        let media = tweet.attachments.as_ref()
+            .map(|a| a.media_keys.iter().filter_map(|key| includes.and_then(|i| i.media_by_key(key))).collect::<Vec<_>>())
+            .filter(|media| !media.is_empty())
+            .map(|media| json!(media));
+
+        let referenced_tweets = tweet.referenced_tweets.iter().map(|reference| {
+            let mut value = json!(reference);
+
+            if let Some(referenced) = includes.and_then(|i| i.tweets.iter().find(|t| t.id == reference.id)) {
+                value["tweet"] = json!(referenced);
+            }
+
+            value
+        }).collect::<Vec<_>>();
+
+        Self {
+            tweet_id: tweet.id.clone(),
+            tweet_type,
+            text: tweet.text.clone(),
+            author_id: tweet.author_id.clone(),
+            entities: tweet.entities.clone(),
+            metrics: tweet.public_metrics.as_ref().map(|m| json!(m)),
+            media,
+            referenced_tweets: Some(json!(referenced_tweets)).filter(|_| !referenced_tweets.is_empty()),
+            created_at: tweet.created_at,
+            archived_at: Utc::now(),
+        }
+    }
+}
+
+
+fn lookup_query(ids: &[String]) -> V2Query {
+    V2Query::new()
+        .ids(ids.iter().cloned())
+        .tweet_fields([
+            TweetField::AuthorId, TweetField::CreatedAt, TweetField::Entities, TweetField::PublicMetrics,
+            TweetField::ReferencedTweets, TweetField::Attachments,
+        ])
+        .expansions([Expansion::MediaKeys, Expansion::ReferencedTweets])
+        .media_fields([MediaField::Url, MediaField::PreviewImageUrl, MediaField::Variants, MediaField::AltText])
+}
+
+/// Looks the items up through `GET /2/tweets`, 100 ids at a time, before anything is removed.
+/// Ids Twitter no longer has are left out, any other failure is returned so nothing is removed unarchived
+pub async fn snapshot(
+    hyper: HttpClient, twitter_url: &str, access_token: &str, rate_limits: RateLimiter, owner: &BudgetOwner, items: &[(String, TweetType)]
+) -> TResult<Vec<ArchivedTweet>> {
+    // an id sent both as a tweet and as a like is looked up once
+    let mut ids: Vec<String> = vec![];
+    for (id, _) in items {
+        if !ids.contains(id) {
+            ids.push(id.clone());
+        }
+    }
+
+    let mut archived = vec![];
+
+    for batch in ids.chunks(MAX_IDS) {
+        let lookup = Paginator::<Tweet>::new(hyper.clone(), format!("{}/2/tweets", twitter_url), access_token.to_string())
+            .with_query(lookup_query(batch))
+            .with_rate_limiter(rate_limits.clone())
+            .with_budget(owner.clone());
+
+        let response = match lookup.pages().next().await.unwrap_or_else(|| Ok(TwitterResponse::default())) {
+            Ok(response) => response,
+            // none of the batch is left on Twitter, there is nothing to keep
+            Err(TError::TwitterError(failure)) if matches!(failure.1, TwitterApiError::Partial { .. }) => continue,
+            Err(e) => return Err(e),
+        };
+
+        for tweet in response.tweets() {
+            let types = items.iter().filter(|(id, _)| *id == tweet.id).map(|(_, tweet_type)| *tweet_type);
+            archived.extend(types.map(|tweet_type| ArchivedTweet::from_lookup(tweet, tweet_type, response.includes.as_ref())));
+        }
+    }
+
+    Ok(archived)
+}
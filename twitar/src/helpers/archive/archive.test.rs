@@ -0,0 +1,57 @@
+#[cfg(test)]
+mod test_archive {
+    use serde_json::json;
+
+    use crate::helpers::archive::ArchivedTweet;
+    use crate::helpers::db_helper::TweetType;
+    use crate::helpers::response::TwitterResponse;
+    use crate::models::Tweet;
+
+    fn lookup() -> TwitterResponse<Vec<Tweet>> {
+        serde_json::from_value(json!({
+            "data": [{
+                "id": "1511757922354663425",
+                "text": "RT @TwitterDev: Lots of new fields",
+                "created_at": "2022-04-06T17:07:13.000Z",
+                "author_id": "2244994945",
+                "referenced_tweets": [{"type": "retweeted", "id": "1511749120564957189"}],
+                "attachments": {"media_keys": ["3_1511757918160338944"]},
+                "entities": {"mentions": [{"start": 3, "end": 14, "username": "TwitterDev"}]},
+                "public_metrics": {"retweet_count": 7, "reply_count": 0, "like_count": 0, "quote_count": 0}
+            }, {
+                "id": "1511757922354663426",
+                "text": "Nothing attached"
+            }],
+            "includes": {
+                "media": [{"media_key": "3_1511757918160338944", "type": "photo", "url": "https://pbs.twimg.com/media/FPplx.jpg"}],
+                "tweets": [{"id": "1511749120564957189", "text": "Lots of new fields"}]
+            }
+        })).unwrap()
+    }
+
+    #[test]
+    fn keeps_the_media_and_the_referenced_tweet_of_the_includes() {
+        let response = lookup();
+        let archived = ArchivedTweet::from_lookup(&response.tweets()[0], TweetType::Rts, response.includes.as_ref());
+
+        assert_eq!(archived.text, "RT @TwitterDev: Lots of new fields");
+        assert_eq!(archived.created_at.unwrap().timestamp(), 1649264833);
+        assert_eq!(archived.entities.unwrap()["mentions"][0]["username"], "TwitterDev");
+        assert_eq!(archived.metrics.unwrap()["retweet_count"], 7);
+        assert_eq!(archived.media.unwrap()[0]["url"], "https://pbs.twimg.com/media/FPplx.jpg");
+        assert_eq!(archived.referenced_tweets, Some(json!([{
+            "type": "retweeted",
+            "id": "1511749120564957189",
+            "tweet": {"id": "1511749120564957189", "text": "Lots of new fields"},
+        }])));
+    }
+
+    #[test]
+    fn leaves_out_what_twitter_did_not_return() {
+        let response = lookup();
+        let archived = ArchivedTweet::from_lookup(&response.tweets()[1], TweetType::Tweets, None);
+
+        assert_eq!(archived.text, "Nothing attached");
+        assert_eq!((archived.media, archived.referenced_tweets, archived.metrics), (None, None, None));
+    }
+}
@@ -0,0 +1,3 @@
+mod audit;
+
+pub use audit::{to_csv, to_jsonl, AuditAction, AuditEntry, AuditEvent, AuditOutcome, AuditPage, AuditQuery, ExportFormat};
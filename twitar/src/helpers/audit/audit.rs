@@ -0,0 +1,318 @@
+use std::{fmt, str::FromStr};
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Serializer};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::helpers::db_helper::TweetType;
+
+#[cfg(test)]
+#[path = "./audit.test.rs"]
+mod audit_test;
+
+
+/// Entries returned by a query when it does not ask for a number
+const DEFAULT_LIMIT: i64 = 1000;
+const MAX_LIMIT: i64 = 10_000;
+
+const CSV_HEADER: &str = "id,user_id,action,outcome,target,request_id,created_at,detail";
+
+
+/// What was done to a user's account
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    /// The user was sent to Twitter to grant access (OAuth 1.0a or 2.0)
+    OauthStart,
+    /// Twitter granted access and the credentials were stored
+    OauthLink,
+    OauthUnlink,
+    TokenRefresh,
+    TimelineSync,
+    /// A tweet was deleted
+    Delete,
+    Unretweet,
+    Unlike,
+    /// A setting of the user changed, e.g. the Twitter account the credentials belong to
+    ConfigChange,
+}
+
+const ACTIONS: [(AuditAction, &str); 9] = [
+    (AuditAction::OauthStart, "oauth_start"),
+    (AuditAction::OauthLink, "oauth_link"),
+    (AuditAction::OauthUnlink, "oauth_unlink"),
+    (AuditAction::TokenRefresh, "token_refresh"),
+    (AuditAction::TimelineSync, "timeline_sync"),
+    (AuditAction::Delete, "delete"),
+    (AuditAction::Unretweet, "unretweet"),
+    (AuditAction::Unlike, "unlike"),
+    (AuditAction::ConfigChange, "config_change"),
+];
+
+impl fmt::Display for AuditAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = ACTIONS.iter().find(|(action, _)| action == self).map(|(_, name)| *name).unwrap_or_default();
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for AuditAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ACTIONS.iter().find(|(_, name)| *name == s).map(|(action, _)| *action)
+            .ok_or_else(|| format!("{:?} is not an audited action", s))
+    }
+}
+
+impl Serialize for AuditAction {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// The removal of a queued item
+impl From<TweetType> for AuditAction {
+    fn from(tweet_type: TweetType) -> Self {
+        match tweet_type {
+            TweetType::Tweets => Self::Delete,
+            TweetType::Rts => Self::Unretweet,
+            TweetType::Likes => Self::Unlike,
+        }
+    }
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditOutcome {
+    Success,
+    Failure,
+    /// Not attempted yet, e.g. a removal left for later by the rate limits
+    Deferred,
+}
+
+impl fmt::Display for AuditOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Success => write!(f, "success"),
+            Self::Failure => write!(f, "failure"),
+            Self::Deferred => write!(f, "deferred"),
+        }
+    }
+}
+
+impl FromStr for AuditOutcome {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "success" => Ok(Self::Success),
+            "failure" => Ok(Self::Failure),
+            "deferred" => Ok(Self::Deferred),
+            other => Err(format!("{:?} is not an outcome", other)),
+        }
+    }
+}
+
+impl Serialize for AuditOutcome {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+
+/// An action about to be appended to the audit log, successful unless told otherwise
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditEvent {
+    pub user_id: Uuid,
+    pub action: AuditAction,
+    pub outcome: AuditOutcome,
+    /// What the action was taken on, e.g. the id of a removed tweet
+    pub target: Option<String>,
+    pub detail: Option<Value>,
+    /// The request the action was taken in
+    pub request_id: String,
+}
+
+impl AuditEvent {
+    pub fn new(user_id: Uuid, action: AuditAction, request_id: &str) -> Self {
+        Self { user_id, action, outcome: AuditOutcome::Success, target: None, detail: None, request_id: request_id.to_string() }
+    }
+
+    pub fn target(self, target: impl Into<String>) -> Self {
+        Self { target: Some(target.into()), ..self }
+    }
+
+    pub fn detail(self, detail: Value) -> Self {
+        Self { detail: Some(detail), ..self }
+    }
+
+    pub fn outcome(self, outcome: AuditOutcome) -> Self {
+        Self { outcome, ..self }
+    }
+
+    /// A failure, with the error added to the detail
+    pub fn failed(self, error: impl fmt::Display) -> Self {
+        let mut detail = self.detail.clone().unwrap_or_else(|| json!({}));
+
+        if let Some(detail) = detail.as_object_mut() {
+            detail.insert("error".into(), Value::String(error.to_string()));
+        }
+
+        Self { outcome: AuditOutcome::Failure, detail: Some(detail), ..self }
+    }
+}
+
+
+/// A row of the audit log
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub user_id: Uuid,
+    pub action: AuditAction,
+    pub outcome: AuditOutcome,
+    pub target: Option<String>,
+    pub detail: Option<Value>,
+    pub request_id: String,
+    pub created_at: DateTime<Utc>,
+}
+
+
+/// The entries of a user, oldest first. `from` is inclusive and `to` exclusive, no `actions` means all of them.
+/// `after` is the `id` the previous page ended on, entries sharing a `created_at` are never skipped or read twice
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditQuery {
+    pub user_id: Uuid,
+    pub actions: Vec<AuditAction>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub after: Option<i64>,
+    pub limit: i64,
+}
+
+impl AuditQuery {
+    pub fn new(user_id: Uuid) -> Self {
+        Self { user_id, actions: vec![], from: None, to: None, after: None, limit: DEFAULT_LIMIT }
+    }
+
+    pub fn actions(self, actions: Vec<AuditAction>) -> Self {
+        Self { actions, ..self }
+    }
+
+    pub fn between(self, from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) -> Self {
+        Self { from, to, ..self }
+    }
+
+    pub fn after(self, after: Option<i64>) -> Self {
+        Self { after, ..self }
+    }
+
+    /// At most `MAX_LIMIT`, a larger export is read page by page, each `after` the `next` of the one before
+    pub fn limit(self, limit: i64) -> Self {
+        Self { limit: limit.clamp(1, MAX_LIMIT), ..self }
+    }
+
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        entry.user_id == self.user_id
+            && (self.actions.is_empty() || self.actions.contains(&entry.action))
+            && !matches!(self.from, Some(from) if entry.created_at < from)
+            && !matches!(self.to, Some(to) if entry.created_at >= to)
+            && !matches!(self.after, Some(after) if entry.id <= after)
+    }
+}
+
+
+/// A page of the entries a query matches. `next` is the `after` of the following page, `None` once there is none
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditPage {
+    pub entries: Vec<AuditEntry>,
+    pub next: Option<i64>,
+}
+
+impl AuditPage {
+    /// `entries` are the ones the stores read for `query`, ordered by `id`. A full page may be followed by another
+    pub fn new(query: &AuditQuery, entries: Vec<AuditEntry>) -> Self {
+        let next = match entries.len() as i64 >= query.limit {
+            true => entries.last().map(|entry| entry.id),
+            false => None,
+        };
+
+        Self { entries, next }
+    }
+}
+
+
+/// How `/audit` answers: the usual JSON envelope, or a file to download
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+    Jsonl,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::Csv => "text/csv",
+            Self::Jsonl => "application/x-ndjson",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+            Self::Jsonl => "jsonl",
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            "jsonl" => Ok(Self::Jsonl),
+            other => Err(format!("{:?} is not json, csv or jsonl", other)),
+        }
+    }
+}
+
+
+/// Quotes the fields that hold a separator, a quote or a line break
+fn csv_field(value: &str) -> String {
+    match value.contains(&[',', '"', '\n', '\r'][..]) {
+        true => format!("\"{}\"", value.replace('"', "\"\"")),
+        false => value.to_string(),
+    }
+}
+
+/// One line per entry after the header, `detail` is kept as JSON
+pub fn to_csv(entries: &[AuditEntry]) -> String {
+    let mut csv = format!("{}\n", CSV_HEADER);
+
+    for entry in entries {
+        let fields = [
+            entry.id.to_string(),
+            entry.user_id.to_string(),
+            entry.action.to_string(),
+            entry.outcome.to_string(),
+            entry.target.clone().unwrap_or_default(),
+            entry.request_id.clone(),
+            entry.created_at.to_rfc3339(),
+            entry.detail.as_ref().map(|d| d.to_string()).unwrap_or_default(),
+        ];
+
+        csv.push_str(&fields.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
+
+/// One JSON object per line
+pub fn to_jsonl(entries: &[AuditEntry]) -> String {
+    entries.iter().filter_map(|entry| serde_json::to_string(entry).ok()).map(|line| line + "\n").collect()
+}
@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod test_audit {
+    use chrono::{TimeZone, Utc};
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use crate::helpers::audit::{to_csv, to_jsonl, AuditAction, AuditEntry, AuditEvent, AuditOutcome, AuditPage, AuditQuery};
+    use crate::helpers::db_helper::TweetType;
+
+    fn entry(user_id: Uuid, action: AuditAction, hour: u32) -> AuditEntry {
+        AuditEntry {
+            id: hour as i64,
+            user_id,
+            action,
+            outcome: AuditOutcome::Success,
+            target: Some("1460323737035677698".into()),
+            detail: None,
+            request_id: "request".into(),
+            created_at: Utc.ymd(2026, 10, 18).and_hms(hour, 0, 0),
+        }
+    }
+
+    #[test]
+    fn actions_are_named_the_same_way_everywhere() {
+        assert_eq!(AuditAction::TimelineSync.to_string(), "timeline_sync");
+        assert_eq!("oauth_unlink".parse::<AuditAction>(), Ok(AuditAction::OauthUnlink));
+        assert!("drop_table".parse::<AuditAction>().is_err());
+        assert_eq!(AuditAction::from(TweetType::Rts), AuditAction::Unretweet);
+    }
+
+    #[test]
+    fn failures_keep_their_detail_and_add_the_error() {
+        let event = AuditEvent::new(Uuid::new_v4(), AuditAction::Delete, "request")
+            .detail(json!({ "tweet_type": "tweets" }))
+            .failed("Not Found");
+
+        assert_eq!(event.outcome, AuditOutcome::Failure);
+        assert_eq!(event.detail, Some(json!({ "tweet_type": "tweets", "error": "Not Found" })));
+    }
+
+    #[test]
+    fn queries_match_the_user_actions_and_a_half_open_range() {
+        let user_id = Uuid::new_v4();
+        let query = AuditQuery::new(user_id)
+            .actions(vec![AuditAction::Delete, AuditAction::Unlike])
+            .between(Some(Utc.ymd(2026, 10, 18).and_hms(9, 0, 0)), Some(Utc.ymd(2026, 10, 18).and_hms(11, 0, 0)));
+
+        assert!(query.matches(&entry(user_id, AuditAction::Delete, 9)));
+        assert!(!query.matches(&entry(user_id, AuditAction::Delete, 11)));
+        assert!(!query.matches(&entry(user_id, AuditAction::TokenRefresh, 10)));
+        assert!(!query.matches(&entry(Uuid::new_v4(), AuditAction::Unlike, 10)));
+        assert_eq!(AuditQuery::new(user_id).limit(1_000_000).limit, 10_000);
+    }
+
+    #[test]
+    fn full_pages_point_to_the_next_one() {
+        let user_id = Uuid::new_v4();
+        let query = AuditQuery::new(user_id).after(Some(9)).limit(2);
+
+        assert!(!query.matches(&entry(user_id, AuditAction::Delete, 9)));
+        assert!(query.matches(&entry(user_id, AuditAction::Delete, 10)));
+
+        let full = AuditPage::new(&query, vec![entry(user_id, AuditAction::Delete, 10), entry(user_id, AuditAction::Delete, 11)]);
+        assert_eq!(full.next, Some(11));
+        assert_eq!(AuditPage::new(&query, vec![entry(user_id, AuditAction::Delete, 10)]).next, None);
+        assert_eq!(AuditPage::new(&query, vec![]).next, None);
+    }
+
+    #[test]
+    fn exports_quote_csv_fields_and_write_a_line_per_entry() {
+        let user_id = Uuid::new_v4();
+        let mut failed = entry(user_id, AuditAction::Unretweet, 10);
+        failed.outcome = AuditOutcome::Failure;
+        failed.detail = Some(json!({ "error": "Not Found" }));
+
+        let csv = to_csv(&[entry(user_id, AuditAction::Delete, 9), failed.clone()]);
+        let lines = csv.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines[0], "id,user_id,action,outcome,target,request_id,created_at,detail");
+        assert_eq!(lines[2], format!(
+            r#"10,{},unretweet,failure,1460323737035677698,request,2026-10-18T10:00:00+00:00,"{{""error"":""Not Found""}}""#, user_id
+        ));
+
+        let jsonl = to_jsonl(&[failed]);
+        let parsed: serde_json::Value = serde_json::from_str(jsonl.trim_end()).unwrap();
+        assert_eq!(parsed["action"], "unretweet");
+        assert_eq!(parsed["detail"]["error"], "Not Found");
+    }
+}
@@ -1,7 +1,7 @@
-use sqlx::{Pool, Postgres};
 use uuid::Uuid;
 
-use crate::{errors::response::TError, base_repository::db::{DB, AuthUser, V2User, V1User}};
+use crate::{errors::response::TError, base_repository::db::{AuthUser, V2User, V1User}, startup::server::CurrentUser};
+use crate::base_repository::repository::{CredentialRepository, UserRepository};
 
 use super::response::TResult;
 
@@ -27,15 +27,15 @@ pub struct UserId(#[display(fmt = "0")] Uuid,);
 impl UserId {
     pub fn parse(input: Option<String>) -> TResult<Self> {
         if let Some(id) = input {
-            let user_id = Uuid::parse_str(&id)?;
+            let user_id = Uuid::parse_str(&id).map_err(|_| TError::InvalidUserId("User id is not valid"))?;
             return Ok(Self(user_id))
         }
         
         Err(TError::InvalidUserId("User id is not present"))
     }
 
-    pub async fn verify(&self, pool: &Pool<Postgres>) -> TResult<AuthUser> {
-        let user_exists = DB::user_exists(pool, self.0).await?;
+    pub async fn verify(&self, users: &dyn UserRepository) -> TResult<AuthUser> {
+        let user_exists = users.user_exists(self.0).await?;
 
         if let Some(user) = user_exists {
             return Ok(user)
@@ -44,8 +44,15 @@ impl UserId {
         return Err(TError::InvalidUserId("User does not exist"))
     }
 
-    pub async fn v2_credentials(&self, pool: &Pool<Postgres>) -> TResult<V2User> {
-        let user = DB::v2_user(pool, self.0).await?;
+    /// The user and both sets of credentials, from the cache when they were loaded recently
+    pub async fn current_user(&self, users: &dyn UserRepository) -> TResult<CurrentUser> {
+        let user = users.current_user(self.0).await?;
+
+        user.ok_or(TError::InvalidUserId("User does not exist"))
+    }
+
+    pub async fn v2_credentials(&self, credentials: &dyn CredentialRepository) -> TResult<V2User> {
+        let user = credentials.v2_user(self.0).await?;
 
         if let Some(credentials) = user {
             return Ok(credentials)
@@ -53,8 +60,8 @@ impl UserId {
         return Err(TError::InvalidUserId("User does not exist"))
     }
 
-    pub async fn v1_credentials(&self, pool: &Pool<Postgres>) -> TResult<V1User> {
-        let user = DB::v1_user(pool, self.0).await?;
+    pub async fn v1_credentials(&self, credentials: &dyn CredentialRepository) -> TResult<V1User> {
+        let user = credentials.v1_user(self.0).await?;
 
         if let Some(credentials) = user {
             return Ok(credentials)
@@ -0,0 +1,3 @@
+mod credentials;
+
+pub use credentials::{CredentialCache, CredentialStore, MemoryCredentials, RedisCredentials};
@@ -0,0 +1,218 @@
+use std::{collections::HashMap, fmt, sync::{Arc, Mutex}, time::{Duration, Instant}};
+use async_trait::async_trait;
+use redis::{AsyncCommands, Client as RedisClient};
+use ring::{aead::{self, Aad, LessSafeKey, Nonce, UnboundKey, NONCE_LEN}, hkdf, rand::{SecureRandom, SystemRandom}};
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::base_repository::db::DB;
+use crate::helpers::{response::TResult, shared::SharedConnection};
+use crate::startup::server::CurrentUser;
+
+#[cfg(test)]
+#[path = "./credentials.test.rs"]
+mod credentials_test;
+
+
+const KEY_PREFIX: &str = "twitar:credentials";
+
+
+#[async_trait]
+pub trait CredentialStore: fmt::Debug + Send + Sync {
+    async fn get(&self, user_id: Uuid) -> TResult<Option<CurrentUser>>;
+
+    async fn set(&self, user: &CurrentUser, ttl: Duration) -> TResult<()>;
+
+    async fn invalidate(&self, user_id: Uuid) -> TResult<()>;
+}
+
+
+fn key(user_id: Uuid) -> String {
+    format!("{}:{}", KEY_PREFIX, user_id)
+}
+
+/// Seals the cached entries with AES-256-GCM, under a key derived from the client secret of the app. The tokens
+/// never reach Redis in the clear, and an entry only opens under the key it was written for
+#[derive(Clone)]
+struct Sealer {
+    key: Arc<LessSafeKey>,
+    random: SystemRandom,
+}
+
+impl Sealer {
+    fn new(secret: &str) -> Self {
+        let prk = hkdf::Salt::new(hkdf::HKDF_SHA256, KEY_PREFIX.as_bytes()).extract(secret.as_bytes());
+        let okm = prk.expand(&[], &aead::AES_256_GCM).expect("the AES-256-GCM key length is valid for HKDF-SHA256");
+        let key = LessSafeKey::new(UnboundKey::from(okm));
+
+        Self { key: Arc::new(key), random: SystemRandom::new() }
+    }
+
+    /// base64 of the nonce followed by the ciphertext, `key` is authenticated so an entry cannot be moved to another user
+    fn seal(&self, key: &str, plain: &[u8]) -> Option<String> {
+        let mut nonce = [0u8; NONCE_LEN];
+        self.random.fill(&mut nonce).ok()?;
+
+        let mut sealed = plain.to_vec();
+        self.key.seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::from(key.as_bytes()), &mut sealed).ok()?;
+
+        Some(base64::encode([&nonce[..], &sealed].concat()))
+    }
+
+    fn open(&self, key: &str, entry: &str) -> Option<Vec<u8>> {
+        let sealed = base64::decode(entry).ok()?;
+        if sealed.len() < NONCE_LEN {
+            return None;
+        }
+
+        let (nonce, sealed) = sealed.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).ok()?;
+        let mut sealed = sealed.to_vec();
+        let plain = self.key.open_in_place(nonce, Aad::from(key.as_bytes()), &mut sealed).ok()?;
+
+        Some(plain.to_vec())
+    }
+}
+
+impl fmt::Debug for Sealer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sealer").finish_non_exhaustive()
+    }
+}
+
+
+/// Entries every replica shares, so a refresh on one replica is not undone by a stale copy on another
+#[derive(Debug, Clone)]
+pub struct RedisCredentials {
+    connection: SharedConnection,
+    sealer: Sealer,
+}
+
+impl RedisCredentials {
+    /// `secret` is the one the entries are sealed with, every replica must be given the same
+    pub fn new(client: RedisClient, secret: &str) -> Self {
+        Self { connection: SharedConnection::new(client), sealer: Sealer::new(secret) }
+    }
+}
+
+#[async_trait]
+impl CredentialStore for RedisCredentials {
+    async fn get(&self, user_id: Uuid) -> TResult<Option<CurrentUser>> {
+        let mut con = self.connection.get().await?;
+        let key = key(user_id);
+        let cached: Option<String> = con.get(&key).await?;
+
+        // an entry written by an older release, or sealed with another secret, is a miss, not an error
+        Ok(cached
+            .and_then(|entry| self.sealer.open(&key, &entry))
+            .and_then(|user| serde_json::from_slice(&user).ok()))
+    }
+
+    async fn set(&self, user: &CurrentUser, ttl: Duration) -> TResult<()> {
+        let mut con = self.connection.get().await?;
+        let key = key(user.basic.user_id);
+        let value = match self.sealer.seal(&key, &serde_json::to_vec(user)?) {
+            Some(value) => value,
+            // nothing is cached rather than the tokens in the clear, the next request reads the database again
+            None => return Ok(()),
+        };
+
+        con.set_ex::<_, _, ()>(key, value, ttl.as_secs().max(1) as usize).await?;
+        Ok(())
+    }
+
+    async fn invalidate(&self, user_id: Uuid) -> TResult<()> {
+        let mut con = self.connection.get().await?;
+        con.del::<_, ()>(key(user_id)).await?;
+
+        Ok(())
+    }
+}
+
+
+/// Users kept in this process, for tests and single replica setups without Redis
+#[derive(Debug, Clone, Default)]
+pub struct MemoryCredentials {
+    users: Arc<Mutex<HashMap<Uuid, (CurrentUser, Instant)>>>,
+}
+
+impl MemoryCredentials {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl CredentialStore for MemoryCredentials {
+    async fn get(&self, user_id: Uuid) -> TResult<Option<CurrentUser>> {
+        let mut users = self.users.lock().unwrap();
+        users.retain(|_, (_, expires)| *expires > Instant::now());
+
+        Ok(users.get(&user_id).map(|(user, _)| user.clone()))
+    }
+
+    async fn set(&self, user: &CurrentUser, ttl: Duration) -> TResult<()> {
+        self.users.lock().unwrap().insert(user.basic.user_id, (user.clone(), Instant::now() + ttl));
+        Ok(())
+    }
+
+    async fn invalidate(&self, user_id: Uuid) -> TResult<()> {
+        self.users.lock().unwrap().remove(&user_id);
+        Ok(())
+    }
+}
+
+
+/// `CurrentUser`s loaded by `auth_middleware`, kept for `ttl`. The DB functions that change the rows they are
+/// loaded from take the cache and invalidate the user, so a copy is never older than the last write made through twitar
+#[derive(Debug, Clone)]
+pub struct CredentialCache {
+    store: Arc<dyn CredentialStore>,
+    ttl: Duration,
+}
+
+impl Default for CredentialCache {
+    fn default() -> Self {
+        Self { store: Arc::new(MemoryCredentials::new()), ttl: Duration::from_secs(30) }
+    }
+}
+
+impl CredentialCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_store(self, store: Arc<dyn CredentialStore>) -> Self {
+        Self { store, ..self }
+    }
+
+    pub fn with_ttl(self, ttl: Duration) -> Self {
+        Self { ttl, ..self }
+    }
+
+    /// The cached user, or the one `DB::current_user` loads. When the cache cannot be reached the database answers alone
+    pub async fn current_user(&self, pool: &Pool<Postgres>, user_id: Uuid) -> TResult<Option<CurrentUser>> {
+        match self.store.get(user_id).await {
+            Ok(Some(user)) => return Ok(Some(user)),
+            Ok(None) => {}
+            Err(e) => tracing::warn!(%user_id, error = %e, "unable to read the credential cache"),
+        }
+
+        let user = DB::current_user(pool, user_id).await?;
+
+        if let Some(user) = &user {
+            if let Err(e) = self.store.set(user, self.ttl).await {
+                tracing::warn!(%user_id, error = %e, "unable to write the credential cache");
+            }
+        }
+
+        Ok(user)
+    }
+
+    /// Drops the cached copy of the user, after its credentials changed
+    pub async fn invalidate(&self, user_id: Uuid) {
+        if let Err(e) = self.store.invalidate(user_id).await {
+            tracing::error!(%user_id, error = %e, "unable to invalidate the credential cache, it expires within the ttl");
+        }
+    }
+}
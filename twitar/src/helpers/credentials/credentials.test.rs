@@ -0,0 +1,72 @@
+#[cfg(test)]
+mod test_credentials {
+    use std::time::Duration;
+    use uuid::Uuid;
+
+    use crate::base_repository::db::{AuthUser, V1User, V2User};
+    use crate::helpers::credentials::{CredentialStore, MemoryCredentials};
+    use super::super::{key, Sealer};
+    use crate::startup::server::CurrentUser;
+
+    fn user(user_id: Uuid, access_token: &str) -> CurrentUser {
+        let basic = AuthUser { user_id, v1_active: true, v2_active: true };
+        let v1_user = V1User {
+            id: 1, user_id, twitter_user_id: None, oauth_token: "oauth-token".into(), oauth_secret: "oauth-secret".into(), oauth_verifier: None,
+        };
+        let v2_user = V2User {
+            id: 1, user_id, twitter_user_id: None, pkce: None, access_token: Some(access_token.into()), refresh_token: None,
+        };
+
+        CurrentUser::new(basic, v1_user, v2_user)
+    }
+
+    #[tokio::test]
+    async fn cached_users_are_returned_until_invalidated() {
+        let store = MemoryCredentials::new();
+        let user_id = Uuid::new_v4();
+
+        store.set(&user(user_id, "first"), Duration::from_secs(30)).await.unwrap();
+        let cached = store.get(user_id).await.unwrap().unwrap();
+        assert_eq!(cached.v2_user.access_token.as_deref(), Some("first"));
+
+        store.invalidate(user_id).await.unwrap();
+        assert!(store.get(user_id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn cached_users_expire_after_the_ttl() {
+        let store = MemoryCredentials::new();
+        let user_id = Uuid::new_v4();
+
+        store.set(&user(user_id, "first"), Duration::from_millis(20)).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(40)).await;
+
+        assert!(store.get(user_id).await.unwrap().is_none());
+    }
+
+    #[test]
+    fn cached_users_survive_a_round_trip_through_json() {
+        let user_id = Uuid::new_v4();
+        let json = serde_json::to_string(&user(user_id, "first")).unwrap();
+        let parsed: CurrentUser = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed.basic.user_id, user_id);
+        assert_eq!(parsed.v1_user.oauth_secret, "oauth-secret");
+    }
+
+    #[test]
+    fn sealed_entries_hide_the_tokens_and_open_only_for_their_key() {
+        let user_id = Uuid::new_v4();
+        let plain = serde_json::to_vec(&user(user_id, "first")).unwrap();
+        let sealer = Sealer::new("client-secret");
+
+        let sealed = sealer.seal(&key(user_id), &plain).unwrap();
+        assert!(!String::from_utf8_lossy(&base64::decode(&sealed).unwrap()).contains("oauth-secret"));
+        assert_ne!(sealer.seal(&key(user_id), &plain).unwrap(), sealed);
+
+        assert_eq!(sealer.open(&key(user_id), &sealed), Some(plain));
+        assert_eq!(sealer.open(&key(Uuid::new_v4()), &sealed), None);
+        assert_eq!(Sealer::new("another-secret").open(&key(user_id), &sealed), None);
+        assert_eq!(sealer.open(&key(user_id), "not sealed"), None);
+    }
+}
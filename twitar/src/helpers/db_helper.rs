@@ -1,3 +1,3 @@
 mod db;
 
-pub use db::{AllTweetIds, TweetIds, TweetType};
\ No newline at end of file
+pub use db::{PlayStatus, PlayTweet, TweetType};
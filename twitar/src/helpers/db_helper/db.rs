@@ -1,10 +1,18 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use sha1::{Digest, Sha1};
+
+use crate::models::{ReferenceType, Tweet};
+
 #[cfg(test)]
 #[path = "./db.test.rs"]
 mod db_test;
 
 #[derive(sqlx::Type)]
 #[sqlx(type_name = "tweet_type", rename_all = "lowercase")]
-#[derive(Clone, Debug, PartialEq, Copy, derive_more::Display)]
+#[derive(Clone, Debug, PartialEq, Copy, derive_more::Display, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum TweetType {
     #[display(fmt = "tweets")]
     Tweets,
@@ -20,29 +28,60 @@ impl TweetType {
     }
 }
 
-pub type TweetIds<'a> = Vec<Vec<&'a String>>;
+/// Where a queued item is in its removal
+#[derive(sqlx::Type)]
+#[sqlx(type_name = "play_status", rename_all = "lowercase")]
+#[derive(Clone, Debug, PartialEq, Eq, Copy, derive_more::Display)]
+pub enum PlayStatus {
+    #[display(fmt = "pending")]
+    Pending,
+    #[display(fmt = "processing")]
+    Processing,
+    #[display(fmt = "done")]
+    Done,
+    #[display(fmt = "failed")]
+    Failed,
+}
+
 
-#[derive(Debug, Clone)]
-pub struct AllTweetIds<'a> {
-    tweets: TweetIds<'a>,
-    rts: TweetIds<'a>,
-    likes: TweetIds<'a>,
+/// A row of `play_tweets`: one tweet, retweet or like of a user, queued for removal
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayTweet {
+    pub tweet_id: String,
+    pub tweet_type: TweetType,
+    pub created_at: Option<DateTime<Utc>>,
+    pub text_hash: Option<String>,
+    pub source_tweet_id: Option<String>,
+    pub metrics: Option<Value>,
 }
 
-impl<'a> AllTweetIds<'a> {
-    pub fn new(tweets: TweetIds<'a>, rts: TweetIds<'a>, likes: TweetIds<'a>) -> Self {
-        Self {tweets, rts, likes}
+impl PlayTweet {
+    /// Only the id is known, e.g. for the ids sent to `/remove`
+    pub fn new(tweet_id: impl Into<String>, tweet_type: TweetType) -> Self {
+        Self { tweet_id: tweet_id.into(), tweet_type, created_at: None, text_hash: None, source_tweet_id: None, metrics: None }
     }
 
-    pub fn get_tweets(&self) -> &TweetIds<'a> {
-        &self.tweets
-    }
+    pub fn from_tweet(tweet: &Tweet, tweet_type: TweetType) -> Self {
+        let source_tweet_id = tweet.referenced_tweets.iter()
+            .find(|r| r.kind == ReferenceType::Retweeted)
+            .map(|r| r.id.clone());
 
-    pub fn get_likes(&self) -> &TweetIds<'a> {
-        &self.likes
+        Self {
+            created_at: tweet.created_at,
+            text_hash: Some(format!("{:x}", Sha1::digest(tweet.text.as_bytes()))),
+            source_tweet_id,
+            metrics: tweet.public_metrics.as_ref().and_then(|m| serde_json::to_value(m).ok()),
+            ..Self::new(tweet.id.clone(), tweet_type)
+        }
     }
 
-    pub fn get_rts(&self) -> &TweetIds<'a>  {
-        &self.rts
+    /// A tweet of the user's own timeline, which is either theirs or a retweet
+    pub fn from_timeline(tweet: &Tweet) -> Self {
+        let tweet_type = match tweet.is_retweet() {
+            true => TweetType::Rts,
+            false => TweetType::Tweets,
+        };
+
+        Self::from_tweet(tweet, tweet_type)
     }
-}
\ No newline at end of file
+}
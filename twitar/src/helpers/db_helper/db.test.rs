@@ -1,7 +1,10 @@
 #[cfg(test)]
 mod test_db_helper {
 
-    use crate::helpers::db_helper::{TweetType, AllTweetIds};
+    use serde_json::json;
+
+    use crate::helpers::db_helper::{PlayStatus, PlayTweet, TweetType};
+    use crate::models::Tweet;
 
 
     #[test]
@@ -18,31 +21,30 @@ mod test_db_helper {
     }
 
     #[test]
-    fn all_tweet_ids_returns_content() {
-        let all = vec![String::from("1"), String::from("2"), String::from("3"), 
-            String::from("4"), String::from("5"), String::from("6")];
-        let mut tweets = Vec::with_capacity(2);
-        let mut likes = Vec::with_capacity(2);
-        let mut rts = Vec::with_capacity(2);
-        
-        for i in 0..6 {
-            if i < 2 {
-                tweets.push(&all[i]);
-            } else if i < 4 {
-                likes.push(&all[i]);
-            } else {
-                rts.push(&all[i]);
-            }
-        }
-        
-        let all = AllTweetIds::new(vec![tweets.clone()], vec![rts.clone()], vec![likes.clone()]);
-
-        let expected_likes = &all.get_likes()[0];
-        let expected_rts = &all.get_rts()[0];
-        let expected_tweets = &all.get_tweets()[0];
-
-        assert_eq!(expected_likes, &likes);
-        assert_eq!(expected_rts, &rts);
-        assert_eq!(expected_tweets, &tweets);
+    fn timeline_tweets_keep_their_metadata() {
+        let tweet: Tweet = serde_json::from_value(json!({
+            "id": "1511757922354663425",
+            "text": "RT @TwitterDev: hello",
+            "created_at": "2022-04-06T17:24:36.000Z",
+            "referenced_tweets": [{ "type": "retweeted", "id": "1511000000000000000" }],
+            "public_metrics": { "retweet_count": 2, "reply_count": 0, "like_count": 5, "quote_count": 0 }
+        })).unwrap();
+
+        let item = PlayTweet::from_timeline(&tweet);
+
+        assert_eq!(item.tweet_type, TweetType::Rts);
+        assert_eq!(item.source_tweet_id.as_deref(), Some("1511000000000000000"));
+        assert_eq!(item.text_hash.as_deref(), Some("6bd32adf806c9f0d202c23dd8c5b07d20ba2dbfa"));
+        assert_eq!(item.metrics.as_ref().unwrap()["like_count"], 5);
+        assert!(item.created_at.is_some());
+    }
+
+    #[test]
+    fn ids_alone_carry_no_metadata() {
+        let item = PlayTweet::new("1", TweetType::Likes);
+
+        assert_eq!(item.tweet_id, "1");
+        assert!(item.created_at.is_none() && item.text_hash.is_none() && item.metrics.is_none());
+        assert_eq!(PlayStatus::Done.to_string(), "done");
     }
-}
\ No newline at end of file
+}
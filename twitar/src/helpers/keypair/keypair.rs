@@ -1,14 +1,24 @@
+use std::fmt;
+
+use crate::helpers::telemetry::REDACTED;
+
 #[cfg(test)]
 #[path = "./keypair.test.rs"]
 mod keypair_test;
 
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct KeyPair {
     pub key: String,
     pub secret: String,
 }
 
+impl fmt::Debug for KeyPair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyPair").field("key", &self.key).field("secret", &REDACTED).finish()
+    }
+}
+
 impl KeyPair {
     /// Creates KeyPair with the given key and secret.
     pub fn new(key: String, secret: String) -> KeyPair {
@@ -0,0 +1,5 @@
+mod lease;
+
+pub use lease::{Job, Lease, Leases, MemoryLeases};
+#[cfg(test)]
+pub(crate) use lease::key;
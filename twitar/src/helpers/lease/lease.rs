@@ -0,0 +1,265 @@
+use std::{collections::HashMap, fmt, sync::{Arc, Mutex}, time::Duration};
+use async_trait::async_trait;
+use redis::{aio::Connection, AsyncCommands, Client as RedisClient, Script};
+use sqlx::{PgPool, Postgres, Row, Transaction};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::base_repository::repository::JobRepository;
+use crate::errors::response::TError;
+use crate::helpers::response::TResult;
+
+#[cfg(test)]
+#[path = "./lease.test.rs"]
+mod lease_test;
+
+
+const KEY_PREFIX: &str = "twitar:lease";
+/// A Redis lease expires on its own when its replica dies, and is extended every third of it while the job runs
+const LEASE_TTL: Duration = Duration::from_secs(60);
+/// The first key of the advisory locks twitar takes, the second one is the user
+const LOCK_SPACE: i32 = 0x7477;
+const APPLICATION_NAME: &str = "twitar:job:";
+
+
+// only the holder of the lease may extend or release it
+const EXTEND: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('PEXPIRE', KEYS[1], ARGV[2])
+end
+return 0
+"#;
+
+const RELEASE: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('DEL', KEYS[1])
+end
+return 0
+"#;
+
+
+/// The jobs that change what twitar stores or removes for a user, at most one of them runs per user
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Job {
+    Remove,
+    TimelineSync,
+}
+
+impl fmt::Display for Job {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Remove => write!(f, "remove"),
+            Self::TimelineSync => write!(f, "timeline_sync"),
+        }
+    }
+}
+
+
+pub(crate) fn key(user_id: Uuid) -> String {
+    format!("{}:{}", KEY_PREFIX, user_id)
+}
+
+/// Hands out one lease per user. Redis is tried first, and while it cannot be reached (or is not configured) a Postgres
+/// advisory lock takes its place. Replicas only exclude each other while they agree on which of the two is used
+#[derive(Debug, Clone)]
+pub struct Leases {
+    redis: Option<RedisClient>,
+    db_pool: PgPool,
+}
+
+impl Leases {
+    pub fn new(redis: Option<RedisClient>, db_pool: PgPool) -> Self {
+        Self { redis, db_pool }
+    }
+
+    async fn acquire_redis(&self, mut con: Connection, redis: &RedisClient, user_id: Uuid, job: Job) -> TResult<Lease> {
+        let key = key(user_id);
+        let value = format!("{}:{}", job, Uuid::new_v4());
+
+        let acquired: Option<String> = redis::cmd("SET").arg(&key).arg(&value)
+            .arg("NX").arg("PX").arg(LEASE_TTL.as_millis() as u64)
+            .query_async(&mut con).await?;
+
+        if acquired.is_none() {
+            let holder: Option<String> = con.get(&key).await?;
+            let running = holder.as_deref().and_then(|holder| holder.split(':').next()).unwrap_or("another job");
+
+            return Err(TError::Conflict(running.to_string()));
+        }
+
+        let keeper = tokio::spawn(extend(redis.clone(), key.clone(), value.clone()));
+
+        Ok(Lease { user_id, job, holder: Some(Holder::Redis { client: redis.clone(), key, value, keeper }) })
+    }
+
+    async fn acquire_postgres(&self, user_id: Uuid, job: Job) -> TResult<Lease> {
+        let mut transaction = self.db_pool.begin().await?;
+        let lock = key(user_id);
+
+        // the name of the session holding the lock is how a second request learns which job runs
+        sqlx::query("SELECT set_config('application_name', $1, true)")
+            .bind(format!("{}{}", APPLICATION_NAME, job))
+            .execute(&mut transaction).await?;
+
+        let acquired: bool = sqlx::query("SELECT pg_try_advisory_xact_lock($1, hashtext($2))")
+            .bind(LOCK_SPACE).bind(&lock)
+            .fetch_one(&mut transaction).await?
+            .try_get(0)?;
+
+        if !acquired {
+            transaction.rollback().await?;
+
+            let holder = sqlx::query(
+                r#"SELECT a.application_name FROM pg_locks l JOIN pg_stat_activity a ON a.pid = l.pid
+                WHERE l.locktype = 'advisory' AND l.granted AND l.classid = $1::text::oid
+                    AND l.objid = (hashtext($2)::bigint & 4294967295)::text::oid AND l.objsubid = 2"#
+            )
+                .bind(LOCK_SPACE.to_string()).bind(&lock)
+                .fetch_optional(&self.db_pool).await?;
+
+            let running = holder.and_then(|row| row.try_get::<String, _>(0).ok())
+                .and_then(|name| name.strip_prefix(APPLICATION_NAME).map(|job| job.to_string()))
+                .unwrap_or_else(|| "another job".to_string());
+
+            return Err(TError::Conflict(running));
+        }
+
+        Ok(Lease { user_id, job, holder: Some(Holder::Postgres(Box::new(transaction))) })
+    }
+}
+
+#[async_trait]
+impl JobRepository for Leases {
+    async fn acquire(&self, user_id: Uuid, job: Job) -> TResult<Lease> {
+        let redis = match &self.redis {
+            Some(redis) => redis,
+            None => return self.acquire_postgres(user_id, job).await,
+        };
+
+        match redis.get_async_connection().await {
+            Ok(con) => self.acquire_redis(con, redis, user_id, job).await,
+            Err(e) => {
+                tracing::warn!(%user_id, error = %e, "redis cannot be reached, falling back to an advisory lock");
+                self.acquire_postgres(user_id, job).await
+            }
+        }
+    }
+}
+
+
+/// The leases of this process, for the in-memory storage where there is a single replica
+#[derive(Debug, Clone, Default)]
+pub struct MemoryLeases {
+    running: Arc<Mutex<HashMap<Uuid, Job>>>,
+}
+
+impl MemoryLeases {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl JobRepository for MemoryLeases {
+    async fn acquire(&self, user_id: Uuid, job: Job) -> TResult<Lease> {
+        let mut running = self.running.lock().unwrap();
+
+        if let Some(running) = running.get(&user_id) {
+            return Err(TError::Conflict(running.to_string()));
+        }
+
+        running.insert(user_id, job);
+        Ok(Lease { user_id, job, holder: Some(Holder::Memory { running: self.running.clone(), user_id }) })
+    }
+}
+
+
+async fn extend(client: RedisClient, key: String, value: String) {
+    let mut interval = tokio::time::interval(LEASE_TTL / 3);
+    interval.tick().await;
+
+    loop {
+        interval.tick().await;
+
+        let extended = match client.get_async_connection().await {
+            Ok(mut con) => Script::new(EXTEND).key(&key).arg(&value).arg(LEASE_TTL.as_millis() as u64)
+                .invoke_async::<_, u8>(&mut con).await,
+            Err(e) => Err(e),
+        };
+
+        match extended {
+            Ok(1) => {}
+            Ok(_) => {
+                tracing::error!(%key, "the lease expired while its job was running");
+                return;
+            }
+            Err(e) => tracing::warn!(%key, error = %e, "unable to extend the lease"),
+        }
+    }
+}
+
+
+enum Holder {
+    Redis { client: RedisClient, key: String, value: String, keeper: JoinHandle<()> },
+    /// The advisory lock lasts as long as the transaction, boxed as it is far larger than the others
+    Postgres(Box<Transaction<'static, Postgres>>),
+    Memory { running: Arc<Mutex<HashMap<Uuid, Job>>>, user_id: Uuid },
+}
+
+impl Holder {
+    async fn release(self) -> TResult<()> {
+        match self {
+            Self::Redis { client, key, value, keeper } => {
+                keeper.abort();
+
+                let mut con = client.get_async_connection().await?;
+                Script::new(RELEASE).key(&key).arg(&value).invoke_async::<_, u8>(&mut con).await?;
+            }
+            Self::Postgres(transaction) => transaction.commit().await?,
+            Self::Memory { running, user_id } => {
+                running.lock().unwrap().remove(&user_id);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+
+/// Held for the duration of a job. `release` it once the job is done, a lease that is dropped instead
+/// (e.g. when the request timed out) is released in the background
+pub struct Lease {
+    pub user_id: Uuid,
+    pub job: Job,
+    holder: Option<Holder>,
+}
+
+impl fmt::Debug for Lease {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Lease").field("user_id", &self.user_id).field("job", &self.job).finish()
+    }
+}
+
+impl Lease {
+    pub async fn release(mut self) {
+        if let Some(holder) = self.holder.take() {
+            if let Err(e) = holder.release().await {
+                tracing::warn!(user_id = %self.user_id, job = %self.job, error = %e, "unable to release the lease, it expires on its own");
+            }
+        }
+    }
+}
+
+impl Drop for Lease {
+    fn drop(&mut self) {
+        let (user_id, job) = (self.user_id, self.job);
+
+        if let (Some(holder), Ok(runtime)) = (self.holder.take(), tokio::runtime::Handle::try_current()) {
+            runtime.spawn(async move {
+                if let Err(e) = holder.release().await {
+                    tracing::warn!(%user_id, %job, error = %e, "unable to release the lease, it expires on its own");
+                }
+            });
+        }
+    }
+}
@@ -0,0 +1,33 @@
+mod test_lease {
+    use uuid::Uuid;
+
+    use crate::base_repository::repository::JobRepository;
+    use crate::errors::response::TError;
+    use crate::helpers::lease::{key, Job, MemoryLeases};
+
+    #[test]
+    fn jobs_are_named_in_conflicts() {
+        assert_eq!(Job::Remove.to_string(), "remove");
+        assert_eq!(Job::TimelineSync.to_string(), "timeline_sync");
+    }
+
+    #[test]
+    fn leases_are_keyed_by_user() {
+        let user_id = Uuid::new_v4();
+        assert_eq!(key(user_id), format!("twitar:lease:{}", user_id));
+    }
+
+    #[tokio::test]
+    async fn memory_leases_turn_away_a_second_job_until_released() {
+        let leases = MemoryLeases::new();
+        let user_id = Uuid::new_v4();
+
+        let lease = leases.acquire(user_id, Job::Remove).await.unwrap();
+        let conflict = leases.acquire(user_id, Job::TimelineSync).await.unwrap_err();
+        assert!(matches!(conflict, TError::Conflict(job) if job == "remove"));
+        assert!(leases.acquire(Uuid::new_v4(), Job::TimelineSync).await.is_ok());
+
+        lease.release().await;
+        assert!(leases.acquire(user_id, Job::TimelineSync).await.is_ok());
+    }
+}
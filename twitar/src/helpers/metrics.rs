@@ -0,0 +1,3 @@
+mod metrics;
+
+pub use metrics::{endpoint_label, observe_query, outcome, route_label, Metrics, METRICS, METRICS_CONTENT_TYPE};
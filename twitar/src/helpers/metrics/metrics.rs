@@ -0,0 +1,170 @@
+use std::{future::Future, time::Instant};
+use once_cell::sync::Lazy;
+use prometheus::{Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry, TextEncoder};
+use sqlx::PgPool;
+
+use crate::helpers::response::TResult;
+use crate::errors::response::TError;
+use crate::routes::paths::ROUTES;
+
+#[cfg(test)]
+#[path = "./metrics.test.rs"]
+mod metrics_test;
+
+
+/// The content type of the text exposition format
+pub const METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+
+/// Every metric twitar exposes on `/metrics`. `make_request` and the DB layer have no `AppState`,
+/// so they are kept in one registry for the whole process
+pub static METRICS: Lazy<Metrics> = Lazy::new(Metrics::new);
+
+#[derive(Debug)]
+pub struct Metrics {
+    registry: Registry,
+    pub http_requests: IntCounterVec,
+    pub http_duration: HistogramVec,
+    pub twitter_requests: IntCounterVec,
+    pub twitter_duration: HistogramVec,
+    pub rate_limit_remaining: IntGaugeVec,
+    pub deletion_queue: IntGauge,
+    pub jobs: IntCounterVec,
+    pub token_refresh_failures: IntCounter,
+    pub db_queries: IntCounterVec,
+    pub db_duration: HistogramVec,
+    pub db_connections: IntGaugeVec,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new_custom(Some("twitar".into()), None).unwrap();
+
+        let counter = |name: &str, help: &str, labels: &[&str]| {
+            let metric = IntCounterVec::new(Opts::new(name, help), labels).unwrap();
+            registry.register(Box::new(metric.clone())).unwrap();
+            metric
+        };
+
+        let histogram = |name: &str, help: &str, labels: &[&str]| {
+            let metric = HistogramVec::new(HistogramOpts::new(name, help), labels).unwrap();
+            registry.register(Box::new(metric.clone())).unwrap();
+            metric
+        };
+
+        let gauge = |name: &str, help: &str, labels: &[&str]| {
+            let metric = IntGaugeVec::new(Opts::new(name, help), labels).unwrap();
+            registry.register(Box::new(metric.clone())).unwrap();
+            metric
+        };
+
+        let deletion_queue = IntGauge::new("deletion_queue_ids", "Tweet ids waiting in play_tweets to be removed").unwrap();
+        registry.register(Box::new(deletion_queue.clone())).unwrap();
+
+        let token_refresh_failures = IntCounter::new("token_refresh_failures_total", "OAuth2 refreshes Twitter did not grant").unwrap();
+        registry.register(Box::new(token_refresh_failures.clone())).unwrap();
+
+        Self {
+            http_requests: counter("http_requests_total", "Requests served, by route and status", &["method", "route", "status"]),
+            http_duration: histogram("http_request_duration_seconds", "Time spent serving requests", &["method", "route"]),
+            twitter_requests: counter("twitter_requests_total", "Requests made to Twitter, by endpoint and outcome", &["endpoint", "outcome"]),
+            twitter_duration: histogram("twitter_request_duration_seconds", "Time Twitter took to answer", &["endpoint"]),
+            rate_limit_remaining: gauge("twitter_rate_limit_remaining", "Requests left in the current window of each endpoint", &["bucket"]),
+            deletion_queue,
+            jobs: counter("jobs_total", "Units of work done for users, e.g. a tweet removed", &["job", "outcome"]),
+            token_refresh_failures,
+            db_queries: counter("db_queries_total", "Database calls, by query and outcome", &["query", "outcome"]),
+            db_duration: histogram("db_query_duration_seconds", "Time spent in the database", &["query"]),
+            db_connections: gauge("db_pool_connections", "Connections of the pool, by state", &["state"]),
+            registry,
+        }
+    }
+
+    /// Records a response of `Routes::wrapper`
+    pub fn observe_request(&self, method: &str, path: &str, status: u16, start: Instant) {
+        let route = route_label(path);
+
+        self.http_requests.with_label_values(&[method, route, &status.to_string()]).inc();
+        self.http_duration.with_label_values(&[method, route]).observe(start.elapsed().as_secs_f64());
+    }
+
+    pub fn observe_twitter<T>(&self, method: &str, path: &str, result: &TResult<T>, start: Instant) {
+        let endpoint = endpoint_label(method, path);
+
+        self.twitter_requests.with_label_values(&[&endpoint, outcome(result)]).inc();
+        self.twitter_duration.with_label_values(&[&endpoint]).observe(start.elapsed().as_secs_f64());
+    }
+
+    pub fn observe_job<T>(&self, job: &str, result: &TResult<T>) {
+        self.jobs.with_label_values(&[job, outcome(result)]).inc();
+    }
+
+    pub fn set_rate_limit(&self, method: &str, path: &str, remaining: u32) {
+        self.rate_limit_remaining.with_label_values(&[&endpoint_label(method, path)]).set(remaining as i64);
+    }
+
+    pub fn observe_pool(&self, pool: &PgPool) {
+        let idle = pool.num_idle() as i64;
+
+        self.db_connections.with_label_values(&["idle"]).set(idle);
+        self.db_connections.with_label_values(&["in_use"]).set(pool.size() as i64 - idle);
+    }
+
+    /// Every metric, in the text exposition format
+    pub fn render(&self) -> String {
+        let mut buffer = vec![];
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer).unwrap();
+
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+}
+
+
+/// Times a database call and counts its outcome under `query`
+pub async fn observe_query<T, F>(query: &str, future: F) -> TResult<T> where F: Future<Output = TResult<T>> {
+    let start = Instant::now();
+    let result = future.await;
+
+    METRICS.db_queries.with_label_values(&[query, outcome(&result)]).inc();
+    METRICS.db_duration.with_label_values(&[query]).observe(start.elapsed().as_secs_f64());
+
+    result
+}
+
+pub fn outcome<T>(result: &TResult<T>) -> &'static str {
+    match result {
+        Ok(_) => "ok",
+        Err(TError::RateLimit(_)) => "rate_limited",
+        Err(TError::TwitterError(..) | TError::BadStatus(_)) => "rejected",
+        Err(TError::NetworkError(_)) => "network_error",
+        Err(_) => "error",
+    }
+}
+
+/// Any path the router does not serve is counted as `unmatched`, so a scan cannot create new series
+pub fn route_label(path: &str) -> &'static str {
+    ROUTES.iter().find(|route| **route == path).copied().unwrap_or("unmatched")
+}
+
+/// `DELETE /2/tweets/1510000000000000001` becomes `DELETE /2/tweets/:id`, so every tweet, user and username shares one series
+pub fn endpoint_label(method: &str, path: &str) -> String {
+    let mut previous = "";
+
+    let is_id = |id: &str| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit());
+
+    // the first segment is the version of the api, e.g. `2` or `1.1`
+    let segments = path.trim_matches('/').split('/').enumerate().map(|(index, segment)| {
+        let label = match (previous, segment.strip_suffix(".json")) {
+            _ if index == 0 => segment.to_string(),
+            ("username", _) => ":username".to_string(),
+            (_, Some(id)) if is_id(id) => ":id.json".to_string(),
+            _ if is_id(segment) => ":id".to_string(),
+            _ => segment.to_string(),
+        };
+
+        previous = segment;
+        label
+    }).collect::<Vec<_>>();
+
+    format!("{} /{}", method, segments.join("/"))
+}
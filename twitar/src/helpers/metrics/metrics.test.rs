@@ -0,0 +1,46 @@
+#[cfg(test)]
+mod test_metrics {
+    use std::time::Instant;
+
+    use crate::errors::response::TError;
+    use crate::helpers::metrics::{endpoint_label, outcome, route_label, METRICS};
+    use crate::helpers::response::TResult;
+
+    #[test]
+    fn ids_and_usernames_share_one_endpoint() {
+        assert_eq!(endpoint_label("DELETE", "/2/tweets/1510000000000000001"), "DELETE /2/tweets/:id");
+        assert_eq!(endpoint_label("DELETE", "/2/users/2244994945/likes/1500000000000000014"), "DELETE /2/users/:id/likes/:id");
+        assert_eq!(endpoint_label("POST", "/1.1/statuses/unretweet/1510000000000000000.json"), "POST /1.1/statuses/unretweet/:id.json");
+        assert_eq!(endpoint_label("GET", "/2/users/by/username/TwitterDev"), "GET /2/users/by/username/:username");
+        assert_eq!(endpoint_label("POST", "/2/oauth2/token"), "POST /2/oauth2/token");
+    }
+
+    #[test]
+    fn unknown_paths_are_unmatched() {
+        assert_eq!(route_label("/remove"), "/remove");
+        assert_eq!(route_label("/wp-admin"), "unmatched");
+    }
+
+    #[test]
+    fn outcomes_tell_rate_limits_from_other_errors() {
+        let ok: TResult<()> = Ok(());
+        let limited: TResult<()> = Err(TError::RateLimit(None));
+        let rejected: TResult<()> = Err(TError::BadStatus(hyper::StatusCode::NOT_FOUND));
+
+        assert_eq!(outcome(&ok), "ok");
+        assert_eq!(outcome(&limited), "rate_limited");
+        assert_eq!(outcome(&rejected), "rejected");
+    }
+
+    #[test]
+    fn renders_the_recorded_metrics() {
+        let limited: TResult<()> = Err(TError::RateLimit(None));
+        METRICS.observe_twitter("GET", "/2/users/42/liked_tweets", &limited, Instant::now());
+        METRICS.set_rate_limit("GET", "/2/users/42/tweets", 899);
+
+        let rendered = METRICS.render();
+
+        assert!(rendered.contains(r#"twitar_twitter_requests_total{endpoint="GET /2/users/:id/liked_tweets",outcome="rate_limited"}"#));
+        assert!(rendered.contains(r#"twitar_twitter_rate_limit_remaining{bucket="GET /2/users/:id/tweets"} 899"#));
+    }
+}
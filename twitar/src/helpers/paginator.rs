@@ -0,0 +1,3 @@
+mod paginator;
+
+pub use paginator::Paginator;
@@ -0,0 +1,167 @@
+use std::{marker::PhantomData, time::Duration};
+use futures::stream::{self, BoxStream, StreamExt};
+use hyper::Method;
+use serde::de::DeserializeOwned;
+
+use crate::errors::response::TError;
+use crate::helpers::{
+    query::V2Query, rate_limit::{BudgetOwner, RateLimiter}, transport::HttpClient,
+    response::{make_request, TResult, TwitterResponse},
+};
+use crate::interceptors::handle_request::Interceptor;
+use crate::middlewares::request_builder::{AuthType, RequestBuilder};
+
+#[cfg(test)]
+#[path = "./paginator.test.rs"]
+mod paginator_test;
+
+
+/// How many times a page is retried after hitting the rate limit before giving up
+const MAX_RATE_LIMIT_RETRIES: u8 = 3;
+
+/// The shortest wait before a rate limited page is asked for again, doubled on every retry
+const RETRY_BACKOFF: Duration = Duration::from_secs(5);
+
+
+/// Walks a v2 list endpoint (tweets, liked_tweets, followers, ...) page by page, following `meta.next_token`.
+/// Nothing is fetched until the stream is polled, and dropping the stream stops the paging
+pub struct Paginator<T> {
+    client: HttpClient,
+    url: String,
+    access_token: String,
+    query: V2Query,
+    rate_limits: RateLimiter,
+    budget: Option<BudgetOwner>,
+    max_items: Option<usize>,
+    retry_backoff: Duration,
+    item: PhantomData<fn() -> T>,
+}
+
+impl<T> Paginator<T> where T: DeserializeOwned + Send + 'static {
+    pub fn new(client: HttpClient, url: String, access_token: String) -> Self {
+        Self {
+            client,
+            url,
+            access_token,
+            query: V2Query::new(),
+            rate_limits: RateLimiter::new(),
+            budget: None,
+            max_items: None,
+            retry_backoff: RETRY_BACKOFF,
+            item: PhantomData,
+        }
+    }
+
+    pub fn with_query(self, query: V2Query) -> Self {
+        Self { query, ..self }
+    }
+
+    /// The number of items requested per page (`max_results`)
+    pub fn page_size(self, size: u32) -> Self {
+        Self { query: self.query.max_results(size), ..self }
+    }
+
+    /// Stops once this many items have been fetched
+    pub fn max_items(self, max: usize) -> Self {
+        Self { max_items: Some(max), ..self }
+    }
+
+    /// Shares the known rate limits with other requests, so the paginator waits for the window to reset
+    /// instead of making a request that is bound to fail
+    pub fn with_rate_limiter(self, rate_limits: RateLimiter) -> Self {
+        Self { rate_limits, ..self }
+    }
+
+    /// Takes every page from the budgets of `owner` (see `RateLimiter::send`), the pages are not counted otherwise
+    pub fn with_budget(self, owner: BudgetOwner) -> Self {
+        Self { budget: Some(owner), ..self }
+    }
+
+    /// The wait before the first retry of a rate limited page, when Twitter did not report a later reset
+    pub fn retry_backoff(self, backoff: Duration) -> Self {
+        Self { retry_backoff: backoff, ..self }
+    }
+
+    /// Until the reset Twitter reported, and no less than the backoff of this retry
+    fn backoff(&self, error: &TError, retries: u8) -> Duration {
+        let backoff = self.retry_backoff * 2u32.pow(retries.into());
+
+        match error {
+            TError::RateLimit(Some(_)) => backoff.max(Duration::from_secs(error.retry_after().unwrap_or_default())),
+            _ => backoff,
+        }
+    }
+
+    async fn fetch(&self, token: Option<&str>) -> TResult<TwitterResponse<Vec<T>>> {
+        let query = match token {
+            Some(token) => self.query.clone().pagination_token(token),
+            None => self.query.clone(),
+        };
+
+        let mut retries = 0;
+
+        loop {
+            self.rate_limits.wait(&self.url).await;
+
+            let request = RequestBuilder::new(Method::GET, self.url.clone())
+                .with_auth(AuthType::Bearer, self.access_token.clone())
+                .with_v2_query(&query)?
+                .build_request();
+
+            let response = match &self.budget {
+                Some(owner) => self.rate_limits.send(owner, request, self.client.clone()).await,
+                None => make_request(request, self.client.clone()).await,
+            };
+
+            match response {
+                Err(e @ TError::RateLimit(_)) if retries < MAX_RATE_LIMIT_RETRIES => {
+                    tokio::time::sleep(self.backoff(&e, retries)).await;
+                    retries += 1;
+                }
+                response => {
+                    if let Ok((headers, _)) = &response {
+                        self.rate_limits.update(&self.url, headers);
+                    }
+
+                    return Interceptor::partial(response);
+                }
+            }
+        }
+    }
+
+    /// Every page of the endpoint. The stream ends after the first error
+    pub fn pages(self) -> BoxStream<'static, TResult<TwitterResponse<Vec<T>>>> {
+        stream::unfold(Some((self, None::<String>, 0)), |state| async move {
+            let (paginator, token, fetched) = state?;
+
+            if matches!(paginator.max_items, Some(max) if fetched >= max) {
+                return None;
+            }
+
+            match paginator.fetch(token.as_deref()).await {
+                Ok(page) => {
+                    let fetched = fetched + page.data.as_ref().map(Vec::len).unwrap_or_default();
+                    let next_token = page.meta.as_ref().and_then(|meta| meta.next_token.clone());
+                    let state = next_token.map(|token| (paginator, Some(token), fetched));
+
+                    Some((Ok(page), state))
+                }
+                Err(e) => Some((Err(e), None)),
+            }
+        }).boxed()
+    }
+
+    /// Every item of the endpoint, across pages
+    pub fn items(self) -> BoxStream<'static, TResult<T>> {
+        let max_items = self.max_items.unwrap_or(usize::MAX);
+
+        self.pages().flat_map(|page| {
+            let items = match page {
+                Ok(page) => page.data.unwrap_or_default().into_iter().map(Ok).collect(),
+                Err(e) => vec![Err(e)],
+            };
+
+            stream::iter(items)
+        }).take(max_items).boxed()
+    }
+}
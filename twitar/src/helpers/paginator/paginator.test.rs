@@ -0,0 +1,106 @@
+#[cfg(test)]
+mod test_paginator {
+    use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+    use futures::{StreamExt, TryStreamExt};
+    use serde_json::json;
+    use wiremock::{MockServer, Mock, ResponseTemplate};
+    use wiremock::matchers::{method, path, query_param, header};
+
+    use crate::helpers::paginator::Paginator;
+    use crate::helpers::transport::HyperTransport;
+    use crate::models::Tweet;
+
+    const TIMELINE: &str = "/2/users/2244994945/tweets";
+
+    fn page(ids: &[&str], next_token: Option<&str>) -> ResponseTemplate {
+        let data = ids.iter().map(|id| json!({"id": id, "text": format!("tweet {}", id)})).collect::<Vec<_>>();
+        let mut meta = json!({"result_count": ids.len()});
+        if let Some(token) = next_token {
+            meta["next_token"] = json!(token);
+        }
+
+        ResponseTemplate::new(200).set_body_json(json!({"data": data, "meta": meta}))
+    }
+
+    async fn paginator(server: &MockServer) -> Paginator<Tweet> {
+        let client = HyperTransport::client();
+        Paginator::new(client, format!("{}{}", server.uri(), TIMELINE), "token".into())
+            .retry_backoff(Duration::from_millis(100))
+    }
+
+    #[tokio::test]
+    async fn follows_next_token_until_the_last_page() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET")).and(path(TIMELINE)).and(query_param("pagination_token", "next"))
+            .respond_with(page(&["3"], None))
+            .expect(1).mount(&server).await;
+
+        Mock::given(method("GET")).and(path(TIMELINE)).and(query_param("max_results", "5"))
+            .and(header("Authorization", "Bearer token"))
+            .respond_with(page(&["1", "2"], Some("next")))
+            .expect(1).mount(&server).await;
+
+        let tweets: Vec<Tweet> = paginator(&server).await.page_size(5).items().try_collect().await.unwrap();
+        let ids = tweets.iter().map(|t| t.id.as_str()).collect::<Vec<_>>();
+
+        assert_eq!(ids, vec!["1", "2", "3"]);
+    }
+
+    #[tokio::test]
+    async fn stops_early_once_enough_items_are_fetched() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET")).and(path(TIMELINE)).and(query_param("pagination_token", "next"))
+            .respond_with(page(&["3"], None))
+            .expect(0).mount(&server).await;
+
+        Mock::given(method("GET")).and(path(TIMELINE))
+            .respond_with(page(&["1", "2"], Some("next")))
+            .expect(1).mount(&server).await;
+
+        let tweets = paginator(&server).await.max_items(1).items().collect::<Vec<_>>().await;
+
+        assert_eq!(tweets.len(), 1);
+        assert_eq!(tweets[0].as_ref().unwrap().id, "1");
+    }
+
+    #[tokio::test]
+    async fn waits_for_the_window_to_reset_instead_of_failing() {
+        let server = MockServer::start().await;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+
+        Mock::given(method("GET")).and(path(TIMELINE))
+            .respond_with(ResponseTemplate::new(429).insert_header("x-rate-limit-reset", now.to_string().as_str()))
+            .up_to_n_times(1).expect(1).mount(&server).await;
+
+        Mock::given(method("GET")).and(path(TIMELINE))
+            .respond_with(page(&["1"], None))
+            .expect(1).mount(&server).await;
+
+        let pages = paginator(&server).await.pages().collect::<Vec<_>>().await;
+
+        assert_eq!(pages.len(), 1);
+        assert!(pages[0].is_ok());
+    }
+
+    #[tokio::test]
+    async fn backs_off_when_twitter_reports_no_reset() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET")).and(path(TIMELINE))
+            .respond_with(ResponseTemplate::new(429))
+            .up_to_n_times(2).expect(2).mount(&server).await;
+
+        Mock::given(method("GET")).and(path(TIMELINE))
+            .respond_with(page(&["1"], None))
+            .expect(1).mount(&server).await;
+
+        let start = Instant::now();
+        let pages = paginator(&server).await.pages().collect::<Vec<_>>().await;
+
+        assert!(pages[0].is_ok());
+        // 100ms, then 200ms
+        assert!(start.elapsed() >= Duration::from_millis(300));
+    }
+}
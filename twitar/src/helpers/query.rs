@@ -0,0 +1,5 @@
+mod query;
+mod fields;
+
+pub use query::{V2Query, MAX_IDS, TIMELINE_RESULTS, LIKED_TWEETS_RESULTS, FOLLOWS_RESULTS};
+pub use fields::{TweetField, UserField, MediaField, PollField, PlaceField, Expansion, Exclude};
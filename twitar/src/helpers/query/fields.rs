@@ -0,0 +1,148 @@
+//! The values accepted by the `*.fields`, `expansions` and `exclude` parameters of the v2 api
+
+
+#[derive(Debug, Clone, Copy, derive_more::Display, PartialEq, Eq)]
+pub enum TweetField {
+    #[display(fmt = "attachments")]
+    Attachments,
+    #[display(fmt = "author_id")]
+    AuthorId,
+    #[display(fmt = "context_annotations")]
+    ContextAnnotations,
+    #[display(fmt = "conversation_id")]
+    ConversationId,
+    #[display(fmt = "created_at")]
+    CreatedAt,
+    #[display(fmt = "entities")]
+    Entities,
+    #[display(fmt = "geo")]
+    Geo,
+    #[display(fmt = "in_reply_to_user_id")]
+    InReplyToUserId,
+    #[display(fmt = "lang")]
+    Lang,
+    #[display(fmt = "possibly_sensitive")]
+    PossiblySensitive,
+    #[display(fmt = "public_metrics")]
+    PublicMetrics,
+    #[display(fmt = "referenced_tweets")]
+    ReferencedTweets,
+    #[display(fmt = "reply_settings")]
+    ReplySettings,
+    #[display(fmt = "source")]
+    Source,
+    #[display(fmt = "withheld")]
+    Withheld,
+}
+
+
+#[derive(Debug, Clone, Copy, derive_more::Display, PartialEq, Eq)]
+pub enum UserField {
+    #[display(fmt = "created_at")]
+    CreatedAt,
+    #[display(fmt = "description")]
+    Description,
+    #[display(fmt = "entities")]
+    Entities,
+    #[display(fmt = "location")]
+    Location,
+    #[display(fmt = "pinned_tweet_id")]
+    PinnedTweetId,
+    #[display(fmt = "profile_image_url")]
+    ProfileImageUrl,
+    #[display(fmt = "protected")]
+    Protected,
+    #[display(fmt = "public_metrics")]
+    PublicMetrics,
+    #[display(fmt = "url")]
+    Url,
+    #[display(fmt = "verified")]
+    Verified,
+    #[display(fmt = "withheld")]
+    Withheld,
+}
+
+
+#[derive(Debug, Clone, Copy, derive_more::Display, PartialEq, Eq)]
+pub enum MediaField {
+    #[display(fmt = "alt_text")]
+    AltText,
+    #[display(fmt = "duration_ms")]
+    DurationMs,
+    #[display(fmt = "height")]
+    Height,
+    #[display(fmt = "preview_image_url")]
+    PreviewImageUrl,
+    #[display(fmt = "public_metrics")]
+    PublicMetrics,
+    #[display(fmt = "url")]
+    Url,
+    #[display(fmt = "variants")]
+    Variants,
+    #[display(fmt = "width")]
+    Width,
+}
+
+
+#[derive(Debug, Clone, Copy, derive_more::Display, PartialEq, Eq)]
+pub enum PollField {
+    #[display(fmt = "duration_minutes")]
+    DurationMinutes,
+    #[display(fmt = "end_datetime")]
+    EndDatetime,
+    #[display(fmt = "options")]
+    Options,
+    #[display(fmt = "voting_status")]
+    VotingStatus,
+}
+
+
+#[derive(Debug, Clone, Copy, derive_more::Display, PartialEq, Eq)]
+pub enum PlaceField {
+    #[display(fmt = "contained_within")]
+    ContainedWithin,
+    #[display(fmt = "country")]
+    Country,
+    #[display(fmt = "country_code")]
+    CountryCode,
+    #[display(fmt = "full_name")]
+    FullName,
+    #[display(fmt = "geo")]
+    Geo,
+    #[display(fmt = "name")]
+    Name,
+    #[display(fmt = "place_type")]
+    PlaceType,
+}
+
+
+#[derive(Debug, Clone, Copy, derive_more::Display, PartialEq, Eq)]
+pub enum Expansion {
+    #[display(fmt = "attachments.media_keys")]
+    MediaKeys,
+    #[display(fmt = "attachments.poll_ids")]
+    PollIds,
+    #[display(fmt = "author_id")]
+    AuthorId,
+    #[display(fmt = "entities.mentions.username")]
+    MentionedUsernames,
+    #[display(fmt = "geo.place_id")]
+    PlaceId,
+    #[display(fmt = "in_reply_to_user_id")]
+    InReplyToUserId,
+    #[display(fmt = "referenced_tweets.id")]
+    ReferencedTweets,
+    #[display(fmt = "referenced_tweets.id.author_id")]
+    ReferencedTweetsAuthor,
+    #[display(fmt = "pinned_tweet_id")]
+    PinnedTweetId,
+}
+
+
+#[derive(Debug, Clone, Copy, derive_more::Display, PartialEq, Eq)]
+pub enum Exclude {
+    #[display(fmt = "retweets")]
+    Retweets,
+    #[display(fmt = "replies")]
+    Replies,
+}
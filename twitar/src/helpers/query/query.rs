@@ -0,0 +1,221 @@
+use std::{fmt::Display, ops::RangeInclusive};
+use chrono::{DateTime, SecondsFormat, Utc};
+use twitar_macro::{Validate, ValidationErrors};
+
+use crate::helpers::query::{TweetField, UserField, MediaField, PollField, PlaceField, Expansion, Exclude};
+
+#[cfg(test)]
+#[path = "./query.test.rs"]
+mod query_test;
+
+
+/// The page sizes (`max_results`) each v2 list endpoint accepts
+pub const TIMELINE_RESULTS: RangeInclusive<u32> = 5..=100;
+pub const LIKED_TWEETS_RESULTS: RangeInclusive<u32> = 10..=100;
+pub const FOLLOWS_RESULTS: RangeInclusive<u32> = 1..=1000;
+/// Checked when the caller does not name the range of its endpoint, the widest of them
+const ANY_RESULTS: RangeInclusive<u32> = FOLLOWS_RESULTS;
+/// Lookups by id take at most 100 ids per request
+pub const MAX_IDS: usize = 100;
+
+
+/// Query parameters of a v2 request.
+/// Unlike `RequestBuilder::with_query`, the field sets are typed, comma joined and checked before anything is sent
+#[derive(Debug, Clone, Default)]
+pub struct V2Query {
+    ids: Vec<String>,
+    tweet_fields: Vec<TweetField>,
+    user_fields: Vec<UserField>,
+    media_fields: Vec<MediaField>,
+    poll_fields: Vec<PollField>,
+    place_fields: Vec<PlaceField>,
+    expansions: Vec<Expansion>,
+    exclude: Vec<Exclude>,
+    max_results: Option<u32>,
+    max_results_range: Option<RangeInclusive<u32>>,
+    pagination_token: Option<String>,
+    since_id: Option<String>,
+    until_id: Option<String>,
+    start_time: Option<DateTime<Utc>>,
+    end_time: Option<DateTime<Utc>>,
+}
+
+
+/// Adds `values` to `list`, skipping the ones it already has
+fn extend<T: PartialEq>(list: &mut Vec<T>, values: impl IntoIterator<Item = T>) {
+    for value in values {
+        if !list.contains(&value) {
+            list.push(value);
+        }
+    }
+}
+
+fn join<T: Display>(values: &[T]) -> Option<String> {
+    if values.is_empty() {
+        return None;
+    }
+
+    Some(values.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(","))
+}
+
+fn timestamp(time: &DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+
+impl V2Query {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The tweets (or users) to look up, e.g. for `GET /2/tweets`
+    pub fn ids(mut self, ids: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        extend(&mut self.ids, ids.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn tweet_fields(mut self, fields: impl IntoIterator<Item = TweetField>) -> Self {
+        extend(&mut self.tweet_fields, fields);
+        self
+    }
+
+    pub fn user_fields(mut self, fields: impl IntoIterator<Item = UserField>) -> Self {
+        extend(&mut self.user_fields, fields);
+        self
+    }
+
+    pub fn media_fields(mut self, fields: impl IntoIterator<Item = MediaField>) -> Self {
+        extend(&mut self.media_fields, fields);
+        self
+    }
+
+    pub fn poll_fields(mut self, fields: impl IntoIterator<Item = PollField>) -> Self {
+        extend(&mut self.poll_fields, fields);
+        self
+    }
+
+    pub fn place_fields(mut self, fields: impl IntoIterator<Item = PlaceField>) -> Self {
+        extend(&mut self.place_fields, fields);
+        self
+    }
+
+    pub fn expansions(mut self, expansions: impl IntoIterator<Item = Expansion>) -> Self {
+        extend(&mut self.expansions, expansions);
+        self
+    }
+
+    pub fn exclude(mut self, exclude: impl IntoIterator<Item = Exclude>) -> Self {
+        extend(&mut self.exclude, exclude);
+        self
+    }
+
+    pub fn max_results(self, max_results: u32) -> Self {
+        Self { max_results: Some(max_results), ..self }
+    }
+
+    /// The page sizes the endpoint accepts, e.g. `LIKED_TWEETS_RESULTS`. It is not sent, only checked against `max_results`
+    pub fn max_results_range(self, range: RangeInclusive<u32>) -> Self {
+        Self { max_results_range: Some(range), ..self }
+    }
+
+    pub fn pagination_token(self, token: impl Into<String>) -> Self {
+        Self { pagination_token: Some(token.into()), ..self }
+    }
+
+    pub fn since_id(self, id: impl Into<String>) -> Self {
+        Self { since_id: Some(id.into()), ..self }
+    }
+
+    pub fn until_id(self, id: impl Into<String>) -> Self {
+        Self { until_id: Some(id.into()), ..self }
+    }
+
+    pub fn start_time(self, time: DateTime<Utc>) -> Self {
+        Self { start_time: Some(time), ..self }
+    }
+
+    pub fn end_time(self, time: DateTime<Utc>) -> Self {
+        Self { end_time: Some(time), ..self }
+    }
+
+    /// The (key, value) pairs of this query, in a stable order. Values are not url encoded
+    pub fn params(&self) -> Vec<(&'static str, String)> {
+        let params = [
+            ("ids", join(&self.ids)),
+            ("tweet.fields", join(&self.tweet_fields)),
+            ("user.fields", join(&self.user_fields)),
+            ("media.fields", join(&self.media_fields)),
+            ("poll.fields", join(&self.poll_fields)),
+            ("place.fields", join(&self.place_fields)),
+            ("expansions", join(&self.expansions)),
+            ("exclude", join(&self.exclude)),
+            ("max_results", self.max_results.map(|n| n.to_string())),
+            ("pagination_token", self.pagination_token.clone()),
+            ("since_id", self.since_id.clone()),
+            ("until_id", self.until_id.clone()),
+            ("start_time", self.start_time.as_ref().map(timestamp)),
+            ("end_time", self.end_time.as_ref().map(timestamp)),
+        ];
+
+        params.into_iter().filter_map(|(k, v)| v.map(|v| (k, v))).collect()
+    }
+
+    /// The encoded query string, without the leading `?`
+    pub fn to_query_string(&self) -> String {
+        self.params().iter()
+            .map(|(k, v)| format!("{}={}", k, urlencoding::encode(v)))
+            .collect::<Vec<_>>().join("&")
+    }
+}
+
+
+impl Validate for V2Query {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+
+        if self.since_id.is_some() && self.start_time.is_some() {
+            errors.add("since_id", "exclusive", "cannot be combined with start_time".into());
+        }
+
+        if self.until_id.is_some() && self.end_time.is_some() {
+            errors.add("until_id", "exclusive", "cannot be combined with end_time".into());
+        }
+
+        if let (Some(start), Some(end)) = (self.start_time, self.end_time) {
+            if start >= end {
+                errors.add("start_time", "order", "must be before end_time".into());
+            }
+        }
+
+        if let Some(max) = self.max_results {
+            let range = self.max_results_range.clone().unwrap_or(ANY_RESULTS);
+
+            if !range.contains(&max) {
+                errors.add("max_results", "range", format!("must be between {} and {}", range.start(), range.end()));
+            }
+        }
+
+        if self.ids.len() > MAX_IDS {
+            errors.add("ids", "max_items", format!("cannot contain more than {} ids", MAX_IDS));
+        }
+
+        if matches!(&self.pagination_token, Some(token) if token.is_empty()) {
+            errors.add("pagination_token", "required", "cannot be empty".into());
+        }
+
+        // Twitter silently ignores the fields of objects that are not expanded
+        let expansion_checks = [
+            ("media.fields", !self.media_fields.is_empty(), Expansion::MediaKeys),
+            ("poll.fields", !self.poll_fields.is_empty(), Expansion::PollIds),
+            ("place.fields", !self.place_fields.is_empty(), Expansion::PlaceId),
+        ];
+
+        for (field, requested, expansion) in expansion_checks {
+            if requested && !self.expansions.contains(&expansion) {
+                errors.add(field, "expansion", format!("requires the {} expansion", expansion));
+            }
+        }
+
+        errors.into_result()
+    }
+}
@@ -0,0 +1,95 @@
+#[cfg(test)]
+mod test_query {
+    use chrono::{TimeZone, Utc};
+    use hyper::Method;
+    use twitar_macro::Validate;
+
+    use crate::helpers::query::{V2Query, TweetField, UserField, MediaField, Expansion, Exclude, TIMELINE_RESULTS, LIKED_TWEETS_RESULTS, FOLLOWS_RESULTS};
+    use crate::middlewares::request_builder::RequestBuilder;
+
+    #[test]
+    fn joins_fields_with_commas_without_duplicates() {
+        let query = V2Query::new()
+            .tweet_fields([TweetField::CreatedAt, TweetField::PublicMetrics])
+            .tweet_fields([TweetField::CreatedAt, TweetField::ReferencedTweets])
+            .user_fields([UserField::Description, UserField::Verified]);
+
+        assert_eq!(query.params(), vec![
+            ("tweet.fields", "created_at,public_metrics,referenced_tweets".to_string()),
+            ("user.fields", "description,verified".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn encodes_the_query_string() {
+        let query = V2Query::new()
+            .expansions([Expansion::MediaKeys])
+            .media_fields([MediaField::Url, MediaField::AltText])
+            .exclude([Exclude::Replies])
+            .max_results(10)
+            .start_time(Utc.ymd(2022, 4, 6).and_hms(17, 7, 13));
+
+        assert_eq!(
+            query.to_query_string(),
+            "media.fields=url%2Calt_text&expansions=attachments.media_keys&exclude=replies&max_results=10&start_time=2022-04-06T17%3A07%3A13Z"
+        );
+    }
+
+    #[test]
+    fn rejects_mutually_exclusive_options() {
+        let start = Utc.ymd(2022, 4, 6).and_hms(0, 0, 0);
+        let query = V2Query::new()
+            .since_id("1")
+            .start_time(start)
+            .until_id("2")
+            .end_time(start)
+            .max_results(101)
+            .max_results_range(TIMELINE_RESULTS);
+
+        let errors = query.validate().unwrap_err();
+        let fields = errors.0.iter().map(|e| e.field).collect::<Vec<_>>();
+
+        assert_eq!(fields, vec!["since_id", "until_id", "start_time", "max_results"]);
+    }
+
+    #[test]
+    fn object_fields_need_their_expansion() {
+        let query = V2Query::new().media_fields([MediaField::Url]);
+        let errors = query.validate().unwrap_err();
+        assert_eq!(errors.0[0].field, "media.fields");
+
+        assert!(query.expansions([Expansion::MediaKeys]).validate().is_ok());
+    }
+
+    #[test]
+    fn appends_to_request_builder_queries() {
+        let query = V2Query::new().tweet_fields([TweetField::Lang]);
+        let builder = RequestBuilder::new(Method::GET, "https://api.twitter.com/2/tweets".into())
+            .with_query("ids", "1")
+            .with_v2_query(&query).unwrap();
+
+        assert_eq!(builder.get_uri(), "https://api.twitter.com/2/tweets?ids=1&tweet.fields=lang");
+
+        let invalid = V2Query::new().max_results(1001);
+        assert!(RequestBuilder::new(Method::GET, "https://api.twitter.com/2/tweets".into()).with_v2_query(&invalid).is_err());
+    }
+
+    #[test]
+    fn looks_up_at_most_100_ids() {
+        let query = V2Query::new().ids(["20", "21", "20"]).tweet_fields([TweetField::Entities]);
+        assert_eq!(query.to_query_string(), "ids=20%2C21&tweet.fields=entities");
+
+        let errors = V2Query::new().ids((0..101).map(|n| n.to_string())).validate().unwrap_err();
+        assert_eq!(errors.0[0].field, "ids");
+    }
+
+    #[test]
+    fn checks_the_page_size_against_the_range_of_the_endpoint() {
+        let valid = |size: u32, range| V2Query::new().max_results(size).max_results_range(range).validate().is_ok();
+
+        assert!(valid(1000, FOLLOWS_RESULTS));
+        assert!(!valid(1000, TIMELINE_RESULTS));
+        assert!(valid(5, TIMELINE_RESULTS));
+        assert!(!valid(5, LIKED_TWEETS_RESULTS));
+    }
+}
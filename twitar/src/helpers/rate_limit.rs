@@ -0,0 +1,5 @@
+mod rate_limit;
+mod counter;
+
+pub use rate_limit::{RateLimit, RateLimiter};
+pub use counter::{app_budget, budget, Bucket, BudgetOwner, MemoryCounter, RateCounter, RedisCounter, Reservation, Window};
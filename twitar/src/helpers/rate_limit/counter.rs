@@ -0,0 +1,445 @@
+use std::{collections::HashMap, fmt, sync::{Arc, Mutex}, time::{Duration, SystemTime, UNIX_EPOCH}};
+use async_trait::async_trait;
+use redis::{Client as RedisClient, Script};
+use uuid::Uuid;
+
+use crate::helpers::{rate_limit::RateLimit, response::TResult, shared::SharedConnection};
+
+#[cfg(test)]
+#[path = "./counter.test.rs"]
+mod counter_test;
+
+
+const KEY_PREFIX: &str = "twitar:rate";
+const FIFTEEN_MINUTES: u64 = 15 * 60;
+const DAY: u64 = 24 * 60 * 60;
+
+
+/// Whose budgets a request is taken from: the app (by its OAuth client id) and the user it is made for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BudgetOwner {
+    pub app: String,
+    pub user: String,
+}
+
+impl BudgetOwner {
+    pub fn new(app: &str, user: &str) -> Self {
+        Self { app: app.into(), user: user.into() }
+    }
+
+    /// The budgets of `endpoint` this owner has, the user's before the app's
+    pub fn buckets(&self, endpoint: &str) -> Vec<(Bucket, Window)> {
+        let user = budget(endpoint).map(|window| (Bucket::user(&self.app, &self.user, endpoint), window));
+        let app = app_budget(endpoint).map(|window| (Bucket::app(&self.app, endpoint), window));
+
+        user.into_iter().chain(app).collect()
+    }
+}
+
+
+/// A budget of requests: of the app, or of one user of the app, on one endpoint (e.g. `DELETE /2/tweets/:id`)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Bucket {
+    pub app: String,
+    pub user: Option<String>,
+    pub endpoint: String,
+}
+
+impl Bucket {
+    pub fn user(app: &str, user: &str, endpoint: &str) -> Self {
+        Self { app: app.into(), user: Some(user.into()), endpoint: endpoint.into() }
+    }
+
+    pub fn app(app: &str, endpoint: &str) -> Self {
+        Self { app: app.into(), user: None, endpoint: endpoint.into() }
+    }
+
+    pub fn key(&self) -> String {
+        format!("{}:{}:{}:{}", KEY_PREFIX, self.app, self.user.as_deref().unwrap_or("app"), self.endpoint)
+    }
+
+    /// What Twitter last reported for the bucket, which can be lower than what the counter allows
+    fn twitter_key(&self) -> String {
+        format!("{}:twitter", self.key())
+    }
+}
+
+
+/// A fixed window restarts every `period` seconds from its first request, like Twitter's 15 minutes windows.
+/// A sliding window counts the requests of the last `period` seconds, for the 24 hours caps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Window {
+    Fixed { limit: u32, period: u64 },
+    Sliding { limit: u32, period: u64 },
+}
+
+impl Window {
+    pub fn limit(&self) -> u32 {
+        match self {
+            Self::Fixed { limit, .. } | Self::Sliding { limit, .. } => *limit,
+        }
+    }
+
+    pub fn period(&self) -> u64 {
+        match self {
+            Self::Fixed { period, .. } | Self::Sliding { period, .. } => *period,
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Fixed { .. } => "fixed",
+            Self::Sliding { .. } => "sliding",
+        }
+    }
+}
+
+/// The per user budget of the endpoints twitar calls, keyed by their `metrics::endpoint_label`
+pub fn budget(endpoint: &str) -> Option<Window> {
+    match endpoint {
+        "DELETE /2/tweets/:id" => Some(Window::Fixed { limit: 50, period: FIFTEEN_MINUTES }),
+        "DELETE /2/users/:id/likes/:id" => Some(Window::Sliding { limit: 1000, period: DAY }),
+        "POST /1.1/statuses/unretweet/:id.json" => Some(Window::Fixed { limit: 300, period: 3 * 60 * 60 }),
+        "GET /2/users/:id/tweets" => Some(Window::Fixed { limit: 900, period: FIFTEEN_MINUTES }),
+        "GET /2/users/:id/liked_tweets" => Some(Window::Fixed { limit: 75, period: FIFTEEN_MINUTES }),
+        "GET /2/users/by/username/:username" => Some(Window::Fixed { limit: 900, period: FIFTEEN_MINUTES }),
+        "GET /2/tweets" => Some(Window::Fixed { limit: 900, period: FIFTEEN_MINUTES }),
+        _ => None,
+    }
+}
+
+/// The budget the app has on the endpoints twitar calls, shared by all of its users
+pub fn app_budget(endpoint: &str) -> Option<Window> {
+    match endpoint {
+        "GET /2/users/:id/tweets" => Some(Window::Fixed { limit: 1500, period: FIFTEEN_MINUTES }),
+        "GET /2/users/:id/liked_tweets" => Some(Window::Fixed { limit: 75, period: FIFTEEN_MINUTES }),
+        "GET /2/users/by/username/:username" => Some(Window::Fixed { limit: 300, period: FIFTEEN_MINUTES }),
+        "GET /2/tweets" => Some(Window::Fixed { limit: 300, period: FIFTEEN_MINUTES }),
+        _ => None,
+    }
+}
+
+
+/// A request taken from a budget before it is sent. It is committed once Twitter answered, or released when it never got there
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reservation {
+    pub bucket: Bucket,
+    pub window: Window,
+    pub id: String,
+    pub granted: bool,
+    pub remaining: u32,
+    /// Unix timestamp (in seconds) at which the budget has room again
+    pub reset: u64,
+}
+
+impl Reservation {
+    fn new(bucket: &Bucket, window: Window, (granted, remaining, reset): (bool, u32, u64)) -> Self {
+        Self { bucket: bucket.clone(), window, id: Uuid::new_v4().to_string(), granted, remaining, reset }
+    }
+
+    /// Granted without counting, e.g. when the counters cannot be reached
+    pub fn unchecked(bucket: &Bucket, window: Window) -> Self {
+        Self::new(bucket, window, (true, window.limit(), 0))
+    }
+
+    /// How long to wait for the budget, when the reservation was denied
+    pub fn wait_time(&self) -> Option<Duration> {
+        let now = now_millis() / 1000;
+
+        match self.granted || self.reset <= now {
+            true => None,
+            false => Some(Duration::from_secs(self.reset - now)),
+        }
+    }
+}
+
+
+/// Counts the requests made from each bucket. Reserving is atomic, so replicas sharing a store never overspend a budget
+#[async_trait]
+pub trait RateCounter: fmt::Debug + Send + Sync {
+    async fn reserve(&self, bucket: &Bucket, window: Window) -> TResult<Reservation>;
+
+    /// Keeps the reservation counted, and aligns the bucket with the `x-rate-limit-*` headers Twitter answered with
+    async fn commit(&self, reservation: &Reservation, reported: Option<RateLimit>) -> TResult<()>;
+
+    /// Gives the reservation back, for a request that did not reach Twitter
+    async fn release(&self, reservation: &Reservation) -> TResult<()>;
+}
+
+
+fn now_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or_default()
+}
+
+
+// every script reads the clock of redis, so replicas with drifting clocks still agree on the windows
+const FIXED_RESERVE: &str = r#"
+local now = tonumber(redis.call('TIME')[1])
+local limit = tonumber(ARGV[1])
+local used = tonumber(redis.call('GET', KEYS[1]) or '0')
+local ttl = redis.call('TTL', KEYS[1])
+if ttl < 0 then ttl = tonumber(ARGV[2]) end
+
+local twitter = redis.call('GET', KEYS[2])
+if twitter and tonumber(twitter) < 1 then
+    return {0, 0, now + math.max(redis.call('TTL', KEYS[2]), 0)}
+end
+
+if used >= limit then
+    return {0, 0, now + ttl}
+end
+
+redis.call('INCR', KEYS[1])
+if redis.call('TTL', KEYS[1]) < 0 then redis.call('EXPIRE', KEYS[1], ARGV[2]) end
+if twitter then redis.call('DECR', KEYS[2]) end
+
+return {1, limit - used - 1, now + ttl}
+"#;
+
+const SLIDING_RESERVE: &str = r#"
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local limit = tonumber(ARGV[1])
+local period = tonumber(ARGV[2]) * 1000
+
+redis.call('ZREMRANGEBYSCORE', KEYS[1], '-inf', now - period)
+local used = redis.call('ZCARD', KEYS[1])
+local oldest = redis.call('ZRANGE', KEYS[1], 0, 0, 'WITHSCORES')
+local reset = now + period
+if oldest[2] then reset = tonumber(oldest[2]) + period end
+
+local twitter = redis.call('GET', KEYS[2])
+if twitter and tonumber(twitter) < 1 then
+    return {0, 0, math.floor(now / 1000) + math.max(redis.call('TTL', KEYS[2]), 0)}
+end
+
+if used >= limit then
+    return {0, 0, math.ceil(reset / 1000)}
+end
+
+redis.call('ZADD', KEYS[1], now, ARGV[3])
+redis.call('PEXPIRE', KEYS[1], period)
+if twitter then redis.call('DECR', KEYS[2]) end
+
+return {1, limit - used - 1, math.ceil(reset / 1000)}
+"#;
+
+const RELEASE: &str = r#"
+if ARGV[1] == 'sliding' then
+    redis.call('ZREM', KEYS[1], ARGV[2])
+elseif tonumber(redis.call('GET', KEYS[1]) or '0') > 0 then
+    redis.call('DECR', KEYS[1])
+end
+
+if redis.call('EXISTS', KEYS[2]) == 1 then redis.call('INCR', KEYS[2]) end
+return 1
+"#;
+
+// Twitter's windows are fixed, so only a fixed counter can take what it reports as used
+const RECONCILE: &str = r#"
+redis.call('SET', KEYS[2], ARGV[1])
+redis.call('EXPIREAT', KEYS[2], ARGV[2])
+
+if ARGV[3] == 'fixed' then
+    local used = tonumber(redis.call('GET', KEYS[1]) or '0')
+    if tonumber(ARGV[4]) > used then
+        redis.call('SET', KEYS[1], ARGV[4])
+        redis.call('EXPIREAT', KEYS[1], ARGV[2])
+    end
+end
+
+return 1
+"#;
+
+
+/// Counters every replica shares, updated by Lua scripts so a check and its increment cannot interleave with another replica's
+#[derive(Debug, Clone)]
+pub struct RedisCounter {
+    connection: SharedConnection,
+}
+
+impl RedisCounter {
+    pub fn new(client: RedisClient) -> Self {
+        Self { connection: SharedConnection::new(client) }
+    }
+}
+
+#[async_trait]
+impl RateCounter for RedisCounter {
+    async fn reserve(&self, bucket: &Bucket, window: Window) -> TResult<Reservation> {
+        let mut con = self.connection.get().await?;
+
+        let script = match window {
+            Window::Fixed { .. } => Script::new(FIXED_RESERVE),
+            Window::Sliding { .. } => Script::new(SLIDING_RESERVE),
+        };
+
+        let reservation = Reservation::new(bucket, window, (false, 0, 0));
+
+        let (granted, remaining, reset): (u8, u32, u64) = script
+            .key(bucket.key()).key(bucket.twitter_key())
+            .arg(window.limit()).arg(window.period()).arg(&reservation.id)
+            .invoke_async(&mut con).await?;
+
+        Ok(Reservation { granted: granted == 1, remaining, reset, ..reservation })
+    }
+
+    async fn commit(&self, reservation: &Reservation, reported: Option<RateLimit>) -> TResult<()> {
+        let reported = match reported {
+            Some(reported) => reported,
+            None => return Ok(()),
+        };
+
+        let mut con = self.connection.get().await?;
+        let used = reported.limit.saturating_sub(reported.remaining);
+
+        Script::new(RECONCILE)
+            .key(reservation.bucket.key()).key(reservation.bucket.twitter_key())
+            .arg(reported.remaining).arg(reported.reset).arg(reservation.window.kind()).arg(used)
+            .invoke_async::<_, u8>(&mut con).await?;
+
+        Ok(())
+    }
+
+    async fn release(&self, reservation: &Reservation) -> TResult<()> {
+        let mut con = self.connection.get().await?;
+
+        Script::new(RELEASE)
+            .key(reservation.bucket.key()).key(reservation.bucket.twitter_key())
+            .arg(reservation.window.kind()).arg(&reservation.id)
+            .invoke_async::<_, u8>(&mut con).await?;
+
+        Ok(())
+    }
+}
+
+
+#[derive(Debug, Default)]
+struct MemoryBuckets {
+    /// Requests made in the fixed window, and when (unix millis) it restarts
+    fixed: HashMap<String, (u32, u64)>,
+    /// When (unix millis) each request of the sliding window was made
+    sliding: HashMap<String, Vec<(u64, String)>>,
+    /// Requests Twitter reported left, and when (unix millis) that stops holding
+    twitter: HashMap<String, (u32, u64)>,
+}
+
+/// The same counters as `RedisCounter`, within this process. For tests and single replica setups without Redis
+#[derive(Debug, Clone, Default)]
+pub struct MemoryCounter {
+    buckets: Arc<Mutex<MemoryBuckets>>,
+}
+
+impl MemoryCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl RateCounter for MemoryCounter {
+    async fn reserve(&self, bucket: &Bucket, window: Window) -> TResult<Reservation> {
+        let now = now_millis();
+        let mut buckets = self.buckets.lock().unwrap();
+        let MemoryBuckets { fixed, sliding, twitter } = &mut *buckets;
+
+        let key = bucket.key();
+        let period = window.period() * 1000;
+        let limit = window.limit();
+
+        twitter.retain(|_, (_, until)| *until > now);
+        let reported = twitter.get_mut(&bucket.twitter_key());
+
+        if let Some((0, until)) = reported.as_deref() {
+            return Ok(Reservation::new(bucket, window, (false, 0, until / 1000)));
+        }
+
+        let reservation = match window {
+            Window::Fixed { .. } => {
+                let (used, reset) = fixed.entry(key).or_insert((0, now + period));
+
+                if *reset <= now {
+                    *used = 0;
+                    *reset = now + period;
+                }
+
+                match *used >= limit {
+                    true => Reservation::new(bucket, window, (false, 0, *reset / 1000)),
+                    false => {
+                        *used += 1;
+                        Reservation::new(bucket, window, (true, limit - *used, *reset / 1000))
+                    }
+                }
+            }
+            Window::Sliding { .. } => {
+                let requests = sliding.entry(key).or_default();
+                requests.retain(|(at, _)| *at + period > now);
+
+                let reset = (requests.first().map_or(now, |(at, _)| *at) + period) / 1000;
+
+                match requests.len() as u32 >= limit {
+                    true => Reservation::new(bucket, window, (false, 0, reset)),
+                    false => {
+                        let reservation = Reservation::new(bucket, window, (true, limit - requests.len() as u32 - 1, reset));
+                        requests.push((now, reservation.id.clone()));
+                        reservation
+                    }
+                }
+            }
+        };
+
+        if let (true, Some((left, _))) = (reservation.granted, reported) {
+            *left -= 1;
+        }
+
+        Ok(reservation)
+    }
+
+    async fn commit(&self, reservation: &Reservation, reported: Option<RateLimit>) -> TResult<()> {
+        let reported = match reported {
+            Some(reported) => reported,
+            None => return Ok(()),
+        };
+
+        let mut buckets = self.buckets.lock().unwrap();
+        let reset = reported.reset * 1000;
+
+        buckets.twitter.insert(reservation.bucket.twitter_key(), (reported.remaining, reset));
+
+        if let Window::Fixed { .. } = reservation.window {
+            let used = reported.limit.saturating_sub(reported.remaining);
+            let counter = buckets.fixed.entry(reservation.bucket.key()).or_insert((0, reset));
+
+            if used > counter.0 {
+                *counter = (used, reset);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn release(&self, reservation: &Reservation) -> TResult<()> {
+        let mut buckets = self.buckets.lock().unwrap();
+        let key = reservation.bucket.key();
+
+        match reservation.window {
+            Window::Fixed { .. } => {
+                if let Some((used, _)) = buckets.fixed.get_mut(&key) {
+                    *used = used.saturating_sub(1);
+                }
+            }
+            Window::Sliding { .. } => {
+                if let Some(requests) = buckets.sliding.get_mut(&key) {
+                    requests.retain(|(_, id)| *id != reservation.id);
+                }
+            }
+        }
+
+        if let Some((left, _)) = buckets.twitter.get_mut(&reservation.bucket.twitter_key()) {
+            *left += 1;
+        }
+
+        Ok(())
+    }
+}
+
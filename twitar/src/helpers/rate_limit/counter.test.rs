@@ -0,0 +1,164 @@
+mod test_counter {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+    use redis::Client as RedisClient;
+    use uuid::Uuid;
+
+    use crate::helpers::rate_limit::{budget, Bucket, BudgetOwner, MemoryCounter, RateCounter, RateLimit, RedisCounter, Window};
+
+    const FIXED: Window = Window::Fixed { limit: 2, period: 900 };
+    const SLIDING: Window = Window::Sliding { limit: 2, period: 86400 };
+
+    fn bucket() -> Bucket {
+        Bucket::user("client-id", "2244994945", "DELETE /2/tweets/:id")
+    }
+
+    fn now() -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+    }
+
+    fn memory_counter() -> (Box<dyn RateCounter>, Bucket) {
+        (Box::new(MemoryCounter::new()), bucket())
+    }
+
+    /// `RedisCounter` (its Lua scripts) on `REDIS_URI` (`redis://127.0.0.1/` by default), with a bucket of its own so
+    /// runs do not share counts. The tests using it are ignored by default, `make test_redis` runs them
+    async fn redis_counter() -> (Box<dyn RateCounter>, Bucket) {
+        let uri = std::env::var("REDIS_URI").unwrap_or_else(|_| "redis://127.0.0.1/".into());
+        let reachable = async {
+            let mut con = RedisClient::open(uri.as_str())?.get_async_connection().await?;
+            redis::cmd("PING").query_async::<_, String>(&mut con).await
+        };
+
+        if !matches!(tokio::time::timeout(Duration::from_secs(1), reachable).await, Ok(Ok(_))) {
+            panic!("redis is not reachable on {}, the RedisCounter scripts cannot be tested", uri);
+        }
+
+        let bucket = Bucket::user(&Uuid::new_v4().to_string(), "2244994945", "DELETE /2/tweets/:id");
+        (Box::new(RedisCounter::new(RedisClient::open(uri.as_str()).unwrap())), bucket)
+    }
+
+    /// One `#[tokio::test]` per scenario below, for the counter given
+    macro_rules! counter_suite {
+        ($name:ident, $counter:expr $(, #[$attr:meta])?) => {
+            mod $name {
+                use super::*;
+
+                #[tokio::test] $(#[$attr])?
+                async fn fixed_windows_deny_once_spent() {
+                    super::fixed_windows_deny_once_spent($counter).await
+                }
+
+                #[tokio::test] $(#[$attr])?
+                async fn sliding_windows_deny_once_spent() {
+                    super::sliding_windows_deny_once_spent($counter).await
+                }
+
+                #[tokio::test] $(#[$attr])?
+                async fn released_reservations_are_given_back() {
+                    super::released_reservations_are_given_back($counter).await
+                }
+
+                #[tokio::test] $(#[$attr])?
+                async fn twitter_reports_take_precedence_over_the_counter() {
+                    super::twitter_reports_take_precedence_over_the_counter($counter).await
+                }
+
+                #[tokio::test] $(#[$attr])?
+                async fn twitter_reports_of_requests_made_elsewhere_raise_the_fixed_count() {
+                    super::twitter_reports_of_requests_made_elsewhere_raise_the_fixed_count($counter).await
+                }
+
+                #[tokio::test] $(#[$attr])?
+                async fn commits_without_a_report_keep_the_count() {
+                    super::commits_without_a_report_keep_the_count($counter).await
+                }
+            }
+        };
+    }
+
+    counter_suite!(in_memory, memory_counter());
+    counter_suite!(in_redis, redis_counter().await, #[ignore = "needs Redis on REDIS_URI, run with `make test_redis`"]);
+
+    #[test]
+    fn buckets_are_keyed_by_app_user_and_endpoint() {
+        assert_eq!(bucket().key(), "twitar:rate:client-id:2244994945:DELETE /2/tweets/:id");
+        assert_eq!(Bucket::app("client-id", "POST /2/oauth2/token").key(), "twitar:rate:client-id:app:POST /2/oauth2/token");
+        assert!(budget("DELETE /2/tweets/:id").is_some());
+        assert!(budget("POST /2/oauth2/token").is_none());
+    }
+
+    #[test]
+    fn owners_spend_the_budget_of_the_user_then_the_one_of_the_app() {
+        let owner = BudgetOwner::new("client-id", "2244994945");
+
+        let buckets = owner.buckets("GET /2/users/:id/liked_tweets").into_iter().map(|(bucket, _)| bucket).collect::<Vec<_>>();
+        assert_eq!(buckets, vec![
+            Bucket::user("client-id", "2244994945", "GET /2/users/:id/liked_tweets"),
+            Bucket::app("client-id", "GET /2/users/:id/liked_tweets"),
+        ]);
+
+        assert_eq!(owner.buckets("DELETE /2/tweets/:id").len(), 1);
+        assert!(owner.buckets("POST /2/oauth2/token").is_empty());
+    }
+
+    async fn fixed_windows_deny_once_spent((counter, bucket): (Box<dyn RateCounter>, Bucket)) {
+        let first = counter.reserve(&bucket, FIXED).await.unwrap();
+        let second = counter.reserve(&bucket, FIXED).await.unwrap();
+        let third = counter.reserve(&bucket, FIXED).await.unwrap();
+
+        assert!(first.granted && second.granted);
+        assert_eq!(second.remaining, 0);
+        assert!(!third.granted);
+        assert!(third.wait_time().is_some());
+    }
+
+    async fn sliding_windows_deny_once_spent((counter, bucket): (Box<dyn RateCounter>, Bucket)) {
+        assert!(counter.reserve(&bucket, SLIDING).await.unwrap().granted);
+        assert!(counter.reserve(&bucket, SLIDING).await.unwrap().granted);
+
+        let denied = counter.reserve(&bucket, SLIDING).await.unwrap();
+        assert!(!denied.granted);
+        assert!(denied.reset > now() + 86000);
+    }
+
+    async fn released_reservations_are_given_back((counter, bucket): (Box<dyn RateCounter>, Bucket)) {
+        for window in [FIXED, SLIDING] {
+            let first = counter.reserve(&bucket, window).await.unwrap();
+            counter.reserve(&bucket, window).await.unwrap();
+            counter.release(&first).await.unwrap();
+
+            assert!(counter.reserve(&bucket, window).await.unwrap().granted);
+            assert!(!counter.reserve(&bucket, window).await.unwrap().granted);
+        }
+    }
+
+    async fn twitter_reports_take_precedence_over_the_counter((counter, bucket): (Box<dyn RateCounter>, Bucket)) {
+        let window = Window::Fixed { limit: 50, period: 900 };
+
+        let reservation = counter.reserve(&bucket, window).await.unwrap();
+        counter.commit(&reservation, Some(RateLimit { limit: 50, remaining: 1, reset: now() + 600 })).await.unwrap();
+
+        assert!(counter.reserve(&bucket, window).await.unwrap().granted);
+
+        let denied = counter.reserve(&bucket, window).await.unwrap();
+        assert!(!denied.granted);
+        assert!(denied.reset > now());
+    }
+
+    async fn twitter_reports_of_requests_made_elsewhere_raise_the_fixed_count((counter, bucket): (Box<dyn RateCounter>, Bucket)) {
+        let window = Window::Fixed { limit: 50, period: 900 };
+
+        let reservation = counter.reserve(&bucket, window).await.unwrap();
+        // 40 of the 50 were spent by requests twitar did not count
+        counter.commit(&reservation, Some(RateLimit { limit: 50, remaining: 10, reset: now() + 600 })).await.unwrap();
+
+        assert_eq!(counter.reserve(&bucket, window).await.unwrap().remaining, 9);
+    }
+
+    async fn commits_without_a_report_keep_the_count((counter, bucket): (Box<dyn RateCounter>, Bucket)) {
+        let reservation = counter.reserve(&bucket, SLIDING).await.unwrap();
+        counter.commit(&reservation, None).await.unwrap();
+
+        assert_eq!(counter.reserve(&bucket, SLIDING).await.unwrap().remaining, 0);
+    }
+}
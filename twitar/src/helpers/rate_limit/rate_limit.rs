@@ -0,0 +1,170 @@
+use std::{collections::HashMap, sync::{Arc, Mutex}, time::{Duration, SystemTime, UNIX_EPOCH}};
+use hyper::{Body, Request};
+
+use crate::errors::response::TError;
+use crate::helpers::metrics::endpoint_label;
+use crate::helpers::rate_limit::{Bucket, BudgetOwner, MemoryCounter, RateCounter, Reservation, Window};
+use crate::helpers::response::{make_request, THeaders, TResult};
+use crate::helpers::transport::HttpClient;
+
+#[cfg(test)]
+#[path = "./rate_limit.test.rs"]
+mod rate_limit_test;
+
+
+const X_RATE_LIMIT_LIMIT: &str = "x-rate-limit-limit";
+const X_RATE_LIMIT_REMAINING: &str = "x-rate-limit-remaining";
+const X_RATE_LIMIT_RESET: &str = "x-rate-limit-reset";
+
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+
+/// The state of a rate limit window, as reported by the `x-rate-limit-*` headers of a response
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub limit: u32,
+    pub remaining: u32,
+    /// Unix timestamp (in seconds) at which the window resets
+    pub reset: u64,
+}
+
+impl RateLimit {
+    pub fn from_headers(headers: &THeaders) -> Option<Self> {
+        let header = |key: &str| headers.get(key)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok());
+
+        Some(Self {
+            limit: header(X_RATE_LIMIT_LIMIT)? as u32,
+            remaining: header(X_RATE_LIMIT_REMAINING)? as u32,
+            reset: header(X_RATE_LIMIT_RESET)?,
+        })
+    }
+
+    /// How long to wait before the next request, if the window is exhausted
+    pub fn wait_time(&self) -> Option<Duration> {
+        let now = now();
+
+        if self.remaining > 0 || self.reset <= now {
+            return None;
+        }
+
+        Some(Duration::from_secs(self.reset - now))
+    }
+}
+
+
+/// Keeps the last known rate limit of each endpoint (or any other key) within this process,
+/// and the budgets of each user in the counters every replica shares
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    limits: Arc<Mutex<HashMap<String, RateLimit>>>,
+    counter: Arc<dyn RateCounter>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self { limits: Arc::default(), counter: Arc::new(MemoryCounter::new()) }
+    }
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_counter(self, counter: Arc<dyn RateCounter>) -> Self {
+        Self { counter, ..self }
+    }
+
+    /// Records the limit reported by a response. Responses without the headers are ignored
+    pub fn update(&self, key: &str, headers: &THeaders) {
+        if let Some(limit) = RateLimit::from_headers(headers) {
+            self.limits.lock().unwrap().insert(key.to_string(), limit);
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<RateLimit> {
+        self.limits.lock().unwrap().get(key).copied()
+    }
+
+    pub fn wait_time(&self, key: &str) -> Option<Duration> {
+        self.get(key).and_then(|limit| limit.wait_time())
+    }
+
+    /// Sleeps until the window of `key` resets when it has no request left
+    pub async fn wait(&self, key: &str) {
+        if let Some(duration) = self.wait_time(key) {
+            tokio::time::sleep(duration).await;
+        }
+    }
+
+    /// Takes a request from the budget of `bucket`. When the counters cannot be reached the request is let through,
+    /// Twitter still refuses the ones over its limits
+    pub async fn reserve(&self, bucket: &Bucket, window: Window) -> Reservation {
+        self.counter.reserve(bucket, window).await.unwrap_or_else(|e| {
+            tracing::warn!(bucket = %bucket.key(), error = %e, "unable to reserve from the rate limit counters");
+            Reservation::unchecked(bucket, window)
+        })
+    }
+
+    /// Gives a granted reservation back, for a request that is not made after all
+    pub async fn release(&self, reservation: &Reservation) {
+        if let Err(e) = self.counter.release(reservation).await {
+            tracing::warn!(bucket = %reservation.bucket.key(), error = %e, "unable to release a rate limit reservation");
+        }
+    }
+
+    /// Settles a granted reservation with the outcome of its request (as `make_request` returns it)
+    pub async fn settle(&self, reservation: &Reservation, result: &TResult<(THeaders, Vec<u8>)>) {
+        let settled = match result {
+            // the request never reached Twitter
+            Err(TError::NetworkError(_)) => self.counter.release(reservation).await,
+            // the headers describe the budget of the user the request was made for
+            _ if reservation.bucket.user.is_none() => self.counter.commit(reservation, None).await,
+            Err(TError::RateLimit(reset)) => {
+                let reported = RateLimit { limit: reservation.window.limit(), remaining: 0, reset: reset.unwrap_or(reservation.reset) };
+                self.counter.commit(reservation, Some(reported)).await
+            }
+            Ok((headers, _)) => self.counter.commit(reservation, RateLimit::from_headers(headers)).await,
+            Err(TError::TwitterError(failure)) => self.counter.commit(reservation, RateLimit::from_headers(&failure.0)).await,
+            Err(_) => self.counter.commit(reservation, None).await,
+        };
+
+        if let Err(e) = settled {
+            tracing::warn!(bucket = %reservation.bucket.key(), error = %e, "unable to settle a rate limit reservation");
+        }
+    }
+
+    /// Sends `request` once the budgets of its endpoint, the user's and the app's, have room, and settles them with the answer.
+    /// A spent budget (possibly spent by another replica) is answered with `TError::RateLimit` without asking Twitter
+    pub async fn send(&self, owner: &BudgetOwner, request: Request<Body>, client: HttpClient) -> TResult<(THeaders, Vec<u8>)> {
+        let endpoint = endpoint_label(request.method().as_str(), request.uri().path());
+        let mut reservations = vec![];
+
+        for (bucket, window) in owner.buckets(&endpoint) {
+            let reservation = self.reserve(&bucket, window).await;
+
+            if !reservation.granted {
+                for granted in &reservations {
+                    self.release(granted).await;
+                }
+
+                return Err(TError::RateLimit(Some(reservation.reset)));
+            }
+
+            reservations.push(reservation);
+        }
+
+        let response = make_request(request, client).await;
+
+        for reservation in &reservations {
+            self.settle(reservation, &response).await;
+        }
+
+        response
+    }
+}
@@ -0,0 +1,78 @@
+#[cfg(test)]
+mod test_rate_limit {
+    use std::{sync::Arc, time::{Duration, SystemTime, UNIX_EPOCH}};
+    use http::{HeaderValue, Method};
+    use serde_json::json;
+
+    use crate::errors::response::TError;
+    use crate::helpers::rate_limit::{app_budget, budget, Bucket, BudgetOwner, MemoryCounter, RateLimit, RateLimiter};
+    use crate::helpers::response::THeaders;
+    use crate::helpers::transport::{MemoryTransport, MockResponse};
+    use crate::middlewares::request_builder::RequestBuilder;
+
+    fn headers(remaining: u32, reset: u64) -> THeaders {
+        let mut headers = THeaders::new();
+        headers.insert("x-rate-limit-limit", HeaderValue::from(900));
+        headers.insert("x-rate-limit-remaining", HeaderValue::from(remaining));
+        headers.insert("x-rate-limit-reset", HeaderValue::from(reset));
+        headers
+    }
+
+    fn now() -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+    }
+
+    #[test]
+    fn parses_the_rate_limit_headers() {
+        let limit = RateLimit::from_headers(&headers(12, 1650000000)).unwrap();
+
+        assert_eq!(limit, RateLimit { limit: 900, remaining: 12, reset: 1650000000 });
+        assert!(RateLimit::from_headers(&THeaders::new()).is_none());
+    }
+
+    #[test]
+    fn waits_only_when_the_window_is_exhausted() {
+        let limiter = RateLimiter::new();
+        let reset = now() + 30;
+
+        limiter.update("tweets", &headers(3, reset));
+        assert_eq!(limiter.wait_time("tweets"), None);
+
+        limiter.update("tweets", &headers(0, reset));
+        let wait = limiter.wait_time("tweets").unwrap();
+        assert!(wait > Duration::from_secs(25) && wait <= Duration::from_secs(30));
+
+        limiter.update("likes", &headers(0, now() - 1));
+        assert_eq!(limiter.wait_time("likes"), None);
+        assert_eq!(limiter.wait_time("unknown"), None);
+    }
+
+    #[tokio::test]
+    async fn spent_budgets_are_not_asked_of_twitter() {
+        let endpoint = "GET /2/users/:id/liked_tweets";
+        let counter = MemoryCounter::new();
+        let limiter = RateLimiter::new().with_counter(Arc::new(counter.clone()));
+        let owner = BudgetOwner::new("client-id", "2244994945");
+
+        let transport = MemoryTransport::new();
+        transport.respond(Method::GET, "/2/users/2244994945/liked_tweets", MockResponse::json(200, json!({"data": []})));
+        let request = || RequestBuilder::new(Method::GET, "https://api.twitter.com/2/users/2244994945/liked_tweets".into()).build_request();
+
+        assert!(limiter.send(&owner, request(), transport.client()).await.is_ok());
+
+        // the app spends the rest of its budget on its other users
+        let app_window = app_budget(endpoint).unwrap();
+        for _ in 1..app_window.limit() {
+            limiter.reserve(&Bucket::app("client-id", endpoint), app_window).await;
+        }
+
+        let denied = limiter.send(&owner, request(), transport.client()).await;
+        assert!(matches!(denied, Err(TError::RateLimit(Some(_)))));
+        assert_eq!(transport.requests().len(), 1);
+
+        // what was taken from the budget of the user is given back
+        let user_window = budget(endpoint).unwrap();
+        let user = limiter.reserve(&Bucket::user("client-id", "2244994945", endpoint), user_window).await;
+        assert_eq!(user.remaining, user_window.limit() - 2);
+    }
+}
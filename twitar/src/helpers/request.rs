@@ -1,4 +1,4 @@
 mod request;
 
-pub use request::HyperClient;
-pub use request::req_query;
\ No newline at end of file
+pub use request::req_query;
+pub use request::{request_parts, extract_query, extract_body};
@@ -1,14 +1,14 @@
-use hyper::{Client, client::HttpConnector};
-use hyper_tls::HttpsConnector;
+use hyper::{Body, Request};
+use twitar_macro::{Extract, ExtractError, RequestParts, Source, Validate};
+use url::form_urlencoded;
+
+use crate::helpers::response::TResult;
 
 #[cfg(test)]
 #[path = "./request.test.rs"]
 mod request_test;
 
 
-pub type HyperClient = Client<HttpsConnector<HttpConnector>>;
-
-
 pub fn req_query<'a>(query: Option<&str>, key: &'a str) -> Option<String> {
 
     if let Some(str_query) = query {
@@ -21,4 +21,44 @@ pub fn req_query<'a>(query: Option<&str>, key: &'a str) -> Option<String> {
     }
     
     None
-}
\ No newline at end of file
+}
+
+
+/// Splits the (decoded) query and the headers of a request into the parts `Extract` reads from
+pub fn request_parts(req: &Request<Body>) -> RequestParts {
+    let query = req.uri().query()
+        .map(|q| form_urlencoded::parse(q.as_bytes()).into_owned().collect())
+        .unwrap_or_default();
+
+    let headers = req.headers().iter()
+        .filter_map(|(k, v)| v.to_str().ok().map(|v| (k.as_str().to_lowercase(), v.to_string())))
+        .collect();
+
+    RequestParts { query, headers, ..RequestParts::default() }
+}
+
+/// Extracts and validates `T` from the query and headers of the request
+pub fn extract_query<T: Extract + Validate>(req: &Request<Body>) -> TResult<T> {
+    let input = T::extract(&request_parts(req))?;
+    input.validate()?;
+
+    Ok(input)
+}
+
+/// Extracts and validates `T` from the request including its JSON body, the request is consumed
+pub async fn extract_body<T: Extract + Validate>(req: Request<Body>) -> TResult<T> {
+    let mut parts = request_parts(&req);
+    let body = hyper::body::to_bytes(req.into_body()).await?;
+
+    if !body.is_empty() {
+        let json = serde_json::from_slice(&body).map_err(|e| ExtractError::Invalid {
+            field: "body", source: Source::Body, reason: e.to_string()
+        })?;
+        parts.body = Some(json);
+    }
+
+    let input = T::extract(&parts)?;
+    input.validate()?;
+
+    Ok(input)
+}
@@ -25,4 +25,65 @@ mod test_req_query {
 
         assert!(req_query(query, key).is_none());
     }
-}
\ No newline at end of file
+}
+
+mod test_extract {
+    use hyper::{Body, Request};
+    use twitar_macro::{Extract, Validate};
+
+    use crate::errors::response::TError;
+    use crate::helpers::request::{extract_body, extract_query};
+
+    #[derive(Debug, Extract, Validate)]
+    struct Lookup {
+        #[extract(query)]
+        #[validate(length(min = 1, max = 15))]
+        username: String,
+        #[extract(header = "x-request-id")]
+        request_id: Option<String>,
+    }
+
+    #[derive(Debug, Extract, Validate)]
+    #[extract(body)]
+    struct Ids {
+        #[validate(numeric, unique, max_items = 2)]
+        tweets: Vec<String>,
+    }
+
+    fn get(uri: &str) -> Request<Body> {
+        Request::builder().uri(uri).header("X-Request-Id", "abc").body(Body::empty()).unwrap()
+    }
+
+    #[test]
+    fn extracts_decoded_query_and_headers() {
+        let lookup: Lookup = extract_query(&get("/user?username=tolu%5Fmide")).unwrap();
+
+        assert_eq!(lookup.username, "tolu_mide");
+        assert_eq!(lookup.request_id, Some("abc".into()));
+    }
+
+    #[test]
+    fn missing_values_are_bad_requests() {
+        let err = extract_query::<Lookup>(&get("/user")).unwrap_err();
+        assert!(matches!(err, TError::BadRequest(_)));
+    }
+
+    #[test]
+    fn broken_rules_are_unprocessable() {
+        let err = extract_query::<Lookup>(&get("/user?username=")).unwrap_err();
+        assert!(matches!(err, TError::Unprocessable(_)));
+    }
+
+    #[tokio::test]
+    async fn extracts_json_body() {
+        let req = Request::builder().body(Body::from(r#"{"tweets": ["1", "2"]}"#)).unwrap();
+        let ids: Ids = extract_body(req).await.unwrap();
+        assert_eq!(ids.tweets, vec!["1", "2"]);
+
+        let req = Request::builder().body(Body::from(r#"{"tweets": ["1", "1", "3"]}"#)).unwrap();
+        assert!(matches!(extract_body::<Ids>(req).await.unwrap_err(), TError::Unprocessable(_)));
+
+        let req = Request::builder().body(Body::from("not json")).unwrap();
+        assert!(matches!(extract_body::<Ids>(req).await.unwrap_err(), TError::BadRequest(_)));
+    }
+}
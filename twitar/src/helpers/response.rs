@@ -1,10 +1,10 @@
 mod response;
 
-pub use response::TwitterResponseData;
-pub use response::TwitterResponseHashData;
+pub use response::TwitterResponse;
 pub use response::ResponseBuilder;
 pub use response::make_request;
 pub use response::CONTENT_TYPE;
+pub use response::RECENT_TWEETS_KEPT;
 pub use response::TResult;
 pub use response::THeaders;
 pub use response::ApiBody;
\ No newline at end of file
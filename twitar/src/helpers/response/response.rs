@@ -1,13 +1,15 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, time::Instant};
 
 use http::{Request, HeaderMap, HeaderValue, StatusCode};
 use hyper::{Response, Body};
 use serde::{Serialize, Deserialize};
-use serde_json::Value;
+use tracing::Instrument;
 
 
 use crate::errors::response::{TError, TwitterErrors};
-use crate::helpers::request::HyperClient;
+use crate::errors::twitter_errors::{PartialError, Problem, TwitterApiError};
+use crate::helpers::{metrics::{endpoint_label, outcome, METRICS}, rate_limit::RateLimit, transport::HttpClient};
+use crate::models::{Includes, Meta, Tweet};
 
 // pub type ApiResponse = http::Result<Response<Body>>;
 
@@ -23,31 +25,61 @@ mod response_test;
 
 
 const X_RATE_LIMIT_RESET: &str = "X-Rate-Limit-Reset";
+/// Twitter's error code for an exhausted rate limit
+const RATE_LIMIT_CODE: i32 = 88;
 
 pub const CONTENT_TYPE: &'static str = "application/x-www-form-urlencoded";
 
 
-pub async fn make_request(request: Request<Body>, client: HyperClient) -> TResult<(THeaders, Vec<u8>)> {
-    let res: Response<Body> = client.request(request).await.unwrap();
+pub async fn make_request(request: Request<Body>, client: HttpClient) -> TResult<(THeaders, Vec<u8>)> {
+    let (method, path) = (request.method().to_string(), request.uri().path().to_string());
+    let span = tracing::info_span!("twitter", method = %method, endpoint = %endpoint_label(&method, &path));
+    let start = Instant::now();
+
+    let result = send_request(request, client, &method, &path).instrument(span.clone()).await;
+    METRICS.observe_twitter(&method, &path, &result, start);
+
+    span.in_scope(|| match &result {
+        Ok(_) => tracing::debug!(latency_ms = start.elapsed().as_millis() as u64, "twitter answered"),
+        Err(e) => tracing::warn!(latency_ms = start.elapsed().as_millis() as u64, outcome = outcome(&result), error = %e, "twitter call failed"),
+    });
+
+    result
+}
+
+async fn send_request(request: Request<Body>, client: HttpClient, method: &str, path: &str) -> TResult<(THeaders, Vec<u8>)> {
+    let res: Response<Body> = client.send(request).await?;
     
     let (parts, body) = res.into_parts();
     let body = hyper::body::to_bytes(body).await?.to_vec();
 
+    if let Some(limit) = RateLimit::from_headers(&parts.headers) {
+        METRICS.set_rate_limit(method, path, limit.remaining);
+    }
+
     // println!("WHAT THE ERROR IS LIKE \n\n\n {:#?} \n\n\n", String::from_utf8_lossy(&body));
     // println!("THE PARTS {:#?}", parts);
     
     if let Ok(errors) = serde_json::from_slice::<TwitterErrors>(&body) {
         // println!("THE LOOPED ERROR SETS");
-        if errors.errors.iter().any(|e| e.code == 88)
+        if errors.errors.iter().any(|e| e.code == RATE_LIMIT_CODE)
         && parts.headers.contains_key(X_RATE_LIMIT_RESET) {
-            return Err(TError::RateLimit())
+            return Err(TError::RateLimit(rate_limit_reset(&parts.headers)))
         } else {
-            return Err(TError::TwitterError(parts.headers, errors))
+            return Err(TError::twitter(parts.headers, TwitterApiError::V1(errors)))
         }
     }
 
+    if parts.status == StatusCode::TOO_MANY_REQUESTS {
+        return Err(TError::RateLimit(rate_limit_reset(&parts.headers)))
+    }
+
     if !parts.status.is_success() {
-        // println!("THERE WAS AN ISSUE!!!");
+        // v2 endpoints describe failed requests with a problem body
+        if let Ok(problem) = serde_json::from_slice::<Problem>(&body) {
+            return Err(TError::twitter(parts.headers, TwitterApiError::V2(problem)))
+        }
+
         return Err(TError::BadStatus(parts.status))
     }
 
@@ -56,6 +88,13 @@ pub async fn make_request(request: Request<Body>, client: HyperClient) -> TResul
     Ok((parts.headers, body))
 }
 
+/// The unix timestamp at which the current rate limit window resets
+fn rate_limit_reset(headers: &THeaders) -> Option<u64> {
+    headers.get(X_RATE_LIMIT_RESET)
+        .and_then(|reset| reset.to_str().ok())
+        .and_then(|reset| reset.parse().ok())
+}
+
 
 
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -88,75 +127,58 @@ impl<T> ResponseBuilder<T> where T: Serialize {
 
 
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct TwitterResponseData {
-    data: Vec<HashMap<String, String>>,
-    meta: HashMap<String, Value>
-}
-
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct TwitterResponseHashData {
-    data: HashMap<String, String>,
+/// A v2 response body. When only some of the requested items could be returned, `data` holds them
+/// and `errors` describes the ones that failed
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TwitterResponse<T> {
+    pub data: Option<T>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<PartialError>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub includes: Option<Includes>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
 }
 
+impl<T> TwitterResponse<T> {
+    pub fn is_partial(&self) -> bool {
+        self.data.is_some() && !self.errors.is_empty()
+    }
 
-impl TwitterResponseHashData {
-        // Only use for responses whose vector (also called array) cotnains only one hashmap (also called objects)
-    pub fn into_one_dict(self) -> HashMap<String, String> {
-        let mut dict: HashMap<String, String> = HashMap::new();
-
-        for key in self.data.keys() {
-            // dict.extend(self.data.iter());
-            dict.insert(key.into(), self.data.get(key).unwrap().into());
-        };
-        return dict
+    /// The ids (or values) Twitter reported as failed
+    pub fn failed_ids(&self) -> Vec<&str> {
+        self.errors.iter()
+            .filter_map(|e| e.resource_id.as_deref().or(e.value.as_deref()))
+            .collect()
     }
 }
 
 
+/// The most recent tweets of a timeline, which are never queued for removal
+pub const RECENT_TWEETS_KEPT: usize = 11;
 
-impl TwitterResponseData {
-    pub fn separate_tweets_from_rts(&self, exclude_head: bool) -> HashMap<String, Vec<String>>{
-        let mut dict = HashMap::new();
-        let mut tweets = vec![];
-        let mut rts = vec![];
+impl TwitterResponse<Vec<Tweet>> {
+    pub fn tweets(&self) -> &[Tweet] {
+        self.data.as_deref().unwrap_or_default()
+    }
 
-        let mut start = 0;
+    /// Splits the ids of a user's own tweets from those of their retweets
+    pub fn separate_tweets_from_rts(&self, exclude_head: bool) -> HashMap<String, Vec<String>> {
+        let start = if exclude_head { RECENT_TWEETS_KEPT } else { 0 };
 
-        if exclude_head {
-            start = 11;
-        }
+        let (rts, tweets): (Vec<&Tweet>, Vec<&Tweet>) = self.tweets().iter().skip(start)
+            .partition(|tweet| tweet.is_retweet());
 
-        for num in start..self.data.len() {
-            let tweet = &self.data[num];
-            let id = tweet.get("id").unwrap().clone();
-            if tweet.get("text").unwrap().starts_with("RT") {
-                rts.push(id);
-            } else {
-                tweets.push(id)
-            }
-        }
+        let ids = |tweets: Vec<&Tweet>| tweets.into_iter().map(|t| t.id.clone()).collect::<Vec<_>>();
 
-        dict.insert("tweets".into(), tweets);
-        dict.insert("rts".into(), rts);
+        let mut dict = HashMap::new();
+        dict.insert("tweets".into(), ids(tweets));
+        dict.insert("rts".into(), ids(rts));
 
         dict
     }
 
-    pub fn parse_metadata(&self) -> HashMap<String, String> {
-        let keys = self.meta.keys().cloned().collect::<Vec<String>>();
-        let map = keys.iter()
-            .map(|k,| (k.to_string(), self.meta.get(k).unwrap().to_string())).collect::<HashMap<_, _>>();
-        
-        return map;
-    }
-
-    // Gets ids from a vector of dictionaries containing "id" and "text"
     pub fn get_ids(&self) -> Vec<String> {
-        self.data.iter().map(|map| map.get("id").unwrap().clone()).collect::<Vec<_>>()
+        self.tweets().iter().map(|tweet| tweet.id.clone()).collect()
     }
 }
-
-
-
@@ -1,14 +1,14 @@
 mod test_response {
-    use std::collections::HashMap;
-
     use wiremock::{MockServer, Mock, ResponseTemplate};
     use wiremock::matchers::{method, path};
-    use http::{Request, Response};
-    use hyper::{Body, Client, };
-    use hyper_tls::HttpsConnector;
+    use http::Request;
+    use hyper::Body;
     use serde_json;
 
-    use crate::helpers::response::make_request;
+    use crate::errors::response::TError;
+    use crate::helpers::response::{make_request, TwitterResponse};
+    use crate::helpers::transport::HyperTransport;
+    use crate::models::Tweet;
 
 
 
@@ -25,11 +25,11 @@ mod test_response {
  
         let req = Request::builder()
             .method("GET")
-            .uri("api?key=value")
+            .uri(format!("{}/api?key=value", mock_server.uri()))
             .body(Body::empty()).unwrap();
 
 
-        let client = Client::builder().build::<_, hyper::Body>(HttpsConnector::new());
+        let client = HyperTransport::client();
 
         let request = make_request(req, client).await;
 
@@ -53,14 +53,56 @@ mod test_response {
  
         let req = Request::builder()
             .method("GET")
-            .uri("api?key=value")
+            .uri(format!("{}/api?key=value", mock_server.uri()))
             .body(Body::empty()).unwrap();
 
 
-        let client = Client::builder().build::<_, hyper::Body>(HttpsConnector::new());
+        let client = HyperTransport::client();
 
         let request = make_request(req, client).await;
 
         assert!(request.is_err());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn too_many_requests_carries_the_reset_time() {
+        let mock_response = ResponseTemplate::new(429)
+            .insert_header("x-rate-limit-reset", "1650000000")
+            .set_body_json(serde_json::json!({"title": "Too Many Requests", "status": 429}));
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api"))
+            .respond_with(mock_response)
+            .mount(&mock_server).await;
+
+        let req = Request::builder()
+            .method("GET")
+            .uri(format!("{}/api", mock_server.uri()))
+            .body(Body::empty()).unwrap();
+
+        let client = HyperTransport::client();
+
+        let request = make_request(req, client).await;
+
+        assert!(matches!(request, Err(TError::RateLimit(Some(1650000000)))));
+    }
+
+    #[test]
+    fn separates_tweets_from_retweets() {
+        let response: TwitterResponse<Vec<Tweet>> = serde_json::from_value(serde_json::json!({
+            "data": [
+                {"id": "1", "text": "hello", "public_metrics": {"retweet_count": 1, "reply_count": 0, "like_count": 2, "quote_count": 0}},
+                {"id": "2", "text": "RT @someone: hi", "referenced_tweets": [{"type": "retweeted", "id": "9"}]}
+            ],
+            "meta": {"result_count": 2, "next_token": "7140dibdnow9c7btw3w29grvxfcgvpb9n9coehpk7xz5i"}
+        })).unwrap();
+
+        let separated = response.separate_tweets_from_rts(false);
+
+        assert_eq!(separated["tweets"], vec!["1"]);
+        assert_eq!(separated["rts"], vec!["2"]);
+        assert_eq!(response.get_ids(), vec!["1", "2"]);
+        assert!(response.meta.unwrap().next_token.is_some());
+    }
+}
@@ -0,0 +1,3 @@
+mod shared;
+
+pub use shared::{SharedConnection, SharedStore};
@@ -0,0 +1,42 @@
+use std::{fmt, sync::Arc};
+use redis::{aio::ConnectionManager, Client as RedisClient};
+use serde::Deserialize;
+use tokio::sync::OnceCell;
+
+use crate::helpers::response::TResult;
+
+
+/// Where state the replicas have in common is kept: `redis` shares it between them, `memory` keeps it in this process
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SharedStore {
+    #[default]
+    Redis,
+    Memory,
+}
+
+
+/// One Redis connection for every request of the process, multiplexed instead of a connection per command.
+/// It is opened on first use, so the server starts while Redis is down, and reconnects by itself once it is back
+#[derive(Clone)]
+pub struct SharedConnection {
+    client: RedisClient,
+    manager: Arc<OnceCell<ConnectionManager>>,
+}
+
+impl SharedConnection {
+    pub fn new(client: RedisClient) -> Self {
+        Self { client, manager: Arc::default() }
+    }
+
+    pub async fn get(&self) -> TResult<ConnectionManager> {
+        let manager = self.manager.get_or_try_init(|| ConnectionManager::new(self.client.clone())).await?;
+        Ok(manager.clone())
+    }
+}
+
+impl fmt::Debug for SharedConnection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedConnection").field("client", &self.client).field("open", &self.manager.initialized()).finish()
+    }
+}
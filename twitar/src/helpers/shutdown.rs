@@ -0,0 +1,3 @@
+mod shutdown;
+
+pub use shutdown::{Shutdown, signal};
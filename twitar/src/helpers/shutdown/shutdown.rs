@@ -0,0 +1,68 @@
+use std::sync::Arc;
+use tokio::sync::watch;
+
+#[cfg(test)]
+#[path = "./shutdown.test.rs"]
+mod shutdown_test;
+
+
+/// Tells the server and the work it started that the process is stopping.
+/// Cloned into every request, so long running controllers can stop picking up new work
+#[derive(Debug, Clone)]
+pub struct Shutdown {
+    sender: Arc<watch::Sender<bool>>,
+    receiver: watch::Receiver<bool>,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        let (sender, receiver) = watch::channel(false);
+        Self { sender: Arc::new(sender), receiver }
+    }
+
+    pub fn trigger(&self) {
+        self.sender.send_replace(true);
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        *self.receiver.borrow()
+    }
+
+    /// Resolves once `trigger` has been called, straight away when it already was
+    pub async fn triggered(mut self) {
+        while !*self.receiver.borrow_and_update() {
+            if self.receiver.changed().await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+
+/// Resolves on SIGINT (ctrl-c) or, on unix, SIGTERM
+pub async fn signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c().await.expect("Failed to listen for ctrl-c");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to listen for SIGTERM")
+            .recv().await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
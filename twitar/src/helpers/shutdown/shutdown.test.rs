@@ -0,0 +1,23 @@
+#[cfg(test)]
+mod test_shutdown {
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    use crate::helpers::shutdown::Shutdown;
+
+    #[tokio::test]
+    async fn every_clone_sees_the_trigger() {
+        let shutdown = Shutdown::new();
+        let waiting = tokio::spawn(shutdown.clone().triggered());
+
+        assert!(!shutdown.is_triggered());
+        assert!(timeout(Duration::from_millis(20), shutdown.clone().triggered()).await.is_err());
+
+        shutdown.clone().trigger();
+
+        assert!(shutdown.is_triggered());
+        assert!(timeout(Duration::from_millis(100), waiting).await.is_ok());
+        // late listeners resolve straight away
+        assert!(timeout(Duration::from_millis(20), shutdown.triggered()).await.is_ok());
+    }
+}
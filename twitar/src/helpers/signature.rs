@@ -62,6 +62,26 @@ impl std::fmt::Display for SignedParams {
     }
 }
 
+impl SignedParams {
+    /// Reads the params back from the value of an `Authorization: OAuth ...` header (with or without the `OAuth` prefix)
+    pub fn parse(header: &str) -> Self {
+        let header = header.strip_prefix("OAuth ").unwrap_or(header);
+
+        let params = header.split(',').filter_map(|param| {
+            let (k, v) = param.trim().split_once('=')?;
+            let k = urlencoding::decode(k).ok()?;
+            let v = urlencoding::decode(v.trim_matches('"')).ok()?;
+            Some((k.to_string(), v.to_string()))
+        }).collect();
+
+        Self { params }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.params.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+}
+
 
 #[derive(Debug)]
 pub struct OAuth {
@@ -0,0 +1,3 @@
+mod telemetry;
+
+pub use telemetry::{init, is_secret, JsonFields, JsonFormat, LogFormat, RedactingVisitor, TextFields, REDACTED};
@@ -0,0 +1,174 @@
+use std::fmt;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use tracing::{field::{Field, Visit}, Event, Subscriber};
+use tracing_subscriber::{EnvFilter, registry::LookupSpan};
+use tracing_subscriber::field::RecordFields;
+use tracing_subscriber::fmt::{format::Writer, FmtContext, FormatEvent, FormatFields, FormattedFields};
+
+#[cfg(test)]
+#[path = "./telemetry.test.rs"]
+mod telemetry_test;
+
+
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Field names whose values are credentials. Any field ending in `_token` or `_secret` is one too
+const SECRET_FIELDS: [&str; 10] = [
+    "token", "secret", "password", "authorization", "pkce", "code", "code_verifier", "oauth_verifier", "oauth_signature", "credentials",
+];
+
+
+/// How log lines are written: `text` for people, `json` for the log collector in production
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+
+pub fn is_secret(name: &str) -> bool {
+    let name = name.to_lowercase();
+    SECRET_FIELDS.contains(&name.as_str()) || name.ends_with("_token") || name.ends_with("_secret")
+}
+
+/// Collects the fields of an event or a span, with the value of every secret field replaced
+#[derive(Debug, Default)]
+pub struct RedactingVisitor(pub Map<String, Value>);
+
+impl RedactingVisitor {
+    fn insert(&mut self, field: &Field, value: Value) {
+        let value = match is_secret(field.name()) {
+            true => Value::from(REDACTED),
+            false => value,
+        };
+
+        self.0.insert(field.name().to_string(), value);
+    }
+}
+
+impl Visit for RedactingVisitor {
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.insert(field, format!("{:?}", value).into());
+    }
+}
+
+
+/// Writes fields as `message key=value ...`
+#[derive(Debug, Default)]
+pub struct TextFields;
+
+impl<'writer> FormatFields<'writer> for TextFields {
+    fn format_fields<R: RecordFields>(&self, mut writer: Writer<'writer>, fields: R) -> fmt::Result {
+        let mut visitor = RedactingVisitor::default();
+        fields.record(&mut visitor);
+
+        let message = visitor.0.remove("message");
+        let mut parts = message.iter().map(display).collect::<Vec<_>>();
+        parts.extend(visitor.0.iter().map(|(k, v)| format!("{}={}", k, display(v))));
+
+        write!(writer, "{}", parts.join(" "))
+    }
+}
+
+/// Writes fields as a JSON object, which `JsonFormat` merges into the line
+#[derive(Debug, Default)]
+pub struct JsonFields;
+
+impl<'writer> FormatFields<'writer> for JsonFields {
+    fn format_fields<R: RecordFields>(&self, mut writer: Writer<'writer>, fields: R) -> fmt::Result {
+        let mut visitor = RedactingVisitor::default();
+        fields.record(&mut visitor);
+
+        write!(writer, "{}", Value::Object(visitor.0))
+    }
+
+    fn add_fields(&self, current: &'writer mut FormattedFields<Self>, fields: &tracing::span::Record<'_>) -> fmt::Result {
+        let mut visitor = RedactingVisitor(parse_object(&current.fields));
+        fields.record(&mut visitor);
+
+        current.fields = Value::Object(visitor.0).to_string();
+        Ok(())
+    }
+}
+
+/// One JSON object per line, with the fields of the spans the event happened in (e.g. `request_id`)
+#[derive(Debug, Default)]
+pub struct JsonFormat;
+
+impl<S> FormatEvent<S, JsonFields> for JsonFormat where S: Subscriber + for<'a> LookupSpan<'a> {
+    fn format_event(&self, ctx: &FmtContext<'_, S, JsonFields>, mut writer: Writer<'_>, event: &Event<'_>) -> fmt::Result {
+        let metadata = event.metadata();
+
+        let mut line = Map::new();
+        line.insert("timestamp".into(), chrono::Utc::now().to_rfc3339().into());
+        line.insert("level".into(), metadata.level().as_str().into());
+        line.insert("target".into(), metadata.target().into());
+
+        if let Some(scope) = ctx.event_scope() {
+            let mut spans = vec![];
+
+            // from the outermost span, so the innermost one wins when they share a field
+            for span in scope.from_root() {
+                if let Some(fields) = span.extensions().get::<FormattedFields<JsonFields>>() {
+                    line.extend(parse_object(&fields.fields));
+                }
+
+                spans.push(Value::from(span.name()));
+            }
+
+            line.insert("spans".into(), Value::Array(spans));
+        }
+
+        let mut visitor = RedactingVisitor::default();
+        event.record(&mut visitor);
+        line.extend(visitor.0);
+
+        writeln!(writer, "{}", Value::Object(line))
+    }
+}
+
+fn parse_object(fields: &str) -> Map<String, Value> {
+    match serde_json::from_str(fields) {
+        Ok(Value::Object(map)) => map,
+        _ => Map::new(),
+    }
+}
+
+fn display(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+
+/// Installs the global subscriber. `RUST_LOG` takes precedence over `level`, e.g. `RUST_LOG=twitar=debug`
+pub fn init(format: LogFormat, level: &str) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(level));
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+
+    // a subscriber may already be installed, e.g. by another test
+    let _ = match format {
+        LogFormat::Text => builder.fmt_fields(TextFields).try_init(),
+        LogFormat::Json => builder.fmt_fields(JsonFields).event_format(JsonFormat).try_init(),
+    };
+}
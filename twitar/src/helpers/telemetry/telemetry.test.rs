@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod test_telemetry {
+    use std::{io, sync::{Arc, Mutex}};
+    use serde_json::Value;
+    use tracing_subscriber::fmt::MakeWriter;
+
+    use crate::helpers::telemetry::{is_secret, JsonFields, JsonFormat, TextFields, REDACTED};
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Buffer {
+        fn contents(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    impl io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Buffer {
+        type Writer = Self;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn recognises_secret_fields() {
+        assert!(is_secret("access_token"));
+        assert!(is_secret("oauth_token_secret"));
+        assert!(is_secret("Authorization"));
+        assert!(!is_secret("user_id"));
+        assert!(!is_secret("tokens_left"));
+    }
+
+    #[test]
+    fn text_lines_redact_secrets() {
+        let buffer = Buffer::default();
+        let subscriber = tracing_subscriber::fmt().with_writer(buffer.clone()).with_ansi(false)
+            .fmt_fields(TextFields).finish();
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(access_token = "live-token", user_id = 4, "tokens refreshed");
+        });
+
+        let line = buffer.contents();
+        assert!(line.contains("tokens refreshed"));
+        assert!(line.contains("user_id=4"));
+        assert!(line.contains(&format!("access_token={}", REDACTED)));
+        assert!(!line.contains("live-token"));
+    }
+
+    #[test]
+    fn json_lines_carry_the_span_fields() {
+        let buffer = Buffer::default();
+        let subscriber = tracing_subscriber::fmt().with_writer(buffer.clone())
+            .fmt_fields(JsonFields).event_format(JsonFormat).finish();
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("request", request_id = "req-1", oauth_token = "live-token");
+            let _guard = span.enter();
+
+            tracing::warn!(status = 503, "draining");
+        });
+
+        let line: Value = serde_json::from_str(buffer.contents().trim()).unwrap();
+        assert_eq!(line["level"], "WARN");
+        assert_eq!(line["message"], "draining");
+        assert_eq!(line["status"], 503);
+        assert_eq!(line["request_id"], "req-1");
+        assert_eq!(line["oauth_token"], REDACTED);
+        assert_eq!(line["spans"][0], "request");
+    }
+}
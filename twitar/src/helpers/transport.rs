@@ -0,0 +1,7 @@
+mod transport;
+mod memory;
+mod fixtures;
+
+pub use transport::{Transport, HttpClient, HyperTransport};
+pub use memory::{MemoryTransport, MockResponse, RecordedRequest};
+pub use fixtures::{FixtureMode, RecordTransport, ReplayTransport, Interaction, REDACTED};
@@ -0,0 +1,306 @@
+use std::{collections::BTreeMap, fs, path::{Path, PathBuf}, sync::{Arc, Mutex}};
+use anyhow::Context;
+use async_trait::async_trait;
+use http::{HeaderMap, Method, StatusCode};
+use hyper::{Body, Request, Response};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::helpers::{response::TResult, transport::{HttpClient, HyperTransport, Transport}};
+
+#[cfg(test)]
+#[path = "./fixtures.test.rs"]
+mod fixtures_test;
+
+
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Headers that carry credentials, their values are never written to a fixture
+const SENSITIVE_HEADERS: [&str; 3] = ["authorization", "cookie", "set-cookie"];
+
+/// Headers that describe the body as it was sent, which no longer hold once it is redacted
+const SKIPPED_HEADERS: [&str; 3] = ["content-length", "transfer-encoding", "connection"];
+
+/// Query, form and JSON keys that carry credentials. In JSON only their string values are, e.g. the v1.1 error
+/// `{"code": 89}` is kept so that it still parses on replay
+const SENSITIVE_KEYS: [&str; 11] = [
+    "access_token", "refresh_token", "token", "code", "code_verifier", "client_secret",
+    "oauth_token", "oauth_token_secret", "oauth_verifier", "oauth_signature", "oauth_consumer_key",
+];
+
+
+/// Whether requests to Twitter are sent as they are, recorded to a fixture file, or answered from one
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(tag = "mode", content = "path", rename_all = "lowercase")]
+pub enum FixtureMode {
+    #[default]
+    Off,
+    Record(PathBuf),
+    Replay(PathBuf),
+}
+
+impl FixtureMode {
+    /// `mode` is one of `off`, `record` or `replay`. The last two need the `path` of the fixture file
+    pub fn parse(mode: Option<&str>, path: Option<&str>) -> Result<Self, String> {
+        match (mode.map(|m| m.trim().to_lowercase()).as_deref(), path.filter(|p| !p.trim().is_empty())) {
+            (None | Some("") | Some("off"), _) => Ok(Self::Off),
+            (Some("record"), Some(path)) => Ok(Self::Record(path.into())),
+            (Some("replay"), Some(path)) => Ok(Self::Replay(path.into())),
+            (Some("record" | "replay"), None) => Err("a fixture path is required to record or replay".into()),
+            (Some(other), _) => Err(format!("{} is not a fixture mode. Use either `off`|`record`|`replay`", other)),
+        }
+    }
+
+    /// The client requests to Twitter should go through
+    pub fn client(&self) -> TResult<HttpClient> {
+        match self {
+            Self::Off => Ok(HyperTransport::client()),
+            Self::Record(path) => Ok(Arc::new(RecordTransport::new(HyperTransport::client(), path))),
+            Self::Replay(path) => Ok(Arc::new(ReplayTransport::load(path)?)),
+        }
+    }
+}
+
+
+fn is_sensitive(key: &str) -> bool {
+    SENSITIVE_KEYS.contains(&key.to_lowercase().as_str())
+}
+
+fn redact_pairs(pairs: impl Iterator<Item = (String, String)>) -> Vec<(String, String)> {
+    pairs.map(|(k, v)| match is_sensitive(&k) {
+        true => (k, REDACTED.to_string()),
+        false => (k, v),
+    }).collect()
+}
+
+/// The pairs of a query string with credentials redacted, sorted so the order they were sent in does not matter
+pub fn normalize_query(query: Option<&str>) -> Vec<(String, String)> {
+    let pairs = url::form_urlencoded::parse(query.unwrap_or_default().as_bytes()).into_owned();
+    let mut pairs = redact_pairs(pairs);
+    pairs.sort();
+    pairs
+}
+
+fn redact_json(value: &mut Value) {
+    match value {
+        Value::Object(map) => map.iter_mut().for_each(|(k, v)| match is_sensitive(k) && v.is_string() {
+            true => *v = json!(REDACTED),
+            false => redact_json(v),
+        }),
+        Value::Array(values) => values.iter_mut().for_each(redact_json),
+        _ => {}
+    }
+}
+
+/// Redacts the credentials of JSON and urlencoded bodies (OAuth1 tokens come back urlencoded), other bodies are kept
+pub fn redact_body(body: &[u8]) -> String {
+    if let Ok(mut value) = serde_json::from_slice::<Value>(body) {
+        redact_json(&mut value);
+        return value.to_string();
+    }
+
+    let body = String::from_utf8_lossy(body).to_string();
+    let is_form = !body.is_empty() && !body.contains(char::is_whitespace) && body.split('&').all(|pair| pair.contains('='));
+
+    match is_form {
+        true => {
+            let pairs = redact_pairs(url::form_urlencoded::parse(body.as_bytes()).into_owned());
+            url::form_urlencoded::Serializer::new(String::new()).extend_pairs(pairs).finish()
+        }
+        false => body,
+    }
+}
+
+fn redact_headers(headers: &HeaderMap) -> BTreeMap<String, String> {
+    headers.iter().filter(|(k, _)| !SKIPPED_HEADERS.contains(&k.as_str())).map(|(k, v)| {
+        let value = match SENSITIVE_HEADERS.contains(&k.as_str()) {
+            true => REDACTED.to_string(),
+            false => v.to_str().unwrap_or_default().to_string(),
+        };
+
+        (k.to_string(), value)
+    }).collect()
+}
+
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FixtureRequest {
+    pub method: String,
+    pub path: String,
+    pub query: Vec<(String, String)>,
+    pub headers: BTreeMap<String, String>,
+    pub body: String,
+}
+
+impl FixtureRequest {
+    /// Requests are matched on their method, path and normalized query
+    fn matches(&self, method: &Method, path: &str, query: &[(String, String)]) -> bool {
+        self.method == method.as_str() && self.path == path && self.query == query
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FixtureResponse {
+    pub status: u16,
+    pub headers: BTreeMap<String, String>,
+    pub body: String,
+}
+
+impl FixtureResponse {
+    fn to_response(&self) -> TResult<Response<Body>> {
+        let mut response = Response::builder().status(StatusCode::from_u16(self.status)?);
+
+        for (k, v) in &self.headers {
+            response = response.header(k.as_str(), v.as_str());
+        }
+
+        Ok(response.body(Body::from(self.body.clone())).context("Invalid recorded response")?)
+    }
+}
+
+/// A request to Twitter and the response it got, with every credential redacted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interaction {
+    pub request: FixtureRequest,
+    pub response: FixtureResponse,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Fixture {
+    interactions: Vec<Interaction>,
+}
+
+
+/// Sends requests with another transport and writes every exchange to a fixture file
+#[derive(Debug, Clone)]
+pub struct RecordTransport {
+    inner: HttpClient,
+    path: PathBuf,
+    fixture: Arc<Mutex<Fixture>>,
+    /// Held while the file is written, so a slower write never replaces the file with fewer interactions
+    writing: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl RecordTransport {
+    /// Starts a new fixture at `path`, replacing the file when it already exists
+    pub fn new(inner: HttpClient, path: impl AsRef<Path>) -> Self {
+        Self {
+            inner,
+            path: path.as_ref().to_path_buf(),
+            fixture: Arc::new(Mutex::new(Fixture::default())),
+            writing: Arc::new(tokio::sync::Mutex::new(())),
+        }
+    }
+
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.fixture.lock().unwrap().interactions.clone()
+    }
+
+    /// Writes the file with every interaction recorded so far, the calls recorded meanwhile do not wait for the disk
+    async fn save(&self, interaction: Interaction) -> TResult<()> {
+        self.fixture.lock().unwrap().interactions.push(interaction);
+
+        let _writing = self.writing.lock().await;
+        let content = serde_json::to_string_pretty(&*self.fixture.lock().unwrap())?;
+
+        if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(dir).await.context("Unable to create the fixture directory")?;
+        }
+
+        tokio::fs::write(&self.path, content).await.with_context(|| format!("Unable to write the fixture {}", self.path.display()))?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Transport for RecordTransport {
+    async fn send(&self, request: Request<Body>) -> TResult<Response<Body>> {
+        let (parts, body) = request.into_parts();
+        let body = hyper::body::to_bytes(body).await?;
+
+        let recorded_request = FixtureRequest {
+            method: parts.method.to_string(),
+            path: parts.uri.path().to_string(),
+            query: normalize_query(parts.uri.query()),
+            headers: redact_headers(&parts.headers),
+            body: redact_body(&body),
+        };
+
+        let response = self.inner.send(Request::from_parts(parts, Body::from(body))).await?;
+
+        let (parts, body) = response.into_parts();
+        let body = hyper::body::to_bytes(body).await?;
+
+        let recorded_response = FixtureResponse {
+            status: parts.status.as_u16(),
+            headers: redact_headers(&parts.headers),
+            body: redact_body(&body),
+        };
+
+        self.save(Interaction { request: recorded_request, response: recorded_response }).await?;
+
+        // the caller gets the real response, only the fixture is redacted
+        Ok(Response::from_parts(parts, Body::from(body)))
+    }
+}
+
+
+/// Answers requests from a fixture file, without any network.
+/// Matching interactions are used in the order they were recorded, and the last one is repeated
+#[derive(Debug, Clone)]
+pub struct ReplayTransport {
+    interactions: Arc<Mutex<Vec<(Interaction, bool)>>>,
+}
+
+impl ReplayTransport {
+    pub fn load(path: impl AsRef<Path>) -> TResult<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path).with_context(|| format!("Unable to read the fixture {}", path.display()))?;
+        let fixture: Fixture = serde_json::from_str(&content)?;
+
+        Ok(Self::from_interactions(fixture.interactions))
+    }
+
+    pub fn from_interactions(interactions: Vec<Interaction>) -> Self {
+        Self { interactions: Arc::new(Mutex::new(interactions.into_iter().map(|i| (i, false)).collect())) }
+    }
+
+    pub fn client(&self) -> HttpClient {
+        Arc::new(self.clone())
+    }
+}
+
+#[async_trait]
+impl Transport for ReplayTransport {
+    async fn send(&self, request: Request<Body>) -> TResult<Response<Body>> {
+        let method = request.method().clone();
+        let path = request.uri().path().to_string();
+        let query = normalize_query(request.uri().query());
+
+        let mut interactions = self.interactions.lock().unwrap();
+        let mut matching = interactions.iter_mut().filter(|(i, _)| i.request.matches(&method, &path, &query)).collect::<Vec<_>>();
+
+        let interaction = match matching.iter().position(|(_, used)| !used) {
+            Some(index) => {
+                matching[index].1 = true;
+                Some(&matching[index].0)
+            }
+            None => matching.last().map(|(i, _)| i),
+        };
+
+        match interaction {
+            Some(interaction) => interaction.response.to_response(),
+            None => FixtureResponse {
+                status: 404,
+                headers: BTreeMap::from([("content-type".to_string(), "application/json".to_string())]),
+                body: json!({
+                    "type": "about:blank",
+                    "title": "Not Found Error",
+                    "status": 404,
+                    "detail": format!("Nothing was recorded for {} {}", method, path),
+                }).to_string(),
+            }.to_response(),
+        }
+    }
+}
@@ -0,0 +1,112 @@
+#[cfg(test)]
+mod test_fixtures {
+    use std::{env, fs};
+    use http::Method;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use crate::errors::response::TError;
+    use crate::errors::twitter_errors::TwitterApiError;
+    use crate::helpers::response::make_request;
+    use crate::helpers::transport::{FixtureMode, MemoryTransport, MockResponse, RecordTransport, ReplayTransport, REDACTED};
+    use crate::middlewares::request_builder::{RequestBuilder, AuthType};
+
+    fn fixture_path() -> std::path::PathBuf {
+        env::temp_dir().join(format!("twitar-fixtures-{}", Uuid::new_v4())).join("fixture.json")
+    }
+
+    #[test]
+    fn parses_the_fixture_mode() {
+        assert_eq!(FixtureMode::parse(None, None), Ok(FixtureMode::Off));
+        assert_eq!(FixtureMode::parse(Some("Record"), Some("fixtures/a.json")), Ok(FixtureMode::Record("fixtures/a.json".into())));
+        assert!(FixtureMode::parse(Some("replay"), None).is_err());
+        assert!(FixtureMode::parse(Some("rewind"), Some("a.json")).is_err());
+    }
+
+    #[tokio::test]
+    async fn records_redacted_interactions_and_replays_them() {
+        let path = fixture_path();
+        let memory = MemoryTransport::new();
+        memory.respond(Method::POST, "/2/oauth2/token", MockResponse::json(200, json!({"access_token": "leaked-access", "token_type": "bearer"})))
+            .respond(Method::POST, "/oauth/access_token", MockResponse::new(200).with_body("oauth_token=leaked-token&oauth_token_secret=leaked-secret&user_id=1"))
+            .respond(Method::GET, "/2/users/1/tweets", MockResponse::json(200, json!({"data": [{"id": "10", "text": "hello"}]})));
+
+        let recorder = RecordTransport::new(memory.client(), &path);
+        let client = std::sync::Arc::new(recorder.clone());
+
+        let token = RequestBuilder::new(Method::POST, "https://api.twitter.com/2/oauth2/token".into())
+            .with_auth(AuthType::Basic, "client:secret".into())
+            .with_body("grant_type=refresh_token&refresh_token=leaked-refresh", "application/x-www-form-urlencoded");
+        let (_, body) = make_request(token.build_request(), client.clone()).await.unwrap();
+        // the caller still gets the real token
+        assert!(String::from_utf8_lossy(&body).contains("leaked-access"));
+
+        let oauth1 = RequestBuilder::new(Method::POST, "https://api.twitter.com/oauth/access_token".into())
+            .with_query("oauth_token", "leaked-token").with_query("oauth_verifier", "leaked-verifier");
+        make_request(oauth1.build_request(), client.clone()).await.unwrap();
+
+        let tweets = RequestBuilder::new(Method::GET, "https://api.twitter.com/2/users/1/tweets".into())
+            .with_auth(AuthType::Bearer, "leaked-bearer".into())
+            .with_query("max_results", "5").with_query("tweet.fields", "created_at");
+        make_request(tweets.build_request(), client).await.unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("leaked"), "{}", content);
+        assert_eq!(recorder.interactions().len(), 3);
+        assert_eq!(recorder.interactions()[0].request.headers["authorization"], REDACTED);
+
+        let replay = ReplayTransport::load(&path).unwrap();
+
+        // the query is matched regardless of its order and credentials
+        let tweets = RequestBuilder::new(Method::GET, "https://api.twitter.com/2/users/1/tweets".into())
+            .with_auth(AuthType::Bearer, "another-bearer".into())
+            .with_query("tweet.fields", "created_at").with_query("max_results", "5");
+        let (_, body) = make_request(tweets.build_request(), replay.client()).await.unwrap();
+        assert_eq!(serde_json::from_slice::<serde_json::Value>(&body).unwrap()["data"][0]["id"], "10");
+
+        let other = RequestBuilder::new(Method::GET, "https://api.twitter.com/2/users/1/tweets".into()).with_query("max_results", "10");
+        let response = make_request(other.build_request(), replay.client()).await;
+        assert!(matches!(response, Err(TError::TwitterError(_))));
+
+        fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[tokio::test]
+    async fn replays_v1_errors_with_their_code() {
+        let path = fixture_path();
+        let memory = MemoryTransport::new();
+        memory.respond(Method::GET, "/1.1/account/verify_credentials.json",
+            MockResponse::json(401, json!({"errors": [{"code": 89, "message": "Invalid or expired token."}]})));
+
+        let request = || RequestBuilder::new(Method::GET, "https://api.twitter.com/1.1/account/verify_credentials.json".into()).build_request();
+        make_request(request(), std::sync::Arc::new(RecordTransport::new(memory.client(), &path))).await.unwrap_err();
+
+        let replayed = make_request(request(), ReplayTransport::load(&path).unwrap().client()).await;
+        let error = match replayed {
+            Err(TError::TwitterError(error)) => error,
+            other => panic!("expected the Twitter error, got {:?}", other),
+        };
+        assert!(matches!(&error.1, TwitterApiError::V1(errors) if errors.errors[0].code == 89));
+
+        fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[tokio::test]
+    async fn concurrent_recordings_all_reach_the_file() {
+        let path = fixture_path();
+        let memory = MemoryTransport::new();
+        memory.respond(Method::GET, "/2/tweets", MockResponse::json(200, json!({"data": []})));
+
+        let client = std::sync::Arc::new(RecordTransport::new(memory.client(), &path));
+        let requests = (0..10).map(|n| {
+            let request = RequestBuilder::new(Method::GET, "https://api.twitter.com/2/tweets".into()).with_query("ids", &n.to_string());
+            make_request(request.build_request(), client.clone())
+        });
+        futures::future::join_all(requests).await;
+
+        let content: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(content["interactions"].as_array().unwrap().len(), 10);
+
+        fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+}
@@ -0,0 +1,171 @@
+use std::{collections::HashMap, sync::{Arc, Mutex}};
+use async_trait::async_trait;
+use http::{HeaderMap, Method, StatusCode, Uri};
+use hyper::{Body, Request, Response};
+use serde_json::{json, Value};
+
+use crate::helpers::{response::TResult, signature::SignedParams, transport::{HttpClient, Transport}};
+
+#[cfg(test)]
+#[path = "./memory.test.rs"]
+mod memory_test;
+
+
+/// A scripted response of the `MemoryTransport`
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    status: StatusCode,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl MockResponse {
+    pub fn new(status: u16) -> Self {
+        Self {
+            status: StatusCode::from_u16(status).expect("invalid status code"),
+            headers: vec![],
+            body: vec![],
+        }
+    }
+
+    pub fn json(status: u16, body: Value) -> Self {
+        Self::new(status)
+            .with_header("content-type", "application/json")
+            .with_body(body.to_string())
+    }
+
+    pub fn with_header(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((key.into(), value.into()));
+        self
+    }
+
+    pub fn with_body(self, body: impl Into<Vec<u8>>) -> Self {
+        Self { body: body.into(), ..self }
+    }
+
+    fn to_response(&self) -> Response<Body> {
+        let mut response = Response::builder().status(self.status);
+
+        for (k, v) in &self.headers {
+            response = response.header(k.as_str(), v.as_str());
+        }
+
+        response.body(Body::from(self.body.clone())).unwrap()
+    }
+}
+
+
+/// A request the `MemoryTransport` received
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: Method,
+    pub uri: Uri,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+impl RecordedRequest {
+    pub fn path(&self) -> &str {
+        self.uri.path()
+    }
+
+    pub fn query(&self) -> HashMap<String, String> {
+        self.uri.query()
+            .map(|q| url::form_urlencoded::parse(q.as_bytes()).into_owned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn header(&self, key: &str) -> Option<&str> {
+        self.headers.get(key).and_then(|v| v.to_str().ok())
+    }
+
+    pub fn body_str(&self) -> String {
+        String::from_utf8_lossy(&self.body).to_string()
+    }
+
+    /// The parameters of an `Authorization: OAuth ...` header (e.g. `oauth_signature`), decoded
+    pub fn oauth_params(&self) -> HashMap<String, String> {
+        match self.header("authorization").filter(|h| h.starts_with("OAuth ")) {
+            Some(header) => SignedParams::parse(header).params.into_iter().collect(),
+            None => HashMap::new(),
+        }
+    }
+}
+
+
+#[derive(Debug, Default)]
+struct Script {
+    /// Responses for a method and path, used in order. The last one is repeated
+    routes: Vec<(Method, String, Vec<MockResponse>)>,
+    requests: Vec<RecordedRequest>,
+}
+
+
+/// Answers requests with scripted responses and records every request it receives, without any network.
+/// Requests nothing was scripted for get a 404 problem, the way the v2 api answers unknown routes
+#[derive(Debug, Clone, Default)]
+pub struct MemoryTransport {
+    script: Arc<Mutex<Script>>,
+}
+
+impl MemoryTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answers `method` `path` with `response`. Scripting the same route again queues another response
+    pub fn respond(&self, method: Method, path: impl Into<String>, response: MockResponse) -> &Self {
+        let path = path.into();
+        let mut script = self.script.lock().unwrap();
+
+        match script.routes.iter_mut().find(|(m, p, _)| *m == method && *p == path) {
+            Some((_, _, responses)) => responses.push(response),
+            None => script.routes.push((method, path, vec![response])),
+        }
+
+        self
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.script.lock().unwrap().requests.clone()
+    }
+
+    pub fn last_request(&self) -> Option<RecordedRequest> {
+        self.script.lock().unwrap().requests.last().cloned()
+    }
+
+    pub fn client(&self) -> HttpClient {
+        Arc::new(self.clone())
+    }
+}
+
+#[async_trait]
+impl Transport for MemoryTransport {
+    async fn send(&self, request: Request<Body>) -> TResult<Response<Body>> {
+        let (parts, body) = request.into_parts();
+        let body = hyper::body::to_bytes(body).await?.to_vec();
+
+        let request = RecordedRequest { method: parts.method, uri: parts.uri, headers: parts.headers, body };
+        let path = request.path().to_string();
+
+        let mut script = self.script.lock().unwrap();
+
+        let response = script.routes.iter_mut()
+            .find(|(m, p, _)| *m == request.method && *p == path)
+            .map(|(_, _, responses)| match responses.len() {
+                1 => responses[0].clone(),
+                _ => responses.remove(0),
+            });
+
+        script.requests.push(request);
+
+        let response = response.unwrap_or_else(|| MockResponse::json(404, json!({
+            "type": "about:blank",
+            "title": "Not Found Error",
+            "status": 404,
+            "detail": format!("Nothing was scripted for {}", path),
+        })));
+
+        Ok(response.to_response())
+    }
+}
@@ -0,0 +1,78 @@
+#[cfg(test)]
+mod test_memory {
+    use http::{Method, StatusCode};
+    use serde_json::json;
+
+    use crate::errors::response::TError;
+    use crate::helpers::keypair::KeyPair;
+    use crate::helpers::response::make_request;
+    use crate::helpers::signature::{OAuth, OAuthAddons};
+    use crate::helpers::transport::{MemoryTransport, MockResponse};
+    use crate::middlewares::request_builder::{RequestBuilder, AuthType};
+
+    #[tokio::test]
+    async fn replays_scripted_responses_in_order() {
+        let transport = MemoryTransport::new();
+        transport
+            .respond(Method::GET, "/2/users/me", MockResponse::json(200, json!({"data": {"id": "1"}})))
+            .respond(Method::GET, "/2/users/me", MockResponse::new(503));
+
+        let request = || RequestBuilder::new(Method::GET, "https://api.twitter.com/2/users/me".into()).build_request();
+
+        assert!(make_request(request(), transport.client()).await.is_ok());
+        assert!(matches!(make_request(request(), transport.client()).await, Err(TError::BadStatus(StatusCode::SERVICE_UNAVAILABLE))));
+        assert!(matches!(make_request(request(), transport.client()).await, Err(TError::BadStatus(StatusCode::SERVICE_UNAVAILABLE))));
+        assert_eq!(transport.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn unscripted_routes_are_not_found() {
+        let transport = MemoryTransport::new();
+        let request = RequestBuilder::new(Method::GET, "https://api.twitter.com/2/tweets".into()).build_request();
+
+        let response = make_request(request, transport.client()).await;
+
+        assert!(matches!(response, Err(TError::TwitterError(_))));
+    }
+
+    #[tokio::test]
+    async fn records_headers_query_and_body() {
+        let transport = MemoryTransport::new();
+        transport.respond(Method::POST, "/2/users/1/likes", MockResponse::json(200, json!({"data": {"liked": true}})));
+
+        let request = RequestBuilder::new(Method::POST, "https://api.twitter.com/2/users/1/likes".into())
+            .with_query("source", "twitar")
+            .with_auth(AuthType::Bearer, "access".into())
+            .with_json_body(json!({"tweet_id": "20"}))
+            .build_request();
+
+        make_request(request, transport.client()).await.unwrap();
+        let recorded = transport.last_request().unwrap();
+
+        assert_eq!(recorded.path(), "/2/users/1/likes");
+        assert_eq!(recorded.query()["source"], "twitar");
+        assert_eq!(recorded.header("authorization"), Some("Bearer access"));
+        assert_eq!(recorded.body_str(), r#"{"tweet_id":"20"}"#);
+    }
+
+    #[tokio::test]
+    async fn exposes_the_oauth_signature() {
+        let transport = MemoryTransport::new();
+        transport.respond(Method::POST, "/oauth/request_token", MockResponse::new(200));
+
+        let consumer = KeyPair::new("consumer_key".into(), "consumer_secret".into());
+        let target = "https://api.twitter.com/oauth/request_token";
+        let signed = OAuth::new(consumer, None, OAuthAddons::None, Method::POST).generate_signature(target.into());
+
+        let request = RequestBuilder::new(Method::POST, target.into())
+            .with_auth(AuthType::OAuth, signed.to_string())
+            .build_request();
+
+        make_request(request, transport.client()).await.unwrap();
+        let oauth = transport.last_request().unwrap().oauth_params();
+
+        assert_eq!(oauth["oauth_consumer_key"], "consumer_key");
+        assert_eq!(oauth["oauth_signature_method"], "HMAC-SHA1");
+        assert!(!oauth["oauth_signature"].is_empty());
+    }
+}
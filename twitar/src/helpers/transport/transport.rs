@@ -0,0 +1,44 @@
+use std::{fmt::Debug, sync::Arc};
+use async_trait::async_trait;
+use hyper::{Body, Client, Request, Response, client::HttpConnector};
+use hyper_tls::HttpsConnector;
+
+use crate::helpers::response::TResult;
+
+
+/// Sends the requests made to Twitter. `make_request` only knows about this trait,
+/// so the real api can be swapped for scripted responses in tests and local development
+#[async_trait]
+pub trait Transport: Send + Sync + Debug {
+    async fn send(&self, request: Request<Body>) -> TResult<Response<Body>>;
+}
+
+pub type HttpClient = Arc<dyn Transport>;
+
+
+/// Sends requests over the network with hyper (and TLS)
+#[derive(Debug, Clone)]
+pub struct HyperTransport(Client<HttpsConnector<HttpConnector>>);
+
+impl HyperTransport {
+    pub fn new() -> Self {
+        Self(Client::builder().build::<_, Body>(HttpsConnector::new()))
+    }
+
+    pub fn client() -> HttpClient {
+        Arc::new(Self::new())
+    }
+}
+
+impl Default for HyperTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Transport for HyperTransport {
+    async fn send(&self, request: Request<Body>) -> TResult<Response<Body>> {
+        Ok(self.0.request(request).await?)
+    }
+}
@@ -1,11 +1,16 @@
 use std::collections::HashMap;
+use serde::de::DeserializeOwned;
 use serde_json::Value;
 
 use crate::{
-    helpers::response::{TResult, THeaders}, 
-    errors::{twitter_errors::TwitterResponseError, response::AppError}
+    helpers::response::{TResult, THeaders, TwitterResponse}, 
+    errors::{twitter_errors::{PartialError, TwitterApiError}, response::{AppError, TError}}
 };
 
+#[cfg(test)]
+#[path = "./handle_request.test.rs"]
+mod handle_request_test;
+
 
 #[derive(Debug)]
 pub struct V2Tokens {
@@ -40,27 +45,52 @@ impl Interceptor {
         match res {
             Ok(resp) => {
                 let (_header, body) = resp;
-                let response: Value = serde_json::from_slice(&body).unwrap();
 
-                if response["errors"] != Value::Null {
-                    let err: TwitterResponseError = serde_json::from_slice(&body).unwrap();
-                    let detail = err.errors[0].get("detail").unwrap();
-                    obj.insert("detail".into(), detail.to_string());
-                    return Err(AppError(obj, 400));
-                }
+                let response: Value = match serde_json::from_slice(&body) {
+                    Ok(response) => response,
+                    Err(_) => {
+                        obj.insert("detail".into(), "Twitter returned an unexpected response".into());
+                        return Err(AppError(obj, 502));
+                    }
+                };
 
-                let data: Value = serde_json::from_slice(&body).unwrap();
-                return Ok(data);
+                // errors next to data only concern some of the requested items (partial success),
+                // the request only failed when there is nothing else in the body
+                if response.get("data").is_none() {
+                    if let Some(errors) = response.get("errors") {
+                        let partial: Vec<PartialError> = serde_json::from_value(errors.clone()).unwrap_or_default();
+                        let detail = match partial.is_empty() {
+                            true => errors.to_string(),
+                            false => partial.iter().map(|e| e.to_string()).collect::<Vec<_>>().join(", "),
+                        };
 
+                        obj.insert("detail".into(), detail);
+                        return Err(AppError(obj, 400));
+                    }
+                }
+
+                Ok(response)
             }
             Err(e) => {
-                println!("THE ERROR OBTAINED!!!!!!!!!!!!!!!!!!! {:#?}", e);
-                obj.insert("detail".into(), "Internal Server Error".into());
-                return Err(AppError(obj, 500));
+                obj.insert("detail".into(), e.to_string());
+                Err(AppError(obj, e.status().as_u16()))
             }
         }
     }
 
+    /// Parses a v2 response, keeping the items that were returned when only some of them failed.
+    /// It is an error only when none of the requested items could be returned.
+    pub fn partial<T: DeserializeOwned>(res: TResult<(THeaders, Vec<u8>)>) -> TResult<TwitterResponse<T>> {
+        let (headers, body) = res?;
+        let response: TwitterResponse<T> = serde_json::from_slice(&body)?;
+
+        if response.data.is_none() && !response.errors.is_empty() {
+            return Err(TError::twitter(headers, TwitterApiError::Partial { errors: response.errors }))
+        }
+
+        Ok(response)
+    }
+
     pub fn v2_tokens(map: Result<Value, AppError>) -> Option<V2Tokens> {
         if let Ok(body) = map {
             let map: HashMap<String, Value> = serde_json::from_value(body).unwrap();
@@ -0,0 +1,52 @@
+mod test_interceptor {
+    use serde_json::{json, Value};
+
+    use crate::errors::{response::TError, twitter_errors::TwitterApiError};
+    use crate::helpers::response::THeaders;
+    use crate::interceptors::handle_request::Interceptor;
+
+    fn ok(body: Value) -> crate::helpers::response::TResult<(THeaders, Vec<u8>)> {
+        Ok((THeaders::new(), serde_json::to_vec(&body).unwrap()))
+    }
+
+    fn lookup_of_two_with_one_missing() -> Value {
+        json!({
+            "data": [{"id": "1", "text": "first"}],
+            "errors": [{
+                "value": "2", "detail": "Could not find tweet with ids: [2].", "title": "Not Found Error",
+                "resource_type": "tweet", "parameter": "ids", "resource_id": "2",
+                "type": "https://api.twitter.com/2/problems/resource-not-found"
+            }]
+        })
+    }
+
+    #[test]
+    fn keeps_data_of_partial_successes() {
+        let response = Interceptor::partial::<Vec<Value>>(ok(lookup_of_two_with_one_missing())).unwrap();
+
+        assert!(response.is_partial());
+        assert_eq!(response.data.as_ref().unwrap().len(), 1);
+        assert_eq!(response.failed_ids(), vec!["2"]);
+
+        assert!(Interceptor::intercept(ok(lookup_of_two_with_one_missing())).is_ok());
+    }
+
+    #[test]
+    fn fails_when_nothing_was_returned() {
+        let body = json!({"errors": [{"title": "Not Found Error", "type": "about:blank", "detail": "Could not find tweet"}]});
+
+        let err = Interceptor::partial::<Vec<Value>>(ok(body.clone())).unwrap_err();
+        assert!(matches!(err, TError::TwitterError(e) if matches!(e.1, TwitterApiError::Partial { .. })));
+
+        let err = Interceptor::intercept(ok(body)).unwrap_err();
+        assert_eq!(err.1, 400);
+        assert_eq!(err.0.get("detail").unwrap(), "Could not find tweet");
+    }
+
+    #[test]
+    fn keeps_the_status_of_failed_requests() {
+        let err = Interceptor::intercept(Err(TError::RateLimit(None))).unwrap_err();
+
+        assert_eq!(err.1, 429);
+    }
+}
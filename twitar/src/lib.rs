@@ -5,9 +5,9 @@ pub mod helpers;
 pub mod routes;
 pub mod middlewares;
 pub mod interceptors;
-pub mod configurations;
 pub mod base_repository;
 pub mod settings;
+pub mod models;
 
-// #[cfg(feature="test")]
-// pub mod stubs;
\ No newline at end of file
+#[cfg(any(test, feature = "test"))]
+pub mod stubs;
\ No newline at end of file
@@ -1,7 +1,65 @@
-use twitar::startup::server::server;
+use sqlx::{migrate::Migrate, PgPool, Pool};
+use twitar::base_repository::repository::Storage;
+use twitar::settings::config::{get_database_configuration, get_storage};
+use twitar::startup::{migrations::{self, Schema}, server::server};
 
+const MIGRATE_USAGE: &str = "usage: twitar migrate status|up|down [steps]";
 
+
+/// `twitar` starts the server, `twitar migrate ...` manages the database schema
 #[tokio::main]
 async fn main() {
-    server().await;
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    match args.first().map(String::as_str) {
+        Some("migrate") => migrate(&args[1..]).await,
+        _ => server().await,
+    }
+}
+
+
+/// Works on the schema of `app.storage`, Postgres unless it says otherwise
+async fn migrate(args: &[String]) {
+    let db = get_database_configuration().unwrap_or_else(|e| exit(e.to_string()));
+
+    match get_storage().unwrap_or_else(|e| exit(e.to_string())) {
+        Storage::Postgres => {
+            let pool = PgPool::connect_with(db.with_db()).await.unwrap_or_else(|e| exit(format!("Unable to connect to the database: {}", e)));
+            run_migrations(&pool, args).await
+        }
+        #[cfg(feature = "sqlite")]
+        Storage::Sqlite => {
+            let pool = sqlx::SqlitePool::connect_with(db.sqlite().create_if_missing(true)).await
+                .unwrap_or_else(|e| exit(format!("Unable to open the sqlite file: {}", e)));
+            run_migrations(&pool, args).await
+        }
+        Storage::Memory => exit("The memory storage has no schema to migrate".into()),
+    }
+}
+
+async fn run_migrations<DB: Schema>(pool: &Pool<DB>, args: &[String])
+where DB::Connection: Migrate {
+    let result = match (args.first().map(String::as_str), args.get(1)) {
+        (Some("status"), None) => Ok(()),
+        (Some("up"), None) => migrations::up(pool).await,
+        (Some("down"), steps) => {
+            let steps = steps.map_or(Ok(1), |s| s.parse::<usize>()).unwrap_or_else(|_| exit(MIGRATE_USAGE.into()));
+            migrations::down(pool, steps).await.map(|reverted| reverted.iter().for_each(|v| println!("reverted {}", v)))
+        }
+        _ => exit(MIGRATE_USAGE.into()),
+    };
+
+    if let Err(e) = result {
+        exit(e.to_string());
+    }
+
+    match migrations::status(pool).await {
+        Ok(statuses) => statuses.iter().for_each(|s| println!("{}", s)),
+        Err(e) => exit(e.to_string()),
+    }
+}
+
+fn exit(message: String) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1)
 }
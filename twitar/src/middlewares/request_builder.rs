@@ -2,7 +2,9 @@ use std::{fmt};
 use http::header::{CONTENT_TYPE};
 use hyper::{Body, Request, Method};
 
-use crate::helpers::keyval::KeyVal;
+use twitar_macro::Validate;
+
+use crate::helpers::{keyval::KeyVal, query::V2Query, response::TResult};
 
 pub struct RequestBuilder {
     base_url: String,
@@ -49,6 +51,26 @@ impl RequestBuilder {
         }
     }
 
+    /// Appends the parameters of a v2 query once it is valid
+    pub fn with_v2_query(self, v2_query: &V2Query) -> TResult<Self> {
+        v2_query.validate()?;
+
+        let v2_query = v2_query.to_query_string();
+        if v2_query.is_empty() {
+            return Ok(self)
+        }
+
+        let query = match &self.query {
+            Some(query) => format!("{}&{}", query, v2_query),
+            None => v2_query,
+        };
+
+        Ok(Self {
+            query: Some(query),
+            ..self
+        })
+    }
+
     pub fn add_query_params(self, query_dict: KeyVal) -> Self {
         let query_str = query_dict
             .iter().map(|(k, v)| format!("{}={}", k, v))
@@ -0,0 +1,13 @@
+pub mod tweet;
+pub mod user;
+pub mod media;
+pub mod poll;
+pub mod place;
+pub mod includes;
+
+pub use tweet::{Tweet, ReferencedTweet, ReferenceType};
+pub use user::User;
+pub use media::{Media, MediaType};
+pub use poll::Poll;
+pub use place::Place;
+pub use includes::{Includes, Meta};
@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+
+use crate::models::{Tweet, User, Media, Poll, Place};
+
+#[cfg(test)]
+#[path = "./includes.test.rs"]
+mod includes_test;
+
+
+/// Objects referenced by `data` and pulled in through `expansions`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Includes {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tweets: Vec<Tweet>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub users: Vec<User>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub media: Vec<Media>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub polls: Vec<Poll>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub places: Vec<Place>,
+    /// Fields this model does not know about yet
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+impl Includes {
+    pub fn media_by_key(&self, key: &str) -> Option<&Media> {
+        self.media.iter().find(|m| m.media_key == key)
+    }
+
+    pub fn user_by_id(&self, id: &str) -> Option<&User> {
+        self.users.iter().find(|u| u.id == id)
+    }
+}
+
+
+/// Pagination details of a list response
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Meta {
+    #[serde(default)]
+    pub result_count: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub newest_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oldest_id: Option<String>,
+    /// Fields this model does not know about yet
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
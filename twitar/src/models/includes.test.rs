@@ -0,0 +1,34 @@
+#[cfg(test)]
+mod test_includes {
+    use serde_json::json;
+    use crate::models::Includes;
+    use crate::models::media::MediaType;
+
+    #[test]
+    fn deserializes_every_expansion() {
+        let includes: Includes = serde_json::from_value(json!({
+            "users": [{"id": "2244994945", "name": "Twitter Dev", "username": "TwitterDev",
+                "public_metrics": {"followers_count": 10, "following_count": 2, "tweet_count": 3, "listed_count": 1}}],
+            "media": [{"media_key": "3_1", "type": "photo", "url": "https://pbs.twimg.com/media/a.jpg", "width": 100, "height": 50}],
+            "polls": [{"id": "1199786642468413448", "voting_status": "closed", "end_datetime": "2019-11-28T20:26:41.000Z",
+                "options": [{"position": 1, "label": "yes", "votes": 4}, {"position": 2, "label": "no", "votes": 1}]}],
+            "places": [{"id": "01a9a39529b27f36", "full_name": "Manhattan, NY", "country_code": "US"}],
+            "topics": [{"id": "1"}]
+        })).unwrap();
+
+        assert_eq!(includes.user_by_id("2244994945").unwrap().public_metrics.as_ref().unwrap().followers_count, 10);
+        assert_eq!(includes.media_by_key("3_1").unwrap().kind, MediaType::Photo);
+        assert_eq!(includes.polls[0].options[1].votes, 1);
+        assert_eq!(includes.places[0].country_code.as_deref(), Some("US"));
+        assert!(includes.extra.contains_key("topics"));
+    }
+
+    #[test]
+    fn unknown_media_types_do_not_fail() {
+        let includes: Includes = serde_json::from_value(json!({
+            "media": [{"media_key": "16_1", "type": "hologram"}]
+        })).unwrap();
+
+        assert_eq!(includes.media[0].kind, MediaType::Unknown);
+    }
+}
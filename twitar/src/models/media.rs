@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaType {
+    Photo,
+    Video,
+    AnimatedGif,
+    #[serde(other)]
+    Unknown,
+}
+
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Variant {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bit_rate: Option<u64>,
+    pub content_type: String,
+    pub url: String,
+}
+
+
+/// Media attached to a tweet, returned in `includes` when `expansions=attachments.media_keys`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Media {
+    pub media_key: String,
+    #[serde(rename = "type")]
+    pub kind: MediaType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview_image_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alt_text: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<Variant>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_metrics: Option<HashMap<String, Value>>,
+    /// Fields this model does not know about yet
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
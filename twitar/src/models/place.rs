@@ -0,0 +1,26 @@
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+
+
+/// A place tagged in a tweet, returned in `includes` when `expansions=geo.place_id`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Place {
+    pub id: String,
+    pub full_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country_code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub place_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contained_within: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub geo: Option<Value>,
+    /// Fields this model does not know about yet
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
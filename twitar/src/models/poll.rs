@@ -0,0 +1,30 @@
+use std::collections::HashMap;
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PollOption {
+    pub position: u32,
+    pub label: String,
+    #[serde(default)]
+    pub votes: u64,
+}
+
+
+/// A poll attached to a tweet, returned in `includes` when `expansions=attachments.poll_ids`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Poll {
+    pub id: String,
+    pub options: Vec<PollOption>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_minutes: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_datetime: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub voting_status: Option<String>,
+    /// Fields this model does not know about yet
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
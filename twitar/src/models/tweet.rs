@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+
+#[cfg(test)]
+#[path = "./tweet.test.rs"]
+mod tweet_test;
+
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReferenceType {
+    Retweeted,
+    Quoted,
+    RepliedTo,
+    #[serde(other)]
+    Unknown,
+}
+
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReferencedTweet {
+    #[serde(rename = "type")]
+    pub kind: ReferenceType,
+    pub id: String,
+}
+
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Attachments {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub media_keys: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub poll_ids: Vec<String>,
+}
+
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Geo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub place_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coordinates: Option<Value>,
+}
+
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct TweetMetrics {
+    #[serde(default)]
+    pub retweet_count: u64,
+    #[serde(default)]
+    pub reply_count: u64,
+    #[serde(default)]
+    pub like_count: u64,
+    #[serde(default)]
+    pub quote_count: u64,
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+
+/// A v2 tweet. Only `id` and `text` are always returned, everything else depends on the requested `tweet.fields`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Tweet {
+    pub id: String,
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conversation_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub in_reply_to_user_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub referenced_tweets: Vec<ReferencedTweet>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attachments: Option<Attachments>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub geo: Option<Geo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_metrics: Option<TweetMetrics>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub possibly_sensitive: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entities: Option<Value>,
+    /// Fields this model does not know about yet
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+impl Tweet {
+    /// Whether this tweet is a retweet of another. Falls back to the "RT" prefix when `referenced_tweets` was not requested
+    pub fn is_retweet(&self) -> bool {
+        if self.referenced_tweets.is_empty() {
+            return self.text.starts_with("RT @");
+        }
+
+        self.referenced_tweets.iter().any(|r| r.kind == ReferenceType::Retweeted)
+    }
+}
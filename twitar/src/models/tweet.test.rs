@@ -0,0 +1,48 @@
+#[cfg(test)]
+mod test_tweet {
+    use serde_json::json;
+    use crate::models::{Tweet, ReferenceType};
+
+    fn retweet() -> serde_json::Value {
+        json!({
+            "id": "1511757922354663425",
+            "text": "RT @TwitterDev: Lots of new fields",
+            "created_at": "2022-04-06T17:07:13.000Z",
+            "author_id": "2244994945",
+            "referenced_tweets": [{"type": "retweeted", "id": "1511749120564957189"}],
+            "public_metrics": {"retweet_count": 7, "reply_count": 0, "like_count": 0, "quote_count": 0, "impression_count": 12},
+            "edit_history_tweet_ids": ["1511757922354663425"]
+        })
+    }
+
+    #[test]
+    fn deserializes_requested_fields() {
+        let tweet: Tweet = serde_json::from_value(retweet()).unwrap();
+
+        assert_eq!(tweet.created_at.unwrap().timestamp(), 1649264833);
+        assert_eq!(tweet.referenced_tweets[0].kind, ReferenceType::Retweeted);
+        assert_eq!(tweet.public_metrics.as_ref().unwrap().retweet_count, 7);
+        assert!(tweet.is_retweet());
+    }
+
+    #[test]
+    fn keeps_unknown_fields() {
+        let tweet: Tweet = serde_json::from_value(retweet()).unwrap();
+
+        assert_eq!(tweet.extra["edit_history_tweet_ids"], json!(["1511757922354663425"]));
+        assert_eq!(tweet.public_metrics.as_ref().unwrap().extra["impression_count"], json!(12));
+        assert_eq!(serde_json::to_value(&tweet).unwrap()["edit_history_tweet_ids"], json!(["1511757922354663425"]));
+    }
+
+    #[test]
+    fn minimal_tweet_falls_back_on_the_text() {
+        let tweet: Tweet = serde_json::from_value(json!({"id": "1", "text": "RT @someone: hello"})).unwrap();
+        assert!(tweet.is_retweet());
+
+        let tweet: Tweet = serde_json::from_value(json!({
+            "id": "2", "text": "RT is how it starts",
+            "referenced_tweets": [{"type": "quoted", "id": "3"}]
+        })).unwrap();
+        assert!(!tweet.is_retweet());
+    }
+}
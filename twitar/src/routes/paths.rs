@@ -0,0 +1,20 @@
+pub const ROOT: &str = "/";
+pub const HEALTHZ: &str = "/healthz";
+pub const READYZ: &str = "/readyz";
+pub const METRICS: &str = "/metrics";
+pub const ENABLE: &str = "/enable";
+pub const OAUTH_CALLBACK: &str = "/oauth/callback";
+pub const REVOKE: &str = "/revoke";
+pub const REFRESH: &str = "/refresh";
+pub const USER: &str = "/user";
+pub const TIMELINE: &str = "/timeline";
+pub const REMOVE: &str = "/remove";
+pub const OAUTH1: &str = "/oauth1";
+pub const AUDIT: &str = "/audit";
+pub const ARCHIVE: &str = "/archive";
+
+/// Every path `Routes::routes` answers on, the request metrics of any other path are labelled `unmatched`
+pub const ROUTES: [&str; 14] = [
+    ROOT, HEALTHZ, READYZ, METRICS, ENABLE, OAUTH_CALLBACK, REVOKE,
+    REFRESH, USER, TIMELINE, REMOVE, OAUTH1, AUDIT, ARCHIVE,
+];
@@ -1,14 +1,17 @@
-use hyper::{Method};
+use std::time::Instant;
+use hyper::{Method, header::HeaderValue};
 
-use crate::errors::response::TError;
+use crate::errors::{envelope::X_REQUEST_ID, response::TError};
 use crate::helpers::commons::UserId;
+use crate::helpers::metrics::METRICS;
 use crate::helpers::request::req_query;
-use crate::startup::server::{AppState, CurrentUser};
-use crate::helpers::response::{ApiBody, ResponseBuilder};
+use crate::routes::paths;
+use crate::startup::server::AppState;
+use crate::helpers::response::ApiBody;
 use crate::{helpers::response::TResult};
 use crate::controllers::{not_found, authorize_bot, 
-    health_check, handle_redirect, revoke_token, refresh_token, user_lookup, 
-    get_timeline, handle_delete, request_token
+    health_check, readiness, metrics, handle_redirect, revoke_token, refresh_token, user_lookup, 
+    get_timeline, handle_delete, request_token, audit_log, archived_tweets
 };
 
 pub struct Routes;
@@ -55,7 +58,7 @@ impl Routes {
     pub async fn auth_middleware(state: AppState) -> TResult<AppState> {
         let req = &state.req;
         
-        let protected_paths = ["/enable", "/revoke", "/remove", "/refresh", "/user", "/timeline"];
+        let protected_paths = [paths::ENABLE, paths::REVOKE, paths::REMOVE, paths::REFRESH, paths::USER, paths::TIMELINE, paths::AUDIT, paths::ARCHIVE];
 
         
         match protected_paths.contains(&req.uri().path()) {
@@ -63,20 +66,13 @@ impl Routes {
                 let query = req.uri().query();
                 let user_id = req_query(query, "user_id");
                 // user_id should be moved into the request header
-                println!("\n\nTHE CONTENT>>>>||||<<<< {:#?}\n\n", user_id);
-                if let Ok(parsed_user_id) = UserId::parse(user_id) {
-                    println!("::::::::::::::::::::::::::::::::::::::::::::::::::::::::::::::::::::::::;");
-                    let auth_user = parsed_user_id.verify(&state.db_pool).await?;
-                    let v2_credentials = parsed_user_id.v2_credentials(&state.db_pool).await?;
-                    let v1_credentials = parsed_user_id.v1_credentials(&state.db_pool).await?;
-                    
-                    if auth_user.v1_active && auth_user.v2_active {
-                        let user_credentials = CurrentUser::new(auth_user, v1_credentials, v2_credentials);
-                        let new_state = AppState::add_user(state, user_credentials);
-                        // Pointer for heap allocation for the return type
-                        return Ok(new_state)
-                    }
+                let parsed_user_id = UserId::parse(user_id)?;
+                let user_credentials = parsed_user_id.current_user(state.store.users.as_ref()).await?;
 
+                if user_credentials.basic.v1_active && user_credentials.basic.v2_active {
+                    let new_state = AppState::add_user(state, user_credentials);
+                    // Pointer for heap allocation for the return type
+                    return Ok(new_state)
                 }
             }
             false => {
@@ -84,18 +80,30 @@ impl Routes {
             }
         }
 
-        return Err(TError::Unauthenticated(""));
+        Err(TError::Unauthenticated("Twitter account is not connected"))
     }
 
+    /// Every failure, from authentication to the controllers, is turned into a JSON error response here
+    /// so that hyper never sees an `Err`
     pub async fn wrapper(state: AppState) -> TResult<ApiBody> {
-        let auth = Self::auth_middleware(state).await;
-        if let Ok(new_state) = auth {
-            println!(":[[[[[[[[[[[[[[[[]]]]]]]]]]]]]]]]]]]]]]]]]]]]:::::");
-            return Self::routes(new_state).await
+        let request_id = state.request_id.clone();
+        let (method, path) = (state.req.method().to_string(), state.req.uri().path().to_string());
+        let start = Instant::now();
+
+        let response = match Self::auth_middleware(state).await {
+            Ok(new_state) => Self::routes(new_state).await,
+            Err(e) => Err(e),
+        };
+
+        let mut response = response.unwrap_or_else(|e| e.into_response(&request_id));
+        METRICS.observe_request(&method, &path, response.status().as_u16(), start);
+
+        if let Ok(id) = HeaderValue::from_str(&request_id) {
+            response.headers_mut().insert(X_REQUEST_ID, id);
         }
 
-        println!("ERROR--ERROR--ERROR--ERROR--ERROR--ERROR--ERROR--ERROR--");
-        return ResponseBuilder::new("Missing or Invalid user id".into(), Some(""), 401).reply();
+        tracing::info!(status = response.status().as_u16(), latency_ms = start.elapsed().as_millis() as u64, "request completed");
+        Ok(response)
     }
 
 
@@ -108,15 +116,20 @@ impl Routes {
         let req = &state.req;
 
         match (req.method(), req.uri().path(), req.uri().query()) {
-            (&Method::GET, "/", _) => health_check().await,
-            (&Method::GET, "/enable", _) => authorize_bot(state).await,
-            (&Method::GET, "/oauth/callback", _x) => handle_redirect(state).await,
-            (&Method::POST, "/revoke", _) => revoke_token(state).await,
-            (&Method::GET, "/refresh", _) => refresh_token(state).await,
-            (&Method::GET, "/user", _x) => user_lookup(state).await,
-            (&Method::GET, "/timeline", _) => get_timeline(state).await,
-            (&Method::POST, "/remove", _) => handle_delete(state).await,
-            (&Method::GET, "/oauth1", _) => request_token(state).await,
+            (&Method::GET, paths::ROOT, _) => health_check().await,
+            (&Method::GET, paths::HEALTHZ, _) => health_check().await,
+            (&Method::GET, paths::READYZ, _) => readiness(state).await,
+            (&Method::GET, paths::METRICS, _) => metrics(state).await,
+            (&Method::GET, paths::ENABLE, _) => authorize_bot(state).await,
+            (&Method::GET, paths::OAUTH_CALLBACK, _x) => handle_redirect(state).await,
+            (&Method::POST, paths::REVOKE, _) => revoke_token(state).await,
+            (&Method::GET, paths::REFRESH, _) => refresh_token(state).await,
+            (&Method::GET, paths::USER, _x) => user_lookup(state).await,
+            (&Method::GET, paths::TIMELINE, _) => get_timeline(state).await,
+            (&Method::POST, paths::REMOVE, _) => handle_delete(state).await,
+            (&Method::GET, paths::OAUTH1, _) => request_token(state).await,
+            (&Method::GET, paths::AUDIT, _) => audit_log(state).await,
+            (&Method::GET, paths::ARCHIVE, _) => archived_tweets(state).await,
             _ => {
                 not_found().await
             }
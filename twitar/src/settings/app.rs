@@ -1,23 +1,55 @@
 use serde::Deserialize;
+use serde_aux::field_attributes::deserialize_number_from_string;
 
+use crate::base_repository::repository::Storage;
+use crate::helpers::{shared::SharedStore, telemetry::LogFormat, transport::FixtureMode};
+use crate::settings::variables::AppEnv;
 
-#[derive(Debug, Deserialize, Clone)]
+
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
 pub struct AppSettings {
-    pub access_token: String,
-    pub access_token_secret: String,
+    /// Set from `APP_ENV`, which also picks the configuration file
+    pub env: AppEnv,
+    /// The address the server binds to, `0.0.0.0` inside a container
+    pub host: String,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub port: u16,
+    /// Seconds the requests in flight get to finish after SIGTERM/SIGINT
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub shutdown_timeout: u64,
     pub app_address: String,
     pub api_key: String,
     pub api_key_secret: String,
-    pub base_url: String,
-    pub bearer_token: String,
-    pub client_url: String,
+    /// The OAuth2 client id, still read from `client_url` for older deployments
+    #[serde(alias = "client_url")]
+    pub client_id: String,
     pub client_secret: String,
     pub callback_url: String,
-    // pub database_url: String,
-    pub app_env: String,
-    pub port: String,
-    pub request_url: String,
     pub response_type: String,
     pub state_code: String,
+    /// Where the v2 and v1.1 endpoints live, e.g. `https://api.twitter.com`
     pub twitter_api: String,
-}
\ No newline at end of file
+    /// Where media is uploaded, e.g. `https://upload.twitter.com`
+    pub upload_api: String,
+    /// Where users are sent to grant twitar access with OAuth2
+    pub authorize_url: String,
+    /// Whether `/readyz` also checks that `twitter_api` can be reached
+    pub probe_twitter: bool,
+    /// Where users, credentials and queued tweets are kept, `postgres`, `sqlite` (one account, with the `sqlite` feature)
+    /// or `memory` (nothing survives a restart)
+    pub storage: Storage,
+    /// Where the rate limit budgets are counted, `redis` (shared by every replica) or `memory`
+    pub rate_limit_store: SharedStore,
+    /// Where the credentials of signed in users are cached, `redis` (shared by every replica) or `memory`
+    pub credential_cache: SharedStore,
+    /// Seconds a cached user is trusted for, writes made through twitar invalidate it sooner
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub credential_ttl: u64,
+    /// Records or replays the requests made to Twitter, e.g. `BOT__APP__FIXTURES__MODE=replay` and `BOT__APP__FIXTURES__PATH=<file>`
+    pub fixtures: FixtureMode,
+    /// `text` or `json`
+    pub log_format: LogFormat,
+    /// The default filter, e.g. `info` or `twitar=debug`. `RUST_LOG` overrides it
+    pub log_level: String,
+}
@@ -1,20 +1,140 @@
+use std::fmt;
 use config::{ConfigBuilder, builder::DefaultState, Environment};
 use dotenv::dotenv;
 use serde::{Deserialize};
+use twitar_macro::{Validate, ValidationErrors};
+use url::Url;
+
+use crate::base_repository::repository::Storage;
 use crate::settings::{database::DbSettings, app::AppSettings, variables::AppEnv};
 
-#[derive(Debug, Deserialize, Clone)]
+#[cfg(test)]
+#[path = "./config.test.rs"]
+mod config_test;
+
+
+/// Everything twitar is configured with, loaded once at startup and shared through `AppState`
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
 pub struct Settings {
     pub db: DbSettings,
     pub app: AppSettings,
+    /// Left empty, rate limits, cached users and leases are kept in this process
     pub redis_uri: String,
 }
 
-pub fn get_configuration() -> Result<Settings, config::ConfigError> {
-    dotenv().ok();
-    let app_env: AppEnv = std::env::var("APP_ENV")
+
+#[derive(Debug)]
+pub enum SettingsError {
+    /// The configuration files or variables could not be read
+    Load(config::ConfigError),
+    Invalid(ValidationErrors),
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Load(e) => write!(f, "Unable to load the configuration: {}", e),
+            Self::Invalid(errors) => {
+                writeln!(f, "Invalid configuration:")?;
+                errors.0.iter().try_for_each(|e| writeln!(f, "  - {}", e))
+            }
+        }
+    }
+}
+
+impl std::error::Error for SettingsError {}
+
+impl From<config::ConfigError> for SettingsError {
+    fn from(e: config::ConfigError) -> Self {
+        Self::Load(e)
+    }
+}
+
+
+impl Validate for Settings {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+
+        let required = [
+            ("app.host", &self.app.host),
+            ("app.app_address", &self.app.app_address),
+            ("app.api_key", &self.app.api_key),
+            ("app.api_key_secret", &self.app.api_key_secret),
+            ("app.client_id", &self.app.client_id),
+            ("app.client_secret", &self.app.client_secret),
+            ("app.callback_url", &self.app.callback_url),
+            ("app.response_type", &self.app.response_type),
+            ("app.state_code", &self.app.state_code),
+            ("app.twitter_api", &self.app.twitter_api),
+            ("app.upload_api", &self.app.upload_api),
+            ("app.authorize_url", &self.app.authorize_url),
+        ];
+
+        // the other storages do not connect to Postgres
+        let postgres = [
+            ("db.host", &self.db.host),
+            ("db.username", &self.db.username),
+            ("db.database_name", &self.db.database_name),
+        ];
+
+        let postgres = match self.app.storage {
+            Storage::Postgres => postgres.as_slice(),
+            _ => &[],
+        };
+
+        for (field, value) in required.iter().chain(postgres) {
+            if value.trim().is_empty() {
+                errors.add(field, "required", "is required".into());
+            }
+        }
+
+        if self.app.storage == Storage::Postgres && self.db.port == 0 {
+            errors.add("db.port", "required", "must be a port number".into());
+        }
+
+        if self.app.port == 0 {
+            errors.add("app.port", "required", "must be a port number".into());
+        }
+
+        if self.app.shutdown_timeout == 0 {
+            errors.add("app.shutdown_timeout", "required", "must be at least a second".into());
+        }
+
+        let urls = [
+            ("app.app_address", &self.app.app_address),
+            ("app.callback_url", &self.app.callback_url),
+            ("app.twitter_api", &self.app.twitter_api),
+            ("app.upload_api", &self.app.upload_api),
+            ("app.authorize_url", &self.app.authorize_url),
+            ("redis_uri", &self.redis_uri),
+        ];
+
+        for (field, value) in urls.into_iter().filter(|(_, value)| !value.trim().is_empty()) {
+            if Url::parse(value).is_err() {
+                errors.add(field, "url", format!("{:?} is not a valid url", value));
+            }
+        }
+
+        errors.into_result()
+    }
+}
+
+
+fn app_env() -> Result<AppEnv, SettingsError> {
+    std::env::var("APP_ENV")
         .unwrap_or_else(|_| "local".into())
-        .try_into().expect("Failed to parse APP_ENV");
+        .try_into()
+        .map_err(|e| {
+            let mut errors = ValidationErrors::new();
+            errors.add("APP_ENV", "one_of", e);
+            SettingsError::Invalid(errors)
+        })
+}
+
+fn builder() -> Result<config::Config, SettingsError> {
+    dotenv().ok();
+    let app_env = app_env()?;
 
      let base_path = std::env::current_dir().expect("Failed to determine the current directory");
      let mut config_dir = base_path.join("configuration");
@@ -25,9 +145,29 @@ pub fn get_configuration() -> Result<Settings, config::ConfigError> {
 
      let settings =  ConfigBuilder::<DefaultState>::default()
         .add_source(config::File::from(config_dir.join("base")))
-        .add_source(config::File::from(config_dir.join(app_env.to_string())))
+        .add_source(config::File::from(config_dir.join(app_env.to_string())).required(false))
         .add_source(Environment::with_prefix("bot").separator("__"))
-        .add_source(Environment::with_prefix(&app_env.to_string()).separator("__"));
+        .add_source(Environment::with_prefix(&app_env.to_string()).separator("__"))
+        .set_override("app.env", app_env.to_string())?;
+
+    Ok(settings.build()?)
+}
 
-    settings.build()?.try_deserialize()
-}
\ No newline at end of file
+/// Reads `configuration/base.yaml`, then the file of the `APP_ENV` environment, then the `BOT__` and `<APP_ENV>__` variables.
+/// Every missing or invalid value is reported at once
+pub fn get_configuration() -> Result<Settings, SettingsError> {
+    let settings: Settings = builder()?.try_deserialize()?;
+    settings.validate().map_err(SettingsError::Invalid)?;
+
+    Ok(settings)
+}
+
+/// Only `app.storage`, so `twitar migrate` works on the schema of the configured storage
+pub fn get_storage() -> Result<Storage, SettingsError> {
+    Ok(builder()?.get::<Option<Storage>>("app.storage")?.unwrap_or_default())
+}
+
+/// Only the `db` section, for tools (migrations, the test harness) that do not need the rest of the settings
+pub fn get_database_configuration() -> Result<DbSettings, SettingsError> {
+    Ok(builder()?.get("db")?)
+}
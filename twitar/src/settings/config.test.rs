@@ -0,0 +1,78 @@
+#[cfg(test)]
+mod test_config {
+    use config::{Config, File, FileFormat};
+    use twitar_macro::Validate;
+
+    use crate::base_repository::repository::Storage;
+    use crate::helpers::transport::FixtureMode;
+    use crate::settings::config::Settings;
+
+    const YAML: &str = r#"
+db:
+  host: localhost
+  port: "5432"
+  username: twitar
+  password: password
+  database_name: twitar
+  require_ssl: false
+app:
+  host: 0.0.0.0
+  port: 8080
+  shutdown_timeout: 30
+  app_address: "http://127.0.0.1:8080"
+  api_key: key
+  api_key_secret: key-secret
+  client_url: client
+  client_secret: client-secret
+  callback_url: "http://127.0.0.1:8080/oauth/callback"
+  response_type: code
+  state_code: state
+  twitter_api: "https://api.twitter.com"
+  upload_api: "https://upload.twitter.com"
+  authorize_url: "https://twitter.com/i/oauth2/authorize"
+  fixtures:
+    mode: replay
+    path: fixtures/timeline.json
+redis_uri: "redis://127.0.0.1/"
+"#;
+
+    fn settings(yaml: &str) -> Settings {
+        Config::builder().add_source(File::from_str(yaml, FileFormat::Yaml)).build().unwrap()
+            .try_deserialize().unwrap()
+    }
+
+    #[test]
+    fn reads_a_complete_configuration() {
+        let settings = settings(YAML);
+
+        assert!(settings.validate().is_ok());
+        assert_eq!(settings.db.port, 5432);
+        assert_eq!(settings.app.client_id, "client");
+        assert_eq!(settings.app.fixtures, FixtureMode::Replay("fixtures/timeline.json".into()));
+    }
+
+    #[test]
+    fn reports_every_missing_value_at_once() {
+        let errors = settings("app:\n  twitter_api: not a url\n").validate().unwrap_err();
+        let fields = errors.0.iter().map(|e| e.field).collect::<Vec<_>>();
+
+        assert!(fields.contains(&"db.host"));
+        assert!(fields.contains(&"app.api_key"));
+        assert!(fields.contains(&"app.port"));
+        assert!(!fields.contains(&"redis_uri"));
+        assert!(errors.0.iter().any(|e| e.field == "app.twitter_api" && e.rule == "url"));
+        assert_eq!(settings("").app.fixtures, FixtureMode::Off);
+    }
+
+    #[test]
+    fn the_memory_storage_needs_neither_postgres_nor_redis() {
+        let yaml = YAML.replace("redis_uri: \"redis://127.0.0.1/\"", "redis_uri: \"\"")
+            .replace("  host: localhost\n", "  host: \"\"\n")
+            .replace("app:\n", "app:\n  storage: memory\n");
+        let memory = settings(&yaml);
+
+        assert!(memory.validate().is_ok());
+        assert_eq!(memory.app.storage, Storage::Memory);
+        assert!(settings("app:\n  storage: postgres\n").validate().unwrap_err().0.iter().any(|e| e.field == "db.host"));
+    }
+}
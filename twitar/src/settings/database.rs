@@ -1,14 +1,23 @@
 use serde::Deserialize;
+use serde_aux::field_attributes::deserialize_number_from_string;
 use sqlx::{postgres::{PgConnectOptions, PgSslMode}};
+#[cfg(feature = "sqlite")]
+use sqlx::sqlite::SqliteConnectOptions;
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
 pub struct DbSettings {
     pub host: String,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
     pub port: u16,
     pub username: String,
     pub database_name: String,
     pub require_ssl: bool,
     pub password: String,
+    /// Applies the embedded migrations when the server starts
+    pub migrate_on_start: bool,
+    /// The database file of the `sqlite` storage, created when it does not exist
+    pub sqlite_path: String,
 }
 
 impl DbSettings {
@@ -25,4 +34,9 @@ impl DbSettings {
     pub fn with_db(&self) -> PgConnectOptions {
         self.without_db().database(&self.database_name)
     }
+
+    #[cfg(feature = "sqlite")]
+    pub fn sqlite(&self) -> SqliteConnectOptions {
+        SqliteConnectOptions::new().filename(&self.sqlite_path)
+    }
 }
\ No newline at end of file
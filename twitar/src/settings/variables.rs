@@ -1,7 +1,9 @@
 use serde::Deserialize;
 
-#[derive(Debug, Clone, Deserialize, derive_more::Display, PartialEq)]
+#[derive(Debug, Clone, Default, Deserialize, derive_more::Display, PartialEq)]
+#[serde(try_from = "String")]
 pub enum AppEnv {
+    #[default]
     #[display(fmt = "local")]
     Local,
     #[display(fmt = "test")]
@@ -21,8 +23,7 @@ impl TryFrom<String> for AppEnv {
             "staging" => Ok(Self::Staging),
             "test" => Ok(Self::Test),
             "local" => Ok(Self::Local),
-            other => Err(format!("
-            {} is not a supported environment app_env. Use either `local`|`test`|`staging`|`production`", other))
+            other => Err(format!("{} is not a supported environment app_env. Use either `local`|`test`|`staging`|`production`", other))
         }
     }
 }
\ No newline at end of file
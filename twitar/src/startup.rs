@@ -1,2 +1,3 @@
 pub mod server;
-pub mod application;
\ No newline at end of file
+pub mod application;
+pub mod migrations;
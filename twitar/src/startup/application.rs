@@ -0,0 +1,116 @@
+use std::{future::Future, net::TcpListener, pin::Pin, sync::Arc, time::Duration};
+use hyper::Server;
+use hyper::service::{make_service_fn, service_fn};
+use redis::Client as RedisClient;
+use sqlx::PgPool;
+use tower::ServiceBuilder;
+use tracing::Instrument;
+
+use crate::helpers::{credentials::{CredentialCache, RedisCredentials}, rate_limit::{RateLimiter, RedisCounter}, shared::SharedStore};
+use crate::base_repository::{memory::MemoryStore, postgres::PgStore, repository::{Storage, Store}};
+#[cfg(feature = "sqlite")]
+use crate::base_repository::sqlite::SqliteStore;
+use crate::helpers::{lease::{Leases, MemoryLeases}, shutdown::Shutdown, transport::HttpClient};
+use crate::routes::server::Routes;
+use crate::settings::config::Settings;
+use crate::startup::server::AppState;
+
+
+type ServerFuture = Pin<Box<dyn Future<Output = hyper::Result<()>> + Send>>;
+
+
+/// The http server with everything it depends on. The listener is bound by the caller,
+/// so tests can ask for a random port and read it back with `port()`
+pub struct Application {
+    port: u16,
+    server: ServerFuture,
+    shutdown: Shutdown,
+    /// How long in-flight requests get to finish once shutdown is triggered
+    drain_deadline: Duration,
+}
+
+impl Application {
+    pub fn build(listener: TcpListener, settings: Settings, hyper: HttpClient, redis: Option<RedisClient>, db_pool: PgPool) -> hyper::Result<Self> {
+        let port = listener.local_addr().map(|addr| addr.port()).unwrap_or_default();
+        // the redis stores fall back to this process when there is no redis_uri
+        let rate_limits = match (settings.app.rate_limit_store, &redis) {
+            (SharedStore::Redis, Some(redis)) => RateLimiter::new().with_counter(Arc::new(RedisCounter::new(redis.clone()))),
+            _ => RateLimiter::new(),
+        };
+        let credentials = match (settings.app.credential_cache, &redis) {
+            (SharedStore::Redis, Some(redis)) => CredentialCache::new().with_store(Arc::new(RedisCredentials::new(redis.clone(), &settings.app.client_secret))),
+            _ => CredentialCache::new(),
+        }.with_ttl(Duration::from_secs(settings.app.credential_ttl));
+        let store = match settings.app.storage {
+            Storage::Postgres => Store::new(PgStore::new(db_pool.clone(), credentials), Arc::new(Leases::new(redis.clone(), db_pool.clone()))),
+            Storage::Memory => Store::new(MemoryStore::new(), Arc::new(MemoryLeases::new())),
+            // a single replica owns the file, its leases need not be shared
+            #[cfg(feature = "sqlite")]
+            Storage::Sqlite => Store::new(SqliteStore::new(settings.db.sqlite()), Arc::new(MemoryLeases::new())),
+        };
+        let shutdown = Shutdown::new();
+        let drain_deadline = Duration::from_secs(settings.app.shutdown_timeout);
+        let settings = Arc::new(settings);
+        let draining = shutdown.clone();
+
+        let service = make_service_fn(move |_| {
+            let redis = redis.clone();
+            let client = hyper.clone();
+            let settings = settings.clone();
+            let db_pool = db_pool.clone();
+            let rate_limits = rate_limits.clone();
+            let store = store.clone();
+            let shutdown = draining.clone();
+
+            let svc = service_fn(move |req| {
+                let state = AppState::new(settings.clone(),
+                    req, client.to_owned(), redis.to_owned(), db_pool.to_owned(), rate_limits.clone(), store.clone(), shutdown.clone());
+
+                let span = tracing::info_span!("request",
+                    request_id = %state.request_id, method = %state.req.method(), path = %state.req.uri().path());
+
+                Routes::wrapper(state).instrument(span)
+            });
+
+            let svc = ServiceBuilder::new()
+                .timeout(Duration::new(45, 0))
+                .service(svc);
+
+            async {
+                Ok::<_, hyper::Error>(svc)
+            }
+        });
+
+        // stops accepting connections once triggered, and resolves when the open ones are done
+        let server = Server::from_tcp(listener)?.serve(service)
+            .with_graceful_shutdown(shutdown.clone().triggered());
+
+        Ok(Self { port, server: Box::pin(server), shutdown, drain_deadline })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Triggering it (e.g. on SIGTERM) stops the server after draining the requests in flight
+    pub fn shutdown(&self) -> Shutdown {
+        self.shutdown.clone()
+    }
+
+    pub async fn run_until_stopped(self) -> hyper::Result<()> {
+        let Self { server, shutdown, drain_deadline, .. } = self;
+
+        let deadline = async move {
+            shutdown.triggered().await;
+            tokio::time::sleep(drain_deadline).await;
+        };
+
+        tokio::select! {
+            result = server => result,
+            _ = deadline => {
+                tracing::warn!(deadline_secs = drain_deadline.as_secs(), "requests were still in flight after the shutdown deadline");
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use std::{collections::HashMap, fmt};
+use futures::future::BoxFuture;
+use sqlx::{Database, PgConnection, Pool, Postgres};
+use sqlx::migrate::{AppliedMigration, Migrate, MigrateError, Migration, Migrator};
+
+
+/// The `migrations/` folder, embedded in the binary so a deployment does not need sqlx-cli
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+
+/// A database twitar keeps a schema in, with the migrations of that schema
+pub trait Schema: Database {
+    fn migrator() -> &'static Migrator;
+
+    /// Whether sqlx's `_sqlx_migrations` table was created yet
+    fn has_migrations_table(conn: &mut Self::Connection) -> BoxFuture<'_, Result<bool, MigrateError>>;
+}
+
+impl Schema for Postgres {
+    fn migrator() -> &'static Migrator {
+        &MIGRATOR
+    }
+
+    fn has_migrations_table(conn: &mut PgConnection) -> BoxFuture<'_, Result<bool, MigrateError>> {
+        Box::pin(async move {
+            Ok(sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL").fetch_one(conn).await?)
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl Schema for sqlx::Sqlite {
+    fn migrator() -> &'static Migrator {
+        &crate::base_repository::sqlite::MIGRATOR
+    }
+
+    fn has_migrations_table(conn: &mut sqlx::SqliteConnection) -> BoxFuture<'_, Result<bool, MigrateError>> {
+        Box::pin(async move {
+            let query = "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations')";
+            Ok(sqlx::query_scalar(query).fetch_one(conn).await?)
+        })
+    }
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    /// Applied, but the file changed since
+    Modified,
+    /// Applied by a newer binary
+    Unknown,
+}
+
+impl fmt::Display for MigrationState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = match self {
+            Self::Applied => "applied",
+            Self::Pending => "pending",
+            Self::Modified => "modified",
+            Self::Unknown => "unknown",
+        };
+
+        write!(f, "{}", state)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    pub state: MigrationState,
+}
+
+impl fmt::Display for MigrationStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {:<8} {}", self.version, self.state, self.description)
+    }
+}
+
+
+fn up_migrations<DB: Schema>() -> impl Iterator<Item = &'static Migration> {
+    DB::migrator().iter().filter(|m| !m.migration_type.is_down_migration())
+}
+
+/// Reads the applied migrations, without creating the migrations table of a database that has none yet
+async fn applied<DB: Schema>(conn: &mut DB::Connection) -> Result<Vec<AppliedMigration>, MigrateError>
+where DB::Connection: Migrate {
+    match DB::has_migrations_table(conn).await? {
+        true => conn.list_applied_migrations().await,
+        false => Ok(vec![]),
+    }
+}
+
+/// Fails with `VersionMissing` when the database has migrations this binary does not know about
+fn ensure_not_ahead<DB: Schema>(applied: &[AppliedMigration]) -> Result<(), MigrateError> {
+    match applied.iter().find(|a| !up_migrations::<DB>().any(|m| m.version == a.version)) {
+        Some(unknown) => Err(MigrateError::VersionMissing(unknown.version)),
+        None => Ok(()),
+    }
+}
+
+
+/// Every embedded migration and every applied one, by version
+pub async fn status<DB: Schema>(pool: &Pool<DB>) -> Result<Vec<MigrationStatus>, MigrateError>
+where DB::Connection: Migrate {
+    let mut conn = pool.acquire().await?;
+    let applied = applied::<DB>(&mut conn).await?
+        .into_iter().map(|a| (a.version, a)).collect::<HashMap<_, _>>();
+
+    let mut statuses = up_migrations::<DB>().map(|m| {
+        let state = match applied.get(&m.version) {
+            Some(a) if a.checksum == m.checksum => MigrationState::Applied,
+            Some(_) => MigrationState::Modified,
+            None => MigrationState::Pending,
+        };
+
+        MigrationStatus { version: m.version, description: m.description.to_string(), state }
+    }).collect::<Vec<_>>();
+
+    statuses.extend(applied.keys()
+        .filter(|version| !up_migrations::<DB>().any(|m| m.version == **version))
+        .map(|version| MigrationStatus { version: *version, description: String::new(), state: MigrationState::Unknown }));
+
+    statuses.sort_by_key(|s| s.version);
+    Ok(statuses)
+}
+
+/// Refuses a schema that is ahead of this binary, without changing anything
+pub async fn check<DB: Schema>(pool: &Pool<DB>) -> Result<(), MigrateError>
+where DB::Connection: Migrate {
+    let mut conn = pool.acquire().await?;
+    ensure_not_ahead::<DB>(&applied::<DB>(&mut conn).await?)
+}
+
+/// Applies the pending migrations. Replicas starting together wait for each other on an advisory lock
+pub async fn up<DB: Schema>(pool: &Pool<DB>) -> Result<(), MigrateError>
+where DB::Connection: Migrate {
+    let mut conn = pool.acquire().await?;
+
+    conn.lock().await?;
+
+    let apply = async {
+        conn.ensure_migrations_table().await?;
+
+        if let Some(version) = conn.dirty_version().await? {
+            return Err(MigrateError::Dirty(version));
+        }
+
+        let applied = applied::<DB>(&mut conn).await?;
+        ensure_not_ahead::<DB>(&applied)?;
+
+        for migration in up_migrations::<DB>() {
+            match applied.iter().find(|a| a.version == migration.version) {
+                Some(a) if a.checksum != migration.checksum => return Err(MigrateError::VersionMismatch(migration.version)),
+                Some(_) => {}
+                None => {
+                    conn.apply(migration).await?;
+                }
+            }
+        }
+
+        Ok(())
+    };
+
+    let result = apply.await;
+    conn.unlock().await?;
+    result
+}
+
+/// Reverts the `steps` most recent migrations, returning their versions
+pub async fn down<DB: Schema>(pool: &Pool<DB>, steps: usize) -> Result<Vec<i64>, MigrateError>
+where DB::Connection: Migrate {
+    let mut conn = pool.acquire().await?;
+
+    conn.lock().await?;
+
+    let revert = async {
+        let mut applied = applied::<DB>(&mut conn).await?;
+        applied.sort_by_key(|a| std::cmp::Reverse(a.version));
+
+        let mut reverted = vec![];
+
+        for version in applied.into_iter().take(steps).map(|a| a.version) {
+            let migration = DB::migrator().iter()
+                .find(|m| m.version == version && m.migration_type.is_down_migration())
+                .ok_or(MigrateError::VersionMissing(version))?;
+
+            conn.revert(migration).await?;
+            reverted.push(version);
+        }
+
+        Ok::<_, MigrateError>(reverted)
+    };
+
+    let result = revert.await;
+    conn.unlock().await?;
+    result
+}
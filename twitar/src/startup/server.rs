@@ -1,26 +1,23 @@
 use http::Request;
-use hyper::{Server, Client, Body};
-use hyper::service::{make_service_fn, service_fn};
-use hyper_tls::HttpsConnector;
+use hyper::Body;
 use sqlx::{PgPool, Pool, Postgres};
 use sqlx::postgres::PgPoolOptions;
-use tower::ServiceBuilder;
-use std::time::Duration;
-use std::{net::SocketAddr};
-use dotenv::dotenv;
+use std::{sync::Arc, time::Duration};
+use std::net::TcpListener;
 use redis::{Client as RedisClient};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
-use crate::base_repository::db::{AuthUser, V1User, V2User};
-use crate::configurations::db_settings::DatabaseSettings;
-use crate::helpers::request::HyperClient;
-use crate::routes::server::Routes;
-use crate::configurations::variables::SettingsVars;
-use crate::settings::config;
+use crate::base_repository::{db::{AuthUser, V1User, V2User}, repository::{Storage, Store}};
+#[cfg(feature = "sqlite")]
+use crate::base_repository::sqlite::SqliteStore;
+use crate::errors::envelope::X_REQUEST_ID;
+use crate::helpers::{rate_limit::RateLimiter, shutdown::{signal, Shutdown}, telemetry, transport::HttpClient};
+use crate::startup::{application::Application, migrations};
+use crate::settings::{config::{get_configuration, Settings}, database::DbSettings};
 
 // use super::timeout::TimeoutLayer;
 
-type GenericError = hyper::Error;
-
 pub enum User {
     AuthUser(AuthUser),
     V1User(),
@@ -28,7 +25,7 @@ pub enum User {
     // All(AuthUser, V2User)
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CurrentUser {
     pub basic: AuthUser,
     pub v2_user: V2User,
@@ -48,17 +45,31 @@ impl CurrentUser {
 
 #[derive(Debug)]
 pub struct AppState {
-    pub redis: RedisClient,
+    /// `None` when `redis_uri` is not set
+    pub redis: Option<RedisClient>,
     pub db_pool: Pool<Postgres>,
-    pub hyper: HyperClient,
+    pub hyper: HttpClient,
     pub req: Request<Body>,
-    pub env_vars: SettingsVars,
+    pub settings: Arc<Settings>,
     pub user: Option<CurrentUser>,
+    /// Identifies the request in error responses, taken from the `X-Request-Id` header when the client sends one
+    pub request_id: String,
+    /// The last known rate limits of the Twitter endpoints, shared by every request
+    pub rate_limits: RateLimiter,
+    /// The repositories the controllers read and write through, over `app.storage`
+    pub store: Store,
+    pub shutdown: Shutdown,
 }
 
 impl AppState {
-    fn new(env_vars: SettingsVars, req: Request<Body>, hyper: HyperClient, redis: RedisClient, db_pool: Pool<Postgres>) -> Self {
-        Self { redis, hyper, req, env_vars, db_pool, user: None}
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(settings: Arc<Settings>, req: Request<Body>, hyper: HttpClient, redis: Option<RedisClient>, db_pool: Pool<Postgres>, rate_limits: RateLimiter, store: Store, shutdown: Shutdown) -> Self {
+        let request_id = req.headers().get(X_REQUEST_ID)
+            .and_then(|id| id.to_str().ok())
+            .map(|id| id.to_string())
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+        Self { redis, hyper, req, settings, db_pool, user: None, request_id, rate_limits, store, shutdown }
     }
 
     pub fn with_user(&mut self, user: CurrentUser) {
@@ -72,58 +83,56 @@ impl AppState {
 
 
 pub async fn server() {
-    dotenv().ok();
-    let app_config = config::get_configuration();
+    let settings = get_configuration().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1)
+    });
 
-    match app_config {
-        Ok(config) => {
-            println!("THE OBTAINED SETTINGS BECAUSE WE FOUND SOMETHING USEFUL {:#?}", config);
-        }
-        Err(e) => {
-            println!("THERE'S A LOT OF THINGS WRONG BUT FIRST LET'S START HERE!!!!!!!!!!!!!!!!!!!!!! {:#?} ", e);
-        }
+    telemetry::init(settings.app.log_format, &settings.app.log_level);
+
+    let address = (settings.app.host.as_str(), settings.app.port);
+    let listener = TcpListener::bind(address).unwrap_or_else(|e| panic!("Failed to bind {}:{}: {}", address.0, address.1, e));
+    // without redis_uri the rate limits, cached users and leases stay in this process
+    let redis_client = match settings.redis_uri.trim() {
+        "" => None,
+        uri => Some(RedisClient::open(uri).expect("Invalid redis_uri")),
+    };
+    let db_pool = get_pool(&settings.db);
+
+    // a schema ahead of this binary was migrated by a newer release, which this one would corrupt
+    let schema = match (settings.app.storage, settings.db.migrate_on_start) {
+        (Storage::Memory, _) => Ok(()),
+        (Storage::Postgres, true) => migrations::up(&db_pool).await,
+        (Storage::Postgres, false) => migrations::check(&db_pool).await,
+        // the file belongs to this binary alone, so it is always brought up to date
+        #[cfg(feature = "sqlite")]
+        (Storage::Sqlite, _) => SqliteStore::migrate(settings.db.sqlite()).await,
+    };
+
+    if let Err(e) = schema {
+        tracing::error!(error = %e, "the database schema cannot be used");
+        std::process::exit(1)
     }
-    let addr = SocketAddr::from(([127, 0, 0, 1], 8080));
-    let https = HttpsConnector::new();
-    let hyper_pool = Client::builder().build::<_, hyper::Body>(https);
-    let hyper_client = hyper_pool.clone();
-    let redis_client= RedisClient::open("redis://127.0.0.1/").expect("Redis connection failed");
-    let env_vars = SettingsVars::new();
-    let db_pool = get_pool(DatabaseSettings::new(env_vars.clone()));
-
-    
-    let service = make_service_fn(move|_| {
-        let redis = redis_client.clone();
-        let client = hyper_client.clone();
-        let vars = env_vars.clone();
-        let db_pool = db_pool.clone();
-
-        let svc= service_fn(move |req| {
-                let state = AppState::new(vars.clone(), 
-                    req, client.to_owned(), redis.to_owned(), db_pool.to_owned());
-                
-                Routes::wrapper(state)
-            });
-        
-        let svc = ServiceBuilder::new()
-            .timeout(Duration::new(45, 0))
-            .service(svc);
-
-        async {
-            Ok::<_, GenericError>(svc)
-        }
-    });
+    let client = settings.app.fixtures.client().expect("Failed to load the Twitter fixtures");
 
-    let server = Server::bind(&addr).serve(service);
+    let application = Application::build(listener, settings, client, redis_client, db_pool)
+        .expect("Failed to build the application");
 
-    if let Err(e) = server.await {
-        eprintln!("server error: {}", e)
+    let shutdown = application.shutdown();
+    tokio::spawn(async move {
+        signal().await;
+        tracing::info!("shutting down, waiting for the requests in flight");
+        shutdown.trigger();
+    });
+
+    if let Err(e) = application.run_until_stopped().await {
+        tracing::error!(error = %e, "server error")
     }
 }
 
 
-pub fn get_pool(config: DatabaseSettings) -> PgPool {
+pub fn get_pool(config: &DbSettings) -> PgPool {
     PgPoolOptions::new()
         .connect_timeout(Duration::from_secs(2))
         .connect_lazy_with(config.with_db())
-}
\ No newline at end of file
+}
@@ -1 +1,5 @@
-pub mod http;
\ No newline at end of file
+pub mod http;
+pub mod state;
+
+pub use self::http::FakeTwitter;
+pub use self::state::FakeState;
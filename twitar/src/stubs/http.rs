@@ -0,0 +1,482 @@
+use std::{collections::HashMap, convert::Infallible, net::{SocketAddr, TcpListener}, ops::RangeInclusive, sync::{Arc, Mutex}, time::Duration};
+use http::{Method, Request, Response, StatusCode};
+use hyper::{Body, Server, service::{make_service_fn, service_fn}};
+use serde_json::{json, Value};
+use url::form_urlencoded;
+
+use crate::helpers::signature::SignedParams;
+use crate::stubs::state::{FakeState, FakeTweet, Window};
+
+#[cfg(test)]
+#[path = "./http.test.rs"]
+mod http_test;
+
+
+/// Requests per 15 minutes window, close to what Twitter allows each user
+const TIMELINE_LIMIT: u32 = 900;
+const LIKES_LIMIT: u32 = 75;
+const LOOKUP_LIMIT: u32 = 900;
+const DELETE_LIMIT: u32 = 50;
+
+const DEFAULT_PAGE_SIZE: usize = 10;
+
+
+type Form = HashMap<String, String>;
+
+fn form(raw: &[u8]) -> Form {
+    form_urlencoded::parse(raw).into_owned().collect()
+}
+
+fn json_response(status: StatusCode, body: Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header("content-type", "application/json; charset=utf-8")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+fn form_response(body: String) -> Response<Body> {
+    Response::builder()
+        .status(StatusCode::OK)
+        .header("content-type", "text/html; charset=utf-8")
+        .body(Body::from(body))
+        .unwrap()
+}
+
+/// The RFC 7807 problem the v2 api answers failed requests with
+fn problem(status: StatusCode, title: &str, detail: &str) -> Response<Body> {
+    json_response(status, json!({
+        "title": title,
+        "detail": detail,
+        "type": "about:blank",
+        "status": status.as_u16(),
+    }))
+}
+
+fn v1_error(status: StatusCode, code: i32, message: &str) -> Response<Body> {
+    json_response(status, json!({"errors": [{"code": code, "message": message}]}))
+}
+
+fn unauthorized() -> Response<Body> {
+    problem(StatusCode::UNAUTHORIZED, "Unauthorized", "Unauthorized")
+}
+
+fn not_found(kind: &str, parameter: &str, value: &str) -> Value {
+    json!({
+        "value": value,
+        "detail": format!("Could not find {} with {}: [{}].", kind, parameter, value),
+        "title": "Not Found Error",
+        "resource_type": kind,
+        "parameter": parameter,
+        "resource_id": value,
+        "type": "https://api.twitter.com/2/problems/resource-not-found",
+    })
+}
+
+fn with_window(mut response: Response<Body>, window: Window) -> Response<Body> {
+    let headers = response.headers_mut();
+    headers.insert("x-rate-limit-limit", window.limit.into());
+    headers.insert("x-rate-limit-remaining", window.remaining.into());
+    headers.insert("x-rate-limit-reset", window.reset.into());
+    response
+}
+
+
+/// Where the fake api should listen for `TWITTER_API` (e.g. `http://127.0.0.1:8181`)
+pub fn address(twitter_api: &str) -> Option<SocketAddr> {
+    let authority = twitter_api.split("://").last()?.split('/').next()?;
+    authority.parse().ok()
+}
+
+
+/// A stateful stand-in for api.twitter.com. It covers the endpoints twitar calls: the OAuth1 and OAuth2 flows,
+/// user lookup, timelines, likes and the tweet, retweet and like deletions, with Twitter's errors and rate limit headers
+#[derive(Debug, Clone)]
+pub struct FakeTwitter {
+    state: Arc<Mutex<FakeState>>,
+    /// How long every request takes to be answered
+    latency: Option<Duration>,
+}
+
+impl Default for FakeTwitter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FakeTwitter {
+    pub fn new() -> Self {
+        Self::with_state(FakeState::seeded())
+    }
+
+    pub fn with_state(state: FakeState) -> Self {
+        Self { state: Arc::new(Mutex::new(state)), latency: None }
+    }
+
+    /// Answers every request after `latency`, to keep calls in flight
+    pub fn with_latency(self, latency: Duration) -> Self {
+        Self { latency: Some(latency), ..self }
+    }
+
+    /// The state behind the api, to seed it further or check what a flow did
+    pub fn state(&self) -> Arc<Mutex<FakeState>> {
+        self.state.clone()
+    }
+
+    pub async fn serve(self, listener: TcpListener) -> hyper::Result<()> {
+        let service = make_service_fn(move |_| {
+            let fake = self.clone();
+
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req| {
+                    let fake = fake.clone();
+                    async move { Ok::<_, Infallible>(fake.handle(req).await) }
+                }))
+            }
+        });
+
+        Server::from_tcp(listener)?.serve(service).await
+    }
+
+    /// Serves on a random local port in the background, and returns the url to use as `TWITTER_API`
+    pub fn spawn(self) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind the fake Twitter api");
+        let address = listener.local_addr().unwrap();
+
+        tokio::spawn(self.serve(listener));
+
+        format!("http://{}", address)
+    }
+
+    pub async fn handle(&self, req: Request<Body>) -> Response<Body> {
+        let (parts, body) = req.into_parts();
+        let body = hyper::body::to_bytes(body).await.map(|b| b.to_vec()).unwrap_or_default();
+
+        let query = parts.uri.query().map(|q| form(q.as_bytes())).unwrap_or_default();
+        let authorization = parts.headers.get("authorization")
+            .and_then(|h| h.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+
+        let path = parts.uri.path().trim_matches('/').to_string();
+        let segments = path.split('/').collect::<Vec<_>>();
+
+        if let Some(latency) = self.latency {
+            tokio::time::sleep(latency).await;
+        }
+
+        let mut state = self.state.lock().unwrap();
+
+        match (&parts.method, segments.as_slice()) {
+            (&Method::POST, ["oauth", "request_token"]) => request_token(&mut state, &authorization, &query),
+            (&Method::GET, ["oauth", "authorize"]) => authorize(&state, &query),
+            (&Method::POST, ["oauth", "access_token"]) => access_token(&mut state, &authorization, &query),
+            (&Method::GET, ["i", "oauth2", "authorize"]) => oauth2_authorize(&query),
+            (&Method::POST, ["2", "oauth2", "token"]) => oauth2_token(&mut state, &authorization, &form(&body)),
+            (&Method::POST, ["2", "oauth2", "revoke"]) => oauth2_revoke(&mut state, &authorization, &form(&body)),
+            (&Method::POST, ["1.1", "statuses", "unretweet", file]) => {
+                let user_id = match oauth1_user(&state, &authorization) {
+                    Some(user_id) => user_id,
+                    None => return v1_error(StatusCode::UNAUTHORIZED, 32, "Could not authenticate you."),
+                };
+
+                let (window, allowed) = state.hit("POST /1.1/statuses/unretweet", &user_id, DELETE_LIMIT);
+                if !allowed {
+                    return with_window(v1_error(StatusCode::TOO_MANY_REQUESTS, 88, "Rate limit exceeded"), window);
+                }
+
+                with_window(unretweet(&mut state, &user_id, file.trim_end_matches(".json")), window)
+            }
+            (method, ["2", ..]) => {
+                let user_id = match bearer_user(&state, &authorization) {
+                    Some(user_id) => user_id,
+                    None => return unauthorized(),
+                };
+
+                let (endpoint, limit) = match (method, &segments[1..]) {
+                    (&Method::GET, ["users", _, "tweets"]) => ("GET /2/users/:id/tweets", TIMELINE_LIMIT),
+                    (&Method::GET, ["users", _, "liked_tweets"]) => ("GET /2/users/:id/liked_tweets", LIKES_LIMIT),
+                    (&Method::DELETE, ["tweets", _]) => ("DELETE /2/tweets/:id", DELETE_LIMIT),
+                    (&Method::DELETE, ["users", _, "likes", _]) => ("DELETE /2/users/:id/likes/:tweet_id", DELETE_LIMIT),
+                    _ => ("GET /2/lookup", LOOKUP_LIMIT),
+                };
+
+                let (window, allowed) = state.hit(endpoint, &user_id, limit);
+                if !allowed {
+                    return with_window(problem(StatusCode::TOO_MANY_REQUESTS, "Too Many Requests", "Too Many Requests"), window);
+                }
+
+                with_window(v2(&mut state, method, &segments[1..], &user_id, &query), window)
+            }
+            _ => problem(StatusCode::NOT_FOUND, "Not Found Error", &format!("No route for {} /{}", parts.method, path)),
+        }
+    }
+}
+
+
+fn bearer_user(state: &FakeState, authorization: &str) -> Option<String> {
+    let token = authorization.strip_prefix("Bearer ")?;
+    state.access_tokens.get(token).cloned()
+}
+
+fn oauth1_user(state: &FakeState, authorization: &str) -> Option<String> {
+    if !authorization.starts_with("OAuth ") {
+        return None;
+    }
+
+    let params = SignedParams::parse(authorization);
+    params.get("oauth_signature")?;
+
+    let token = params.get("oauth_token")?;
+    state.oauth_tokens.get(token).map(|(_, user_id)| user_id.clone())
+}
+
+fn has_client_credentials(authorization: &str) -> bool {
+    matches!(authorization.strip_prefix("Basic "), Some(credentials) if !credentials.is_empty())
+}
+
+
+fn request_token(state: &mut FakeState, authorization: &str, query: &Form) -> Response<Body> {
+    let params = SignedParams::parse(authorization);
+
+    if params.get("oauth_consumer_key").is_none() || params.get("oauth_signature").is_none() {
+        return v1_error(StatusCode::UNAUTHORIZED, 32, "Could not authenticate you.");
+    }
+
+    let callback = params.get("oauth_callback").map(|c| c.to_string())
+        .or_else(|| query.get("oauth_callback").cloned())
+        .unwrap_or_default();
+
+    let (token, secret) = state.issue_request_token(callback);
+    form_response(format!("oauth_token={}&oauth_token_secret={}&oauth_callback_confirmed=true", token, secret))
+}
+
+/// Stands in for the consent page: the user accepts right away and is sent back to the callback
+fn authorize(state: &FakeState, query: &Form) -> Response<Body> {
+    let token = query.get("oauth_token").cloned().unwrap_or_default();
+
+    match state.request_tokens.get(&token) {
+        Some((_, callback, verifier)) => Response::builder()
+            .status(StatusCode::FOUND)
+            .header("location", format!("{}?oauth_token={}&oauth_verifier={}", callback, token, verifier))
+            .body(Body::empty())
+            .unwrap(),
+        None => v1_error(StatusCode::UNAUTHORIZED, 89, "Invalid or expired token."),
+    }
+}
+
+/// The user always consents. Any code is accepted by the token endpoint
+fn oauth2_authorize(query: &Form) -> Response<Body> {
+    let redirect_uri = match query.get("redirect_uri").filter(|uri| !uri.is_empty()) {
+        Some(uri) => uri,
+        None => return problem(StatusCode::BAD_REQUEST, "Invalid Request", "redirect_uri is required"),
+    };
+
+    let params = form_urlencoded::Serializer::new(String::new())
+        .append_pair("state", query.get("state").map(String::as_str).unwrap_or_default())
+        .append_pair("code", "fake-authorization-code")
+        .finish();
+
+    Response::builder()
+        .status(StatusCode::FOUND)
+        .header("location", format!("{}?{}", redirect_uri, params))
+        .body(Body::empty())
+        .unwrap()
+}
+
+fn access_token(state: &mut FakeState, authorization: &str, query: &Form) -> Response<Body> {
+    let params = SignedParams::parse(authorization);
+    let param = |key: &str| params.get(key).map(|v| v.to_string()).or_else(|| query.get(key).cloned()).unwrap_or_default();
+
+    let (token, verifier) = (param("oauth_token"), param("oauth_verifier"));
+
+    let (user_id, username) = match (state.request_tokens.get(&token), state.users.first()) {
+        (Some((_, _, expected)), Some(user)) if *expected == verifier => (user.id.clone(), user.username.clone()),
+        _ => return v1_error(StatusCode::UNAUTHORIZED, 89, "Invalid or expired token."),
+    };
+
+    state.request_tokens.remove(&token);
+    let (oauth_token, secret) = state.issue_request_token(String::new());
+    state.request_tokens.remove(&oauth_token);
+    state.oauth_tokens.insert(oauth_token.clone(), (secret.clone(), user_id.clone()));
+
+    form_response(format!("oauth_token={}&oauth_token_secret={}&user_id={}&screen_name={}", oauth_token, secret, user_id, username))
+}
+
+fn oauth2_token(state: &mut FakeState, authorization: &str, form: &Form) -> Response<Body> {
+    if !has_client_credentials(authorization) {
+        return problem(StatusCode::UNAUTHORIZED, "Unauthorized", "Missing client credentials");
+    }
+
+    let invalid = || json_response(StatusCode::BAD_REQUEST, json!({
+        "error": "invalid_request",
+        "error_description": "Value passed for the authorization code was invalid.",
+    }));
+
+    let user_id = match form.get("grant_type").map(|g| g.as_str()) {
+        Some("authorization_code") => match (form.get("code"), state.users.first()) {
+            (Some(code), Some(user)) if !code.is_empty() => user.id.clone(),
+            _ => return invalid(),
+        },
+        Some("refresh_token") => match form.get("refresh_token").and_then(|t| state.refresh_tokens.remove(t)) {
+            Some(user_id) => user_id,
+            None => return invalid(),
+        },
+        _ => return invalid(),
+    };
+
+    let (access_token, refresh_token) = state.issue_tokens(&user_id);
+
+    json_response(StatusCode::OK, json!({
+        "token_type": "bearer",
+        "expires_in": 7200,
+        "access_token": access_token,
+        "scope": "tweet.read users.read like.read like.write offline.access tweet.write",
+        "refresh_token": refresh_token,
+    }))
+}
+
+fn oauth2_revoke(state: &mut FakeState, authorization: &str, form: &Form) -> Response<Body> {
+    if !has_client_credentials(authorization) {
+        return problem(StatusCode::UNAUTHORIZED, "Unauthorized", "Missing client credentials");
+    }
+
+    if let Some(token) = form.get("token") {
+        state.access_tokens.remove(token);
+        state.refresh_tokens.remove(token);
+    }
+
+    json_response(StatusCode::OK, json!({"revoked": true}))
+}
+
+
+fn unretweet(state: &mut FakeState, user_id: &str, id: &str) -> Response<Body> {
+    // Twitter accepts the id of the retweet or of the tweet that was retweeted
+    let retweet = state.tweets.iter()
+        .find(|t| t.author_id == user_id && t.retweet_of.is_some() && (t.id == id || t.retweet_of.as_deref() == Some(id)))
+        .map(|t| t.id.clone());
+
+    match retweet.and_then(|id| state.remove_tweet(&id)) {
+        Some(retweet) => {
+            let source = retweet.retweet_of.unwrap_or_default();
+            json_response(StatusCode::OK, json!({
+                "id_str": source,
+                "text": retweet.text,
+                "retweeted": false,
+                "user": {"id_str": user_id},
+            }))
+        }
+        None => v1_error(StatusCode::NOT_FOUND, 144, "No status found with that ID."),
+    }
+}
+
+
+/// The v2 endpoints, once the bearer token is known to belong to `user_id`
+fn v2(state: &mut FakeState, method: &Method, segments: &[&str], user_id: &str, query: &Form) -> Response<Body> {
+    let fields = query.get("tweet.fields").map(|f| f.split(',').collect::<Vec<_>>()).unwrap_or_default();
+
+    match (method, segments) {
+        (&Method::GET, ["users", "me"]) => {
+            let user = state.user(user_id).map(|u| u.to_json());
+            json_response(StatusCode::OK, json!({"data": user}))
+        }
+        (&Method::GET, ["users", "by", "username", username]) => match state.user_by_username(username) {
+            Some(user) => json_response(StatusCode::OK, json!({"data": user.to_json()})),
+            None => json_response(StatusCode::OK, json!({"errors": [not_found("user", "username", username)]})),
+        },
+        (&Method::GET, ["users", id, "tweets"]) => match state.user(id) {
+            Some(_) => page(state.timeline(id), &fields, query, 5..=100),
+            None => json_response(StatusCode::OK, json!({"errors": [not_found("user", "id", id)]})),
+        },
+        (&Method::GET, ["users", id, "liked_tweets"]) => match state.user(id) {
+            Some(_) => page(state.liked_tweets(id), &fields, query, 10..=100),
+            None => json_response(StatusCode::OK, json!({"errors": [not_found("user", "id", id)]})),
+        },
+        (&Method::GET, ["tweets"]) => {
+            let ids = query.get("ids").map(|ids| ids.split(',').collect::<Vec<_>>()).unwrap_or_default();
+
+            if ids.is_empty() || ids.len() > 100 {
+                return problem(StatusCode::BAD_REQUEST, "Invalid Request", "The `ids` query parameter needs between 1 and 100 ids.");
+            }
+
+            let data = ids.iter().filter_map(|id| state.tweet(id)).map(|t| t.to_json(&fields)).collect::<Vec<_>>();
+            let errors = ids.iter().filter(|id| state.tweet(id).is_none()).map(|id| not_found("tweet", "ids", id)).collect::<Vec<_>>();
+
+            let expanded = query.get("expansions").into_iter().flat_map(|e| e.split(',')).any(|e| e == "referenced_tweets.id");
+            let referenced = ids.iter()
+                .filter_map(|id| state.tweet(id).and_then(|t| t.retweet_of.as_deref()))
+                .filter_map(|id| state.tweet(id)).map(|t| t.to_json(&fields))
+                .collect::<Vec<_>>();
+
+            let mut body = json!({});
+            if !data.is_empty() {
+                body["data"] = json!(data);
+            }
+            if expanded && !referenced.is_empty() {
+                body["includes"] = json!({"tweets": referenced});
+            }
+            if !errors.is_empty() {
+                body["errors"] = json!(errors);
+            }
+
+            json_response(StatusCode::OK, body)
+        }
+        (&Method::DELETE, ["tweets", id]) => match state.tweet(id).map(|t| t.author_id == user_id) {
+            Some(true) => {
+                state.remove_tweet(id);
+                json_response(StatusCode::OK, json!({"data": {"deleted": true}}))
+            }
+            Some(false) => problem(StatusCode::FORBIDDEN, "Forbidden", "You are not allowed to delete this Tweet."),
+            None => json_response(StatusCode::OK, json!({"data": {"deleted": false}})),
+        },
+        (&Method::DELETE, ["users", id, "likes", tweet_id]) => {
+            if *id != user_id {
+                return problem(StatusCode::FORBIDDEN, "Forbidden", "You are not permitted to perform this action.");
+            }
+
+            state.remove_like(id, tweet_id);
+            json_response(StatusCode::OK, json!({"data": {"liked": false}}))
+        }
+        _ => problem(StatusCode::NOT_FOUND, "Not Found Error", &format!("No route for {} /2/{}", method, segments.join("/"))),
+    }
+}
+
+/// A page of tweets. `pagination_token` is the offset of the page, which real clients treat as opaque.
+/// `sizes` are the `max_results` the endpoint accepts
+fn page(tweets: Vec<&FakeTweet>, fields: &[&str], query: &Form, sizes: RangeInclusive<usize>) -> Response<Body> {
+    let size = match query.get("max_results").map(|m| m.parse::<usize>()) {
+        None => DEFAULT_PAGE_SIZE,
+        Some(Ok(size)) if sizes.contains(&size) => size,
+        Some(_) => return json_response(StatusCode::BAD_REQUEST, json!({
+            "errors": [{
+                "parameters": {"max_results": [query.get("max_results")]},
+                "message": format!("The `max_results` query parameter value is not between {} and {}", sizes.start(), sizes.end()),
+            }],
+            "title": "Invalid Request",
+            "detail": "One or more parameters to your request was invalid.",
+            "type": "https://api.twitter.com/2/problems/invalid-request",
+        })),
+    };
+
+    let offset = query.get("pagination_token").and_then(|t| t.parse::<usize>().ok()).unwrap_or_default();
+    let items = tweets.iter().skip(offset).take(size).collect::<Vec<_>>();
+
+    let mut meta = json!({"result_count": items.len()});
+    if let (Some(newest), Some(oldest)) = (items.first(), items.last()) {
+        meta["newest_id"] = json!(newest.id);
+        meta["oldest_id"] = json!(oldest.id);
+    }
+    if offset + size < tweets.len() {
+        meta["next_token"] = json!((offset + size).to_string());
+    }
+    if offset > 0 {
+        meta["previous_token"] = json!(offset.saturating_sub(size).to_string());
+    }
+
+    let mut body = json!({"meta": meta});
+    if !items.is_empty() {
+        body["data"] = json!(items.iter().map(|t| t.to_json(fields)).collect::<Vec<_>>());
+    }
+
+    json_response(StatusCode::OK, body)
+}
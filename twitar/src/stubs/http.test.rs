@@ -0,0 +1,181 @@
+#[cfg(test)]
+mod test_http {
+    use futures::TryStreamExt;
+    use hyper::{Body, Method, StatusCode};
+    use serde_json::Value;
+
+    use crate::errors::response::TError;
+    use crate::errors::twitter_errors::TwitterApiError;
+    use crate::helpers::keyval::KeyVal;
+    use crate::helpers::keypair::KeyPair;
+    use crate::helpers::paginator::Paginator;
+    use crate::helpers::query::{V2Query, TweetField};
+    use crate::helpers::response::make_request;
+    use crate::helpers::signature::{OAuth, OAuthAddons};
+    use crate::helpers::transport::{HyperTransport, Transport};
+    use crate::middlewares::request_builder::{RequestBuilder, AuthType};
+    use crate::models::{Tweet, User};
+    use crate::stubs::http::address;
+    use crate::stubs::state::{SEED_ACCESS_TOKEN, SEED_OAUTH_SECRET, SEED_OAUTH_TOKEN, SEED_USER_ID};
+    use crate::stubs::{FakeState, FakeTwitter};
+
+    fn get(url: String) -> RequestBuilder {
+        RequestBuilder::new(Method::GET, url).with_auth(AuthType::Bearer, SEED_ACCESS_TOKEN.into())
+    }
+
+    async fn json(request: RequestBuilder) -> Value {
+        let (_, body) = make_request(request.build_request(), HyperTransport::client()).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[test]
+    fn reads_the_address_from_twitter_api() {
+        assert_eq!(address("http://127.0.0.1:8181").unwrap().port(), 8181);
+        assert_eq!(address("http://0.0.0.0:9000/").unwrap().port(), 9000);
+        assert!(address("https://api.twitter.com").is_none());
+    }
+
+    #[tokio::test]
+    async fn pages_through_the_timeline() {
+        let api = FakeTwitter::new().spawn();
+
+        let tweets: Vec<Tweet> = Paginator::new(HyperTransport::client(), format!("{}/2/users/{}/tweets", api, SEED_USER_ID), SEED_ACCESS_TOKEN.into())
+            .with_query(V2Query::new().tweet_fields([TweetField::ReferencedTweets]))
+            .page_size(7)
+            .items().try_collect().await.unwrap();
+
+        assert_eq!(tweets.len(), 30);
+        assert_eq!(tweets.iter().filter(|t| t.is_retweet()).count(), 10);
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_tokens() {
+        let api = FakeTwitter::new().spawn();
+        let request = RequestBuilder::new(Method::GET, format!("{}/2/users/me", api))
+            .with_auth(AuthType::Bearer, "not-a-token".into()).build_request();
+
+        let response = make_request(request, HyperTransport::client()).await;
+
+        assert!(matches!(response, Err(TError::TwitterError(e)) if matches!(&e.1, TwitterApiError::V2(p) if p.status == Some(401))));
+    }
+
+    #[tokio::test]
+    async fn sends_rate_limit_headers_and_enforces_them() {
+        let api = FakeTwitter::with_state(FakeState::seeded().with_window_limit(1)).spawn();
+        let url = format!("{}/2/users/{}/liked_tweets", api, SEED_USER_ID);
+
+        let (headers, _) = make_request(get(url.clone()).build_request(), HyperTransport::client()).await.unwrap();
+        assert_eq!(headers["x-rate-limit-remaining"], "0");
+
+        let response = make_request(get(url).build_request(), HyperTransport::client()).await;
+        assert!(matches!(response, Err(TError::RateLimit(Some(_)))));
+    }
+
+    #[tokio::test]
+    async fn looks_up_users_and_reports_missing_tweets() {
+        let api = FakeTwitter::new().spawn();
+
+        let body = json(get(format!("{}/2/users/by/username/twitterdev", api))).await;
+        let user: User = serde_json::from_value(body["data"].clone()).unwrap();
+        assert_eq!(user.id, SEED_USER_ID);
+
+        let body = json(get(format!("{}/2/tweets", api)).with_query("ids", "1500000000000000001,1")).await;
+        assert_eq!(body["data"].as_array().unwrap().len(), 1);
+        assert_eq!(body["errors"][0]["resource_id"], "1");
+    }
+
+    #[tokio::test]
+    async fn deletes_tweets_and_likes() {
+        let fake = FakeTwitter::new();
+        let state = fake.state();
+        let api = fake.spawn();
+
+        let delete = |url: String| RequestBuilder::new(Method::DELETE, url).with_auth(AuthType::Bearer, SEED_ACCESS_TOKEN.into());
+
+        let body = json(delete(format!("{}/2/tweets/1510000000000000001", api))).await;
+        assert_eq!(body["data"]["deleted"], true);
+
+        let body = json(delete(format!("{}/2/users/{}/likes/1500000000000000014", api, SEED_USER_ID))).await;
+        assert_eq!(body["data"]["liked"], false);
+
+        {
+            let state = state.lock().unwrap();
+            assert!(state.tweet("1510000000000000001").is_none());
+            assert_eq!(state.liked_tweets(SEED_USER_ID).len(), 14);
+        }
+
+        let response = make_request(delete(format!("{}/2/tweets/1500000000000000001", api)).build_request(), HyperTransport::client()).await;
+        assert!(matches!(response, Err(TError::TwitterError(e)) if matches!(&e.1, TwitterApiError::V2(p) if p.status == Some(403))));
+    }
+
+    #[tokio::test]
+    async fn runs_the_oauth1_flow_then_unretweets() {
+        let api = FakeTwitter::new().spawn();
+        let consumer = KeyPair::new("consumer".into(), "consumer-secret".into());
+
+        let target = format!("{}/oauth/request_token", api);
+        let callback = OAuthAddons::Callback("http://localhost:8080/oauth/callback".into());
+        let signature = OAuth::new(consumer.clone(), None, callback, Method::POST).generate_signature(target.clone());
+        let request = RequestBuilder::new(Method::POST, target).with_auth(AuthType::OAuth, signature.to_string());
+        let (_, body) = make_request(request.build_request(), HyperTransport::client()).await.unwrap();
+        let tokens = KeyVal::string_to_keyval(String::from_utf8_lossy(&body).to_string()).unwrap();
+        assert_eq!(tokens.get("oauth_callback_confirmed").unwrap(), "true");
+
+        let authorize = RequestBuilder::new(Method::GET, format!("{}/oauth/authorize", api))
+            .with_query("oauth_token", tokens.get("oauth_token").unwrap()).build_request();
+        let redirect = HyperTransport::new().send(authorize).await.unwrap();
+        assert_eq!(redirect.status(), StatusCode::FOUND);
+        let location = redirect.headers()["location"].to_str().unwrap().to_string();
+        let verifier = location.split("oauth_verifier=").last().unwrap();
+
+        let request = RequestBuilder::new(Method::POST, format!("{}/oauth/access_token", api))
+            .with_query("oauth_token", tokens.get("oauth_token").unwrap())
+            .with_query("oauth_verifier", verifier);
+        let (_, body) = make_request(request.build_request(), HyperTransport::client()).await.unwrap();
+        let access = KeyVal::string_to_keyval(String::from_utf8_lossy(&body).to_string()).unwrap();
+        assert_eq!(access.get("user_id").unwrap(), SEED_USER_ID);
+
+        let unretweet = |token: KeyPair| {
+            let target = format!("{}/1.1/statuses/unretweet/1510000000000000000.json", api);
+            let signature = OAuth::new(consumer.clone(), Some(token), OAuthAddons::None, Method::POST).generate_signature(target.clone());
+            RequestBuilder::new(Method::POST, target).with_auth(AuthType::OAuth, signature.to_string()).build_request()
+        };
+
+        let token = KeyPair::new(access.get("oauth_token").unwrap().to_string(), access.get("oauth_token_secret").unwrap().to_string());
+        assert!(make_request(unretweet(token), HyperTransport::client()).await.is_ok());
+
+        let seeded = KeyPair::new(SEED_OAUTH_TOKEN.into(), SEED_OAUTH_SECRET.into());
+        let response = make_request(unretweet(seeded), HyperTransport::client()).await;
+        assert!(matches!(response, Err(TError::TwitterError(e)) if matches!(&e.1, TwitterApiError::V1(e) if e.errors[0].code == 144)));
+    }
+
+    #[tokio::test]
+    async fn issues_refreshes_and_revokes_oauth2_tokens() {
+        let api = FakeTwitter::new().spawn();
+
+        let token_request = |form: KeyVal| RequestBuilder::new(Method::POST, format!("{}/2/oauth2/token", api))
+            .with_auth(AuthType::Basic, "client:secret".into())
+            .with_body(Body::from(form.to_urlencode()), "application/x-www-form-urlencoded");
+
+        let body = json(token_request(KeyVal::new().add_list_keyval(vec![
+            ("grant_type".into(), "authorization_code".into()),
+            ("code".into(), "code".into()),
+        ]))).await;
+
+        let body = json(token_request(KeyVal::new().add_list_keyval(vec![
+            ("grant_type".into(), "refresh_token".into()),
+            ("refresh_token".into(), body["refresh_token"].as_str().unwrap().into()),
+        ]))).await;
+        let access_token = body["access_token"].as_str().unwrap().to_string();
+
+        let me = || RequestBuilder::new(Method::GET, format!("{}/2/users/me", api)).with_auth(AuthType::Bearer, access_token.clone());
+        assert_eq!(json(me()).await["data"]["id"], SEED_USER_ID);
+
+        let revoke = RequestBuilder::new(Method::POST, format!("{}/2/oauth2/revoke", api))
+            .with_auth(AuthType::Basic, "client:secret".into())
+            .with_body(Body::from(format!("token={}", access_token)), "application/x-www-form-urlencoded");
+        json(revoke).await;
+
+        assert!(make_request(me().build_request(), HyperTransport::client()).await.is_err());
+    }
+}
@@ -0,0 +1,233 @@
+use std::{collections::HashMap, time::{SystemTime, UNIX_EPOCH}};
+use chrono::{Duration, TimeZone, Utc};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+
+/// Twitter's rate limit windows last 15 minutes
+const WINDOW_SECS: u64 = 15 * 60;
+
+/// Credentials of the seeded user, so requests can be made without going through the OAuth flows first
+pub const SEED_USER_ID: &str = "2244994945";
+pub const SEED_USERNAME: &str = "TwitterDev";
+pub const SEED_ACCESS_TOKEN: &str = "fake-access-token";
+pub const SEED_REFRESH_TOKEN: &str = "fake-refresh-token";
+pub const SEED_OAUTH_TOKEN: &str = "fake-oauth-token";
+pub const SEED_OAUTH_SECRET: &str = "fake-oauth-secret";
+
+const OTHER_USER_ID: &str = "783214";
+
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+fn token() -> String {
+    Uuid::new_v4().to_simple().to_string()
+}
+
+
+#[derive(Debug, Clone)]
+pub struct FakeUser {
+    pub id: String,
+    pub name: String,
+    pub username: String,
+}
+
+impl FakeUser {
+    pub fn to_json(&self) -> Value {
+        json!({"id": self.id, "name": self.name, "username": self.username})
+    }
+}
+
+
+#[derive(Debug, Clone)]
+pub struct FakeTweet {
+    pub id: String,
+    pub text: String,
+    pub author_id: String,
+    pub created_at: String,
+    /// The id of the retweeted tweet when this is a retweet
+    pub retweet_of: Option<String>,
+}
+
+impl FakeTweet {
+    /// Only `id` and `text` are returned unless more fields are requested, the way the v2 api does
+    pub fn to_json(&self, fields: &[&str]) -> Value {
+        let mut tweet = json!({"id": self.id, "text": self.text});
+
+        if fields.contains(&"author_id") {
+            tweet["author_id"] = json!(self.author_id);
+        }
+
+        if fields.contains(&"created_at") {
+            tweet["created_at"] = json!(self.created_at);
+        }
+
+        if let (true, Some(source)) = (fields.contains(&"referenced_tweets"), &self.retweet_of) {
+            tweet["referenced_tweets"] = json!([{"type": "retweeted", "id": source}]);
+        }
+
+        tweet
+    }
+}
+
+
+#[derive(Debug, Clone, Copy)]
+pub struct Window {
+    pub limit: u32,
+    pub remaining: u32,
+    pub reset: u64,
+}
+
+
+/// Everything the fake api knows about. Deleting a tweet or a like removes it for good,
+/// so a flow can be replayed against the state it left behind
+#[derive(Debug, Default)]
+pub struct FakeState {
+    pub users: Vec<FakeUser>,
+    pub tweets: Vec<FakeTweet>,
+    /// The ids of the tweets each user liked, most recent first
+    pub likes: HashMap<String, Vec<String>>,
+    /// OAuth2 access tokens, with the user they belong to
+    pub access_tokens: HashMap<String, String>,
+    pub refresh_tokens: HashMap<String, String>,
+    /// OAuth1 request tokens: (secret, callback, verifier)
+    pub request_tokens: HashMap<String, (String, String, String)>,
+    /// OAuth1 access tokens: (secret, user id)
+    pub oauth_tokens: HashMap<String, (String, String)>,
+    windows: HashMap<String, Window>,
+    /// Overrides the limit of every endpoint
+    window_limit: Option<u32>,
+}
+
+impl FakeState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Two users, tweets and retweets for the first one and likes of the second one's tweets
+    pub fn seeded() -> Self {
+        let mut state = Self::new();
+
+        state.users.push(FakeUser { id: SEED_USER_ID.into(), name: "Twitter Dev".into(), username: SEED_USERNAME.into() });
+        state.users.push(FakeUser { id: OTHER_USER_ID.into(), name: "Twitter".into(), username: "Twitter".into() });
+
+        let start = Utc.ymd(2022, 4, 1).and_hms(9, 0, 0);
+
+        for n in 0..20u64 {
+            state.tweets.push(FakeTweet {
+                id: (1500000000000000000 + n).to_string(),
+                text: format!("A tweet from Twitter #{}", n),
+                author_id: OTHER_USER_ID.into(),
+                created_at: (start + Duration::minutes(n as i64)).to_rfc3339(),
+                retweet_of: None,
+            });
+        }
+
+        // every third tweet of the seeded user is a retweet
+        for n in 0..30u64 {
+            let retweet_of = (n % 3 == 0).then(|| (1500000000000000000 + n % 20).to_string());
+            let text = match &retweet_of {
+                Some(_) => format!("RT @Twitter: A tweet from Twitter #{}", n % 20),
+                None => format!("A tweet from Twitter Dev #{}", n),
+            };
+
+            state.tweets.push(FakeTweet {
+                id: (1510000000000000000 + n).to_string(),
+                text,
+                author_id: SEED_USER_ID.into(),
+                created_at: (start + Duration::hours(1 + n as i64)).to_rfc3339(),
+                retweet_of,
+            });
+        }
+
+        let likes = (0..15u64).rev().map(|n| (1500000000000000000 + n).to_string()).collect();
+        state.likes.insert(SEED_USER_ID.into(), likes);
+
+        state.access_tokens.insert(SEED_ACCESS_TOKEN.into(), SEED_USER_ID.into());
+        state.refresh_tokens.insert(SEED_REFRESH_TOKEN.into(), SEED_USER_ID.into());
+        state.oauth_tokens.insert(SEED_OAUTH_TOKEN.into(), (SEED_OAUTH_SECRET.into(), SEED_USER_ID.into()));
+
+        state
+    }
+
+    pub fn with_window_limit(self, limit: u32) -> Self {
+        Self { window_limit: Some(limit), ..self }
+    }
+
+    pub fn user(&self, id: &str) -> Option<&FakeUser> {
+        self.users.iter().find(|u| u.id == id)
+    }
+
+    pub fn user_by_username(&self, username: &str) -> Option<&FakeUser> {
+        self.users.iter().find(|u| u.username.eq_ignore_ascii_case(username))
+    }
+
+    pub fn tweet(&self, id: &str) -> Option<&FakeTweet> {
+        self.tweets.iter().find(|t| t.id == id)
+    }
+
+    /// The tweets (and retweets) of a user, most recent first
+    pub fn timeline(&self, user_id: &str) -> Vec<&FakeTweet> {
+        let mut tweets = self.tweets.iter().filter(|t| t.author_id == user_id).collect::<Vec<_>>();
+        tweets.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        tweets
+    }
+
+    pub fn liked_tweets(&self, user_id: &str) -> Vec<&FakeTweet> {
+        self.likes.get(user_id).into_iter().flatten()
+            .filter_map(|id| self.tweet(id))
+            .collect()
+    }
+
+    pub fn remove_tweet(&mut self, id: &str) -> Option<FakeTweet> {
+        let index = self.tweets.iter().position(|t| t.id == id)?;
+        Some(self.tweets.remove(index))
+    }
+
+    pub fn remove_like(&mut self, user_id: &str, tweet_id: &str) -> bool {
+        match self.likes.get_mut(user_id) {
+            Some(likes) => {
+                let count = likes.len();
+                likes.retain(|id| id != tweet_id);
+                likes.len() != count
+            }
+            None => false,
+        }
+    }
+
+    pub fn issue_tokens(&mut self, user_id: &str) -> (String, String) {
+        let (access, refresh) = (token(), token());
+        self.access_tokens.insert(access.clone(), user_id.into());
+        self.refresh_tokens.insert(refresh.clone(), user_id.into());
+        (access, refresh)
+    }
+
+    pub fn issue_request_token(&mut self, callback: String) -> (String, String) {
+        let (request_token, secret) = (token(), token());
+        self.request_tokens.insert(request_token.clone(), (secret.clone(), callback, token()));
+        (request_token, secret)
+    }
+
+    /// Uses up one request of the `endpoint` window of `credential`.
+    /// Returns the window after the request, and whether the request is allowed
+    pub fn hit(&mut self, endpoint: &str, credential: &str, limit: u32) -> (Window, bool) {
+        let limit = self.window_limit.unwrap_or(limit);
+        let now = now();
+
+        let window = self.windows.entry(format!("{} {}", endpoint, credential))
+            .or_insert(Window { limit, remaining: limit, reset: now + WINDOW_SECS });
+
+        if window.reset <= now {
+            *window = Window { limit, remaining: limit, reset: now + WINDOW_SECS };
+        }
+
+        if window.remaining == 0 {
+            return (*window, false);
+        }
+
+        window.remaining -= 1;
+        (*window, true)
+    }
+}
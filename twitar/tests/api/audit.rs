@@ -0,0 +1,77 @@
+use serde_json::json;
+
+use twitar::stubs::FakeTwitter;
+use twitar::stubs::state::FakeState;
+
+use crate::helpers::app::{json, spawn_app, spawn_app_with};
+
+
+#[tokio::test]
+async fn every_removal_is_audited_including_the_deferred_ones() {
+    let app = spawn_app_with(FakeTwitter::with_state(FakeState::seeded().with_window_limit(3))).await;
+
+    let ids = (1..=5u64).map(|n| (1510000000000000000 + n).to_string()).collect::<Vec<_>>();
+    let response = app.post(&format!("/remove?user_id={}", app.users.connected), json!({ "tweets": ids, "rts": [] })).await;
+    assert_eq!(response.status().as_u16(), 429);
+
+    let response = app.get(&format!("/audit?user_id={}&action=delete", app.users.connected)).await;
+    assert!(response.status().is_success());
+
+    let entries = json(response).await["body"].as_array().unwrap().clone();
+    let outcomes = |outcome: &str| entries.iter().filter(|e| e["outcome"] == outcome).count();
+
+    assert_eq!(entries.len(), 5);
+    assert_eq!((outcomes("success"), outcomes("deferred")), (3, 2));
+    assert!(entries.iter().all(|e| ids.contains(&e["target"].as_str().unwrap().to_string())));
+    assert!(entries.iter().filter(|e| e["outcome"] == "deferred").all(|e| e["detail"]["reason"] == "rate_limited"));
+}
+
+#[tokio::test]
+async fn the_audit_log_is_exported_as_csv() {
+    let app = spawn_app().await;
+
+    let response = app.post(&format!("/remove?user_id={}", app.users.connected), json!({ "tweets": ["1510000000000000001"], "rts": [] })).await;
+    assert!(response.status().is_success());
+
+    let response = app.get(&format!("/audit?user_id={}&format=csv", app.users.connected)).await;
+    assert_eq!(response.headers()["content-type"], "text/csv");
+    assert!(response.headers()["content-disposition"].to_str().unwrap().starts_with("attachment"));
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let csv = String::from_utf8(body.to_vec()).unwrap();
+    let lines = csv.lines().collect::<Vec<_>>();
+
+    assert_eq!(lines.len(), 2);
+    assert!(lines[1].contains(",delete,success,1510000000000000001,"));
+}
+
+#[tokio::test]
+async fn the_audit_log_rejects_unknown_actions() {
+    let app = spawn_app().await;
+
+    let response = app.get(&format!("/audit?user_id={}&action=drop_table", app.users.connected)).await;
+    assert!(response.status().is_client_error());
+}
+
+#[tokio::test]
+async fn the_audit_log_is_read_page_by_page() {
+    let app = spawn_app().await;
+
+    let ids = (1..=3u64).map(|n| (1510000000000000000 + n).to_string()).collect::<Vec<_>>();
+    let response = app.post(&format!("/remove?user_id={}", app.users.connected), json!({ "tweets": ids, "rts": [] })).await;
+    assert!(response.status().is_success());
+
+    let page = |after: &str| format!("/audit?user_id={}&action=delete&limit=2{}", app.users.connected, after);
+
+    let first = app.get(&page("")).await;
+    let next = first.headers()["x-next-after"].to_str().unwrap().to_string();
+    let mut targets = json(first).await["body"].as_array().unwrap().clone();
+
+    let second = app.get(&page(&format!("&after={}", next))).await;
+    assert!(second.headers().get("x-next-after").is_none());
+    targets.extend(json(second).await["body"].as_array().unwrap().clone());
+
+    let mut targets = targets.iter().map(|e| e["target"].as_str().unwrap().to_string()).collect::<Vec<_>>();
+    targets.sort();
+    assert_eq!(targets, ids);
+}
@@ -0,0 +1,189 @@
+use std::time::Duration;
+use serde_json::json;
+
+use twitar::stubs::FakeTwitter;
+use twitar::stubs::state::{FakeState, SEED_USER_ID};
+
+use crate::helpers::app::{json, spawn_app, spawn_app_with};
+
+
+#[tokio::test]
+async fn remove_deletes_tweets_retweets_and_likes() {
+    let app = spawn_app().await;
+    let state = app.twitter.state();
+
+    let body = json!({
+        "tweets": ["1510000000000000001"],
+        "rts": ["1510000000000000000"],
+        "likes": ["1500000000000000014"],
+    });
+    let response = app.post(&format!("/remove?user_id={}", app.users.connected), body).await;
+    assert!(response.status().is_success());
+
+    let state = state.lock().unwrap();
+    assert!(state.tweet("1510000000000000001").is_none());
+    assert!(state.tweet("1510000000000000000").is_none());
+    assert_eq!(state.liked_tweets(SEED_USER_ID).len(), 14);
+}
+
+#[tokio::test]
+async fn remove_rejects_ids_that_are_not_numeric() {
+    let app = spawn_app().await;
+
+    let body = json!({ "tweets": ["not-an-id"], "rts": [] });
+    let response = app.post(&format!("/remove?user_id={}", app.users.connected), body).await;
+
+    assert!(response.status().is_client_error());
+}
+
+#[tokio::test]
+async fn shutdown_lets_in_flight_deletions_finish_and_requeues_the_rest() {
+    let app = spawn_app_with(FakeTwitter::new().with_latency(Duration::from_millis(200))).await;
+    let state = app.twitter.state();
+
+    let ids = (0..30u64).map(|n| (1510000000000000000 + n).to_string()).collect::<Vec<_>>();
+    let path = format!("/remove?user_id={}", app.users.connected);
+
+    // the archive lookup takes the first 200ms, the shutdown lands while the first 10 removals are in flight
+    let shutdown = app.shutdown.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        shutdown.trigger();
+    });
+
+    let response = app.post(&path, json!({ "tweets": ids, "rts": [] })).await;
+    assert_eq!(response.status().as_u16(), 503);
+    assert!(json(response).await["body"].as_str().unwrap().starts_with("20 ids"));
+
+    let remaining = ids.iter().filter(|id| state.lock().unwrap().tweet(id).is_some()).count();
+    assert_eq!(remaining, 20);
+
+    let requeued: Vec<String> = sqlx::query_scalar("SELECT tweet_id FROM play_tweets WHERE user_id = $1 AND status = 'pending'")
+        .bind(app.users.connected)
+        .fetch_all(&app.db_pool).await.unwrap();
+    assert_eq!(requeued.len(), 20);
+    assert!(requeued.iter().all(|id| state.lock().unwrap().tweet(id).is_some()));
+}
+
+#[tokio::test]
+async fn a_removal_the_client_gave_up_on_still_finishes() {
+    let app = spawn_app_with(FakeTwitter::new().with_latency(Duration::from_millis(200))).await;
+    let state = app.twitter.state();
+
+    let ids = (0..30u64).map(|n| (1510000000000000000 + n).to_string()).collect::<Vec<_>>();
+    let path = format!("/remove?user_id={}", app.users.connected);
+
+    // the connection is closed while the first removals are in flight, as when the request times out
+    let gave_up = tokio::time::timeout(Duration::from_millis(300), app.post(&path, json!({ "tweets": ids, "rts": [] }))).await;
+    assert!(gave_up.is_err());
+
+    tokio::time::sleep(Duration::from_millis(1000)).await;
+
+    let remaining = ids.iter().filter(|id| state.lock().unwrap().tweet(id).is_some()).count();
+    assert_eq!(remaining, 0);
+
+    let response = app.get(&format!("/audit?user_id={}&action=delete", app.users.connected)).await;
+    assert_eq!(json(response).await["body"].as_array().unwrap().len(), 30);
+
+    // the lease went with the removal
+    let timeline = app.get(&format!("/timeline?user_id={}", app.users.connected)).await;
+    assert_eq!(timeline.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn remove_requeues_what_the_rate_limit_does_not_allow() {
+    let app = spawn_app_with(FakeTwitter::with_state(FakeState::seeded().with_window_limit(3))).await;
+    let state = app.twitter.state();
+
+    let ids = (1..=5u64).map(|n| (1510000000000000000 + n).to_string()).collect::<Vec<_>>();
+    let path = format!("/remove?user_id={}", app.users.connected);
+
+    let response = app.post(&path, json!({ "tweets": ids, "rts": [] })).await;
+    assert_eq!(response.status().as_u16(), 429);
+    assert!(json(response).await["body"].as_str().unwrap().starts_with("2 ids"));
+
+    let removed = ids.iter().filter(|id| state.lock().unwrap().tweet(id).is_none()).count();
+    assert_eq!(removed, 3);
+
+    // the window Twitter reported is spent, so the next removal is refused as well
+    let remaining = ids.iter().filter(|id| state.lock().unwrap().tweet(id).is_some()).cloned().collect::<Vec<_>>();
+    let response = app.post(&path, json!({ "tweets": remaining, "rts": [] })).await;
+    assert_eq!(response.status().as_u16(), 429);
+
+    // the same two ids were requeued twice, and are queued once
+    let requeued: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM play_tweets WHERE user_id = $1 AND status = 'pending'")
+        .bind(app.users.connected)
+        .fetch_one(&app.db_pool).await.unwrap();
+    assert_eq!(requeued, 2);
+}
+
+#[tokio::test]
+async fn a_second_job_for_the_same_user_is_turned_away_while_one_runs() {
+    let app = spawn_app_with(FakeTwitter::new().with_latency(Duration::from_millis(300))).await;
+    let path = format!("/remove?user_id={}", app.users.connected);
+
+    let first = app.post(&path, json!({ "tweets": ["1510000000000000001"], "rts": [] }));
+    let second = async {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        app.post(&path, json!({ "tweets": ["1510000000000000002"], "rts": [] })).await
+    };
+
+    let (first, second) = tokio::join!(first, second);
+    assert_eq!(first.status().as_u16(), 200);
+    assert_eq!(second.status().as_u16(), 409);
+
+    let body = json(second).await;
+    assert_eq!(body["code"], "job_in_progress");
+    assert_eq!(body["details"]["running_job"], "remove");
+
+    // the lease went with the first job
+    let timeline = app.get(&format!("/timeline?user_id={}", app.users.connected)).await;
+    assert_eq!(timeline.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn removed_items_are_archived_before_they_are_gone() {
+    let app = spawn_app().await;
+    let state = app.twitter.state();
+
+    let body = json!({
+        "tweets": ["1510000000000000001"],
+        "rts": ["1510000000000000000"],
+        // already gone, so there is nothing to keep
+        "likes": ["1590000000000000000"],
+    });
+    let response = app.post(&format!("/remove?user_id={}", app.users.connected), body).await;
+    assert!(response.status().is_success());
+    assert!(state.lock().unwrap().tweet("1510000000000000001").is_none());
+
+    let response = app.get(&format!("/archive?user_id={}", app.users.connected)).await;
+    let archived = json(response).await["body"].as_array().unwrap().clone();
+
+    let of_type = |tweet_type: &str| archived.iter().find(|a| a["tweet_type"] == tweet_type).unwrap().clone();
+
+    assert_eq!(archived.len(), 2);
+    assert_eq!(of_type("tweets")["text"], "A tweet from Twitter Dev #1");
+    assert_eq!(of_type("tweets")["created_at"], "2022-04-01T11:00:00Z");
+    assert_eq!(of_type("rts")["referenced_tweets"][0]["id"], "1500000000000000000");
+    assert_eq!(of_type("rts")["referenced_tweets"][0]["tweet"]["text"], "A tweet from Twitter #0");
+}
+
+#[tokio::test]
+async fn items_are_looked_up_100_at_a_time() {
+    let app = spawn_app().await;
+
+    let ids = |from: u64, count: u64| (from..from + count).map(|n| n.to_string()).collect::<Vec<_>>();
+    // 115 ids, which Twitter would refuse in a single lookup. Only the 30 tweets and 15 likes exist
+    let body = json!({
+        "tweets": ids(1510000000000000000, 50),
+        "rts": ids(1510000000000000050, 50),
+        "likes": ids(1500000000000000000, 15),
+    });
+    let response = app.post(&format!("/remove?user_id={}", app.users.connected), body).await;
+    assert!(response.status().is_success());
+
+    let archived: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tweet_archive WHERE user_id = $1")
+        .bind(app.users.connected)
+        .fetch_one(&app.db_pool).await.unwrap();
+    assert_eq!(archived, 45);
+}
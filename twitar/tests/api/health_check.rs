@@ -0,0 +1,107 @@
+use std::time::Duration;
+
+use twitar::startup::migrations;
+use twitar::stubs::FakeTwitter;
+
+use crate::helpers::app::{spawn_app, spawn_app_with, json};
+
+
+#[tokio::test]
+async fn health_check_works() {
+    let app = spawn_app().await;
+
+    let response = app.get("/").await;
+
+    assert!(response.status().is_success());
+}
+
+#[tokio::test]
+async fn users_without_a_connected_account_get_an_error_envelope() {
+    let app = spawn_app().await;
+
+    let response = app.get(&format!("/timeline?user_id={}", app.users.pending)).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+    let body = json(response).await;
+    assert_eq!(body["status"], 401);
+    assert!(body["request_id"].is_string());
+}
+
+#[tokio::test]
+async fn liveness_does_not_depend_on_anything() {
+    let app = spawn_app().await;
+
+    let response = app.get("/healthz").await;
+
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn readiness_reports_every_component() {
+    let app = spawn_app().await;
+
+    let response = app.get("/readyz").await;
+    let status = response.status().as_u16();
+    let body = json(response).await;
+
+    let components = &body["body"]["components"];
+    assert_eq!(components["postgres"]["up"], true);
+    assert!(components["postgres"]["latency_ms"].is_u64());
+    assert!(components["redis"]["up"].is_boolean());
+    assert_eq!(components["twitter"]["up"], true);
+
+    // redis is not always running where the tests are
+    let expected = match components["redis"]["up"].as_bool().unwrap() {
+        true => 200,
+        false => 503,
+    };
+    assert_eq!(status, expected);
+    assert_eq!(body["body"]["ready"], status == 200);
+}
+
+#[tokio::test]
+async fn readiness_fails_while_migrations_are_pending() {
+    let app = spawn_app().await;
+    migrations::down(&app.db_pool, 1).await.unwrap();
+
+    let response = app.get("/readyz").await;
+
+    assert_eq!(response.status().as_u16(), 503);
+    let body = json(response).await;
+    assert_eq!(body["body"]["components"]["postgres"]["up"], false);
+    assert_eq!(body["body"]["components"]["postgres"]["detail"], "1 migrations are not applied");
+}
+
+#[tokio::test]
+async fn readiness_fails_while_draining() {
+    // the fake api is slow enough for the shutdown to start while the probes run
+    let app = spawn_app_with(FakeTwitter::new().with_latency(Duration::from_millis(300))).await;
+
+    let shutdown = app.shutdown.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        shutdown.trigger();
+    });
+
+    let response = app.get("/readyz").await;
+
+    assert_eq!(response.status().as_u16(), 503);
+    let body = json(response).await;
+    assert_eq!(body["body"]["draining"], true);
+    assert_eq!(body["body"]["components"]["twitter"]["up"], true);
+}
+
+#[tokio::test]
+async fn every_response_carries_the_request_id() {
+    let app = spawn_app().await;
+
+    let response = app.get("/healthz").await;
+    assert!(response.headers().get("x-request-id").is_some());
+
+    let request = hyper::Request::get(format!("{}/healthz", app.address))
+        .header("x-request-id", "req-42")
+        .body(hyper::Body::empty()).unwrap();
+    let response = hyper::Client::new().request(request).await.unwrap();
+
+    assert_eq!(response.headers()["x-request-id"], "req-42");
+}
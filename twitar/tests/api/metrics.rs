@@ -0,0 +1,33 @@
+use serde_json::json;
+
+use crate::helpers::app::spawn_app;
+
+
+async fn scrape(app: &crate::helpers::app::TestApp) -> String {
+    let response = app.get("/metrics").await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.headers()["content-type"].to_str().unwrap().starts_with("text/plain"));
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn metrics_describe_requests_twitter_calls_and_the_database() {
+    let app = spawn_app().await;
+
+    let body = json!({ "tweets": ["1510000000000000001"], "rts": [] });
+    app.post(&format!("/remove?user_id={}", app.users.connected), body).await;
+    app.get("/not-a-route").await;
+
+    let metrics = scrape(&app).await;
+
+    assert!(metrics.contains(r#"twitar_http_requests_total{method="POST",route="/remove",status="200"}"#));
+    assert!(metrics.contains(r#"route="unmatched""#));
+    assert!(metrics.contains(r#"twitar_twitter_requests_total{endpoint="DELETE /2/tweets/:id",outcome="ok"}"#));
+    assert!(metrics.contains(r#"twitar_twitter_rate_limit_remaining{bucket="DELETE /2/tweets/:id"}"#));
+    assert!(metrics.contains(r#"twitar_jobs_total{job="remove_tweets",outcome="ok"}"#));
+    assert!(metrics.contains(r#"twitar_db_queries_total{outcome="ok",query="current_user"}"#));
+    assert!(metrics.contains(r#"twitar_db_pool_connections{state="idle"}"#));
+    assert!(metrics.contains("twitar_deletion_queue_ids"));
+}
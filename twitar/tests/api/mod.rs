@@ -0,0 +1,8 @@
+mod health_check;
+mod metrics;
+mod oauth;
+mod timeline;
+mod destroy;
+mod tokens;
+mod user_lookup;
+mod audit;
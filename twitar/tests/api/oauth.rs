@@ -0,0 +1,72 @@
+use sqlx::Row;
+
+use twitar::stubs::state::{SEED_ACCESS_TOKEN, SEED_OAUTH_TOKEN};
+
+use crate::helpers::app::{spawn_app, STATE_CODE};
+
+
+#[tokio::test]
+async fn oauth2_callback_stores_the_tokens() {
+    let app = spawn_app().await;
+
+    let response = app.get(&format!("/oauth/callback?code=auth-code&state={}", STATE_CODE)).await;
+    assert!(response.status().is_success());
+
+    let row = sqlx::query("SELECT access_token FROM auth_two WHERE user_id = $1")
+        .bind(app.users.pending)
+        .fetch_one(&app.db_pool).await.unwrap();
+    let access_token: Option<String> = row.get("access_token");
+
+    assert!(matches!(access_token, Some(token) if token != SEED_ACCESS_TOKEN));
+}
+
+#[tokio::test]
+async fn enable_sends_the_user_through_the_configured_authorize_url() {
+    let app = spawn_app().await;
+
+    let response = app.get(&format!("/enable?user_id={}", app.users.connected)).await;
+    assert_eq!(response.status().as_u16(), 302);
+    let authorize = response.headers()["location"].to_str().unwrap().to_string();
+    assert!(authorize.starts_with(&app.twitter_api), "{}", authorize);
+
+    // the fake api consents straight away and sends the user back to the callback
+    let response = app.get(&authorize).await;
+    assert_eq!(response.status().as_u16(), 302);
+    let callback = response.headers()["location"].to_str().unwrap().to_string();
+
+    let response = app.get(&callback).await;
+    assert!(response.status().is_success());
+}
+
+#[tokio::test]
+async fn oauth2_callback_rejects_an_unknown_state() {
+    let app = spawn_app().await;
+
+    let response = app.get("/oauth/callback?code=auth-code&state=forged").await;
+
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn oauth1_flow_stores_the_access_token() {
+    let app = spawn_app().await;
+
+    // the app sends the user to Twitter, which sends them back to the app's callback
+    let response = app.get("/oauth1").await;
+    assert_eq!(response.status().as_u16(), 302);
+    let authorize = response.headers()["location"].to_str().unwrap().to_string();
+
+    let response = app.get(&authorize).await;
+    assert_eq!(response.status().as_u16(), 302);
+    let callback = response.headers()["location"].to_str().unwrap().to_string();
+
+    let response = app.get(&callback).await;
+    assert!(response.status().is_success());
+
+    let row = sqlx::query("SELECT oauth_token, oauth_verifier FROM auth_one WHERE user_id = $1")
+        .bind(app.users.pending)
+        .fetch_one(&app.db_pool).await.unwrap();
+
+    assert_ne!(row.get::<String, _>("oauth_token"), SEED_OAUTH_TOKEN);
+    assert!(row.get::<Option<String>, _>("oauth_verifier").is_some());
+}
@@ -0,0 +1,66 @@
+use sqlx::Row;
+
+use twitar::stubs::state::SEED_ACCESS_TOKEN;
+
+use crate::helpers::app::{spawn_app, json};
+
+
+#[tokio::test]
+async fn timeline_stores_the_ids_of_tweets_retweets_and_likes() {
+    let app = spawn_app().await;
+
+    let response = app.get(&format!("/timeline?user_id={}", app.users.connected)).await;
+    assert!(response.status().is_success(), "{}", json(response).await);
+
+    let rows = sqlx::query("SELECT tweet_type::text AS tweet_type, created_at IS NOT NULL AS dated, text_hash FROM play_tweets WHERE user_id = $1")
+        .bind(app.users.connected)
+        .fetch_all(&app.db_pool).await.unwrap();
+
+    let count = |tweet_type: &str| rows.iter()
+        .filter(|row| row.get::<String, _>("tweet_type") == tweet_type)
+        .count();
+
+    // 30 tweets where every third one is a retweet, the 11 most recent are skipped
+    assert_eq!(count("tweets") + count("rts"), 19);
+    assert_eq!(count("likes"), 15);
+    assert!(rows.iter().all(|row| row.get::<bool, _>("dated") && row.get::<Option<String>, _>("text_hash").is_some()));
+}
+
+#[tokio::test]
+async fn syncing_twice_queues_every_id_once() {
+    let app = spawn_app().await;
+    let path = format!("/timeline?user_id={}", app.users.connected);
+
+    assert!(app.get(&path).await.status().is_success());
+    assert!(app.get(&path).await.status().is_success());
+
+    let queued: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM play_tweets WHERE user_id = $1")
+        .bind(app.users.connected)
+        .fetch_one(&app.db_pool).await.unwrap();
+    assert_eq!(queued, 34);
+}
+
+#[tokio::test]
+async fn a_failed_fetch_is_returned_and_nothing_is_queued() {
+    let app = spawn_app().await;
+    app.twitter.state().lock().unwrap().access_tokens.remove(SEED_ACCESS_TOKEN);
+
+    let response = app.get(&format!("/timeline?user_id={}", app.users.connected)).await;
+    assert_eq!(response.status(), 401);
+
+    let queued: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM play_tweets WHERE user_id = $1")
+        .bind(app.users.connected)
+        .fetch_one(&app.db_pool).await.unwrap();
+    assert_eq!(queued, 0);
+}
+
+#[tokio::test]
+async fn a_user_without_a_looked_up_twitter_id_is_unauthenticated() {
+    let app = spawn_app().await;
+    sqlx::query("UPDATE auth_two SET twitter_user_id = NULL WHERE user_id = $1")
+        .bind(app.users.connected)
+        .execute(&app.db_pool).await.unwrap();
+
+    let response = app.get(&format!("/timeline?user_id={}", app.users.connected)).await;
+    assert_eq!(response.status(), 401);
+}
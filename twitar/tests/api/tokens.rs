@@ -0,0 +1,43 @@
+use sqlx::Row;
+
+use twitar::stubs::state::SEED_ACCESS_TOKEN;
+
+use crate::helpers::app::spawn_app;
+
+
+#[tokio::test]
+async fn refresh_replaces_the_stored_tokens() {
+    let app = spawn_app().await;
+
+    let response = app.get(&format!("/refresh?user_id={}", app.users.connected)).await;
+    assert!(response.status().is_success());
+
+    let row = sqlx::query("SELECT access_token FROM auth_two WHERE user_id = $1")
+        .bind(app.users.connected)
+        .fetch_one(&app.db_pool).await.unwrap();
+    let access_token: String = row.get("access_token");
+
+    assert_ne!(access_token, SEED_ACCESS_TOKEN);
+    assert!(app.twitter.state().lock().unwrap().access_tokens.contains_key(&access_token));
+}
+
+#[tokio::test]
+async fn revoke_invalidates_the_access_token() {
+    let app = spawn_app().await;
+
+    let response = app.post(&format!("/revoke?user_id={}", app.users.connected), serde_json::json!({})).await;
+    assert!(response.status().is_success());
+
+    assert!(!app.twitter.state().lock().unwrap().access_tokens.contains_key(SEED_ACCESS_TOKEN));
+}
+
+#[tokio::test]
+async fn a_refresh_is_seen_by_the_next_request() {
+    let app = spawn_app().await;
+
+    // the user is cached before the first refresh spends the refresh token it carries
+    assert!(app.get(&format!("/refresh?user_id={}", app.users.connected)).await.status().is_success());
+
+    let response = app.get(&format!("/refresh?user_id={}", app.users.connected)).await;
+    assert!(response.status().is_success());
+}
@@ -0,0 +1,18 @@
+use sqlx::Row;
+
+use crate::helpers::app::spawn_app;
+
+
+#[tokio::test]
+async fn user_lookup_stores_the_twitter_id() {
+    let app = spawn_app().await;
+
+    let response = app.get(&format!("/user?user_id={}&username=Twitter", app.users.connected)).await;
+    assert!(response.status().is_success());
+
+    let row = sqlx::query("SELECT twitter_user_id FROM auth_two WHERE user_id = $1")
+        .bind(app.users.connected)
+        .fetch_one(&app.db_pool).await.unwrap();
+
+    assert_eq!(row.get::<String, _>("twitter_user_id"), "783214");
+}
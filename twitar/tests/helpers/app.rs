@@ -1,18 +1,145 @@
+use std::{env, net::TcpListener};
+use hyper::{Body, Client, Method, Request, Response, client::HttpConnector};
+use redis::Client as RedisClient;
+use serde_json::Value;
+use sqlx::{Connection, Executor, PgConnection, PgPool};
 use uuid::Uuid;
-use std::env;
 
-use crate::twitar::settings::config::{get_configuration, Settings};
+use twitar::helpers::{shutdown::Shutdown, transport::HyperTransport};
+use twitar::settings::config::{get_configuration, Settings};
+use twitar::settings::database::DbSettings;
+use twitar::startup::{application::Application, migrations};
+use twitar::stubs::FakeTwitter;
+use twitar::stubs::state::{SEED_ACCESS_TOKEN, SEED_OAUTH_SECRET, SEED_OAUTH_TOKEN, SEED_REFRESH_TOKEN, SEED_USER_ID};
 
 
-fn get_test_config() -> Settings {
-    let config = {
-        env::set_var("APP_ENV", "test");
+/// The OAuth controllers do not know who the user is yet and always use this id
+pub const PENDING_USER_ID: &str = "1b97475c-4ba1-4ccf-8a62-35baf9ff1075";
+/// The `app.state_code` of `configuration/test.yaml`
+pub const STATE_CODE: &str = "state-code";
+
+
+pub struct SeededUsers {
+    /// Went through both OAuth flows, with the credentials of the fake api's seeded user
+    pub connected: Uuid,
+    /// Has not connected a Twitter account yet
+    pub pending: Uuid,
+}
 
-        let db_name = Uuid::new_v4().to_string();
-        let mut app_config = get_configuration().expect("Failed to read configuration");
-        app_config.db.database_name = db_name;
 
-        app_config
+pub struct TestApp {
+    pub address: String,
+    pub db_pool: PgPool,
+    pub twitter: FakeTwitter,
+    /// The base url of the fake Twitter api
+    pub twitter_api: String,
+    pub users: SeededUsers,
+    /// Stops the application as a SIGTERM would
+    pub shutdown: Shutdown,
+    client: Client<HttpConnector>,
+}
+
+impl TestApp {
+    pub async fn get(&self, path: &str) -> Response<Body> {
+        self.request(Method::GET, path, Body::empty()).await
     }
-    return config;
-}
\ No newline at end of file
+
+    pub async fn post(&self, path: &str, body: Value) -> Response<Body> {
+        self.request(Method::POST, path, Body::from(body.to_string())).await
+    }
+
+    async fn request(&self, method: Method, path: &str, body: Body) -> Response<Body> {
+        let uri = match path.starts_with("http") {
+            true => path.to_string(),
+            false => format!("{}{}", self.address, path),
+        };
+
+        let request = Request::builder().method(method).uri(uri)
+            .header("content-type", "application/json")
+            .body(body).unwrap();
+
+        self.client.request(request).await.expect("Failed to send the request")
+    }
+}
+
+
+pub async fn json(response: Response<Body>) -> Value {
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    serde_json::from_slice(&body).unwrap_or(Value::Null)
+}
+
+
+fn get_test_config() -> Settings {
+    env::set_var("APP_ENV", "test");
+
+    let mut settings = get_configuration().expect("Failed to read the configuration");
+    settings.db.database_name = Uuid::new_v4().to_string();
+    settings
+}
+
+async fn configure_database(db: &DbSettings) -> PgPool {
+    // the maintenance database always exists, unlike one named after the role
+    let mut connection = PgConnection::connect_with(&db.without_db().database("postgres")).await
+        .expect("Failed to connect to Postgres");
+
+    connection.execute(format!(r#"CREATE DATABASE "{}";"#, db.database_name).as_str()).await
+        .expect("Failed to create the database");
+
+    let pool = PgPool::connect_with(db.with_db()).await.expect("Failed to connect to the test database");
+    migrations::up(&pool).await.expect("Failed to run the migrations");
+
+    pool
+}
+
+async fn seed_users(pool: &PgPool) -> SeededUsers {
+    let connected = Uuid::new_v4();
+    let pending = Uuid::parse_str(PENDING_USER_ID).unwrap();
+
+    sqlx::query("INSERT INTO user_preference (user_id, v1_active, v2_active) VALUES ($1, true, true), ($2, false, false)")
+        .bind(connected).bind(pending)
+        .execute(pool).await.unwrap();
+
+    sqlx::query("INSERT INTO auth_one (user_id, twitter_user_id, oauth_token, oauth_secret) VALUES ($1, $2, $3, $4)")
+        .bind(connected).bind(SEED_USER_ID).bind(SEED_OAUTH_TOKEN).bind(SEED_OAUTH_SECRET)
+        .execute(pool).await.unwrap();
+
+    sqlx::query("INSERT INTO auth_two (user_id, twitter_user_id, access_token, refresh_token) VALUES ($1, $2, $3, $4)")
+        .bind(connected).bind(SEED_USER_ID).bind(SEED_ACCESS_TOKEN).bind(SEED_REFRESH_TOKEN)
+        .execute(pool).await.unwrap();
+
+    sqlx::query("INSERT INTO auth_two (user_id, pkce) VALUES ($1, 'challenge')")
+        .bind(pending)
+        .execute(pool).await.unwrap();
+
+    SeededUsers { connected, pending }
+}
+
+pub async fn spawn_app() -> TestApp {
+    spawn_app_with(FakeTwitter::new()).await
+}
+
+/// Like `spawn_app`, against a fake api set up by the test
+pub async fn spawn_app_with(twitter: FakeTwitter) -> TestApp {
+    let mut settings = get_test_config();
+    let db_pool = configure_database(&settings.db).await;
+    let users = seed_users(&db_pool).await;
+
+    settings.app.twitter_api = twitter.clone().spawn();
+    settings.app.authorize_url = format!("{}/i/oauth2/authorize", settings.app.twitter_api);
+
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind a random port");
+    let address = format!("http://127.0.0.1:{}", listener.local_addr().unwrap().port());
+
+    settings.app.app_address = address.clone();
+    settings.app.callback_url = format!("{}/oauth/callback", address);
+
+    let redis = RedisClient::open(settings.redis_uri.as_str()).unwrap();
+    let twitter_api = settings.app.twitter_api.clone();
+
+    let application = Application::build(listener, settings, HyperTransport::client(), Some(redis), db_pool.clone())
+        .expect("Failed to build the application");
+    let shutdown = application.shutdown();
+    tokio::spawn(application.run_until_stopped());
+
+    TestApp { address, db_pool, twitter, twitter_api, users, shutdown, client: Client::new() }
+}
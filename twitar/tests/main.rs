@@ -1 +1,7 @@
-mod helpers;
\ No newline at end of file
+//! End to end tests: every test gets its own database, a fake Twitter api and a server on a random port.
+//! They need Postgres (see `configuration/test.yaml`) and only run with `cargo test --features test`
+#![cfg(feature = "test")]
+
+mod helpers;
+mod api;
+mod migrations;
@@ -0,0 +1,111 @@
+use sqlx::migrate::MigrateError;
+
+use twitar::startup::migrations::{self, MigrationState};
+
+use crate::helpers::app::spawn_app;
+
+
+const PLAY_TWEETS_ITEMS: i64 = 20261018090000;
+const AUDIT_LOG: i64 = 20261018110000;
+const TWEET_ARCHIVE: i64 = 20261018120000;
+
+
+#[tokio::test]
+async fn every_embedded_migration_is_applied() {
+    let app = spawn_app().await;
+
+    let statuses = migrations::status(&app.db_pool).await.unwrap();
+
+    assert_eq!(statuses.len(), 8);
+    assert!(statuses.iter().all(|s| s.state == MigrationState::Applied));
+}
+
+#[tokio::test]
+async fn down_reverts_the_latest_migrations_and_up_reapplies_them() {
+    let app = spawn_app().await;
+
+    assert_eq!(migrations::down(&app.db_pool, 3).await.unwrap(), vec![TWEET_ARCHIVE, AUDIT_LOG, PLAY_TWEETS_ITEMS]);
+    assert!(sqlx::query("SELECT status FROM play_tweets").execute(&app.db_pool).await.is_err());
+    assert!(sqlx::query("SELECT id FROM audit_log").execute(&app.db_pool).await.is_err());
+    assert!(sqlx::query("SELECT id FROM tweet_archive").execute(&app.db_pool).await.is_err());
+
+    let statuses = migrations::status(&app.db_pool).await.unwrap();
+    assert_eq!(statuses.last().unwrap().state, MigrationState::Pending);
+
+    migrations::up(&app.db_pool).await.unwrap();
+    assert!(sqlx::query("SELECT status FROM play_tweets").execute(&app.db_pool).await.is_ok());
+    assert!(sqlx::query("SELECT id FROM audit_log").execute(&app.db_pool).await.is_ok());
+    assert!(sqlx::query("SELECT id FROM tweet_archive").execute(&app.db_pool).await.is_ok());
+}
+
+#[tokio::test]
+async fn chunked_ids_are_converted_to_one_row_each() {
+    let app = spawn_app().await;
+    migrations::down(&app.db_pool, 3).await.unwrap();
+
+    let ids = (1..=12).map(|n| n.to_string()).collect::<Vec<_>>();
+    sqlx::query("INSERT INTO play_tweets (user_id, tweet_type, tweet_ids) VALUES ($1, 'tweets', $2), ($1, 'tweets', $3), ($1, 'likes', $4), (NULL, 'likes', $4)")
+        .bind(app.users.connected).bind(&ids[..10]).bind(&ids[8..]).bind(&ids[..1])
+        .execute(&app.db_pool).await.unwrap();
+
+    migrations::up(&app.db_pool).await.unwrap();
+
+    let rows: Vec<(String, String, String)> = sqlx::query_as("SELECT tweet_id, tweet_type::text, status::text FROM play_tweets WHERE user_id = $1 ORDER BY id")
+        .bind(app.users.connected)
+        .fetch_all(&app.db_pool).await.unwrap();
+
+    // the ids both chunks held are kept once, the row without a user is dropped
+    assert_eq!(rows.iter().filter(|(_, t, _)| t == "tweets").count(), 12);
+    assert_eq!(rows.iter().filter(|(_, t, _)| t == "likes").count(), 1);
+    assert!(rows.iter().all(|(_, _, status)| status == "pending"));
+
+    // and back into chunks of 10
+    migrations::down(&app.db_pool, 3).await.unwrap();
+    let chunks: Vec<Vec<String>> = sqlx::query_scalar("SELECT tweet_ids FROM play_tweets WHERE user_id = $1 AND tweet_type = 'tweets' ORDER BY id")
+        .bind(app.users.connected)
+        .fetch_all(&app.db_pool).await.unwrap();
+    assert_eq!(chunks.iter().map(|c| c.len()).collect::<Vec<_>>(), vec![10, 2]);
+}
+
+#[tokio::test]
+async fn a_schema_ahead_of_the_binary_is_refused() {
+    let app = spawn_app().await;
+    let newer = 29990101000000i64;
+
+    sqlx::query("INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) VALUES ($1, 'from a newer release', true, '\\x00', 0)")
+        .bind(newer)
+        .execute(&app.db_pool).await.unwrap();
+
+    assert!(matches!(migrations::check(&app.db_pool).await, Err(MigrateError::VersionMissing(v)) if v == newer));
+    assert!(matches!(migrations::up(&app.db_pool).await, Err(MigrateError::VersionMissing(v)) if v == newer));
+
+    let statuses = migrations::status(&app.db_pool).await.unwrap();
+    assert_eq!(statuses.last().unwrap().state, MigrationState::Unknown);
+}
+
+#[tokio::test]
+async fn reading_the_status_does_not_create_the_migrations_table() {
+    let app = spawn_app().await;
+    sqlx::query("DROP TABLE _sqlx_migrations").execute(&app.db_pool).await.unwrap();
+
+    let statuses = migrations::status(&app.db_pool).await.unwrap();
+    assert!(statuses.iter().all(|s| s.state == MigrationState::Pending));
+    assert!(migrations::check(&app.db_pool).await.is_ok());
+
+    let created: bool = sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+        .fetch_one(&app.db_pool).await.unwrap();
+    assert!(!created);
+}
+
+#[tokio::test]
+async fn the_audit_log_can_only_be_appended_to() {
+    let app = spawn_app().await;
+
+    sqlx::query("INSERT INTO audit_log (user_id, action, outcome, request_id) VALUES ($1, 'delete', 'success', 'request')")
+        .bind(app.users.connected)
+        .execute(&app.db_pool).await.unwrap();
+
+    assert!(sqlx::query("UPDATE audit_log SET outcome = 'failure'").execute(&app.db_pool).await.is_err());
+    assert!(sqlx::query("DELETE FROM audit_log").execute(&app.db_pool).await.is_err());
+    assert!(sqlx::query("TRUNCATE audit_log").execute(&app.db_pool).await.is_err());
+}
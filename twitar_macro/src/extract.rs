@@ -0,0 +1,101 @@
+use std::{collections::HashMap, fmt, str::FromStr};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+#[cfg(test)]
+#[path = "./extract.test.rs"]
+mod extract_test;
+
+
+/// Where a value was (or should have been) found on the request
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    Path,
+    Query,
+    Header,
+    Body,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Path => write!(f, "path"),
+            Self::Query => write!(f, "query"),
+            Self::Header => write!(f, "header"),
+            Self::Body => write!(f, "body"),
+        }
+    }
+}
+
+
+/// The raw inputs of a request, already split by source.
+/// Header names are expected in lowercase.
+#[derive(Debug, Clone, Default)]
+pub struct RequestParts {
+    pub path: HashMap<String, String>,
+    pub query: HashMap<String, String>,
+    pub headers: HashMap<String, String>,
+    pub body: Option<Value>,
+}
+
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ExtractError {
+    /// The value is required but the request does not contain it
+    Missing { field: &'static str, source: Source },
+    /// The value is present but could not be converted to the field's type
+    Invalid { field: &'static str, source: Source, reason: String },
+}
+
+impl fmt::Display for ExtractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing { field, source } => write!(f, "{} is missing from the request {}", field, source),
+            Self::Invalid { field, source, reason } => write!(f, "{} in the request {} is invalid: {}", field, source, reason),
+        }
+    }
+}
+
+impl std::error::Error for ExtractError {}
+
+
+pub trait Extract: Sized {
+    fn extract(parts: &RequestParts) -> Result<Self, ExtractError>;
+}
+
+
+/// Converts a single raw path, query or header value
+pub fn parse_param<T>(raw: &str, field: &'static str, source: Source) -> Result<T, ExtractError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    raw.parse::<T>().map_err(|e| ExtractError::Invalid { field, source, reason: e.to_string() })
+}
+
+/// Converts a comma separated raw value, empty entries are ignored
+pub fn parse_list<T>(raw: &str, field: &'static str, source: Source) -> Result<Vec<T>, ExtractError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    raw.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| parse_param(item, field, source))
+        .collect()
+}
+
+/// Reads a key of the JSON body, a missing key or an explicit `null` are both `None`
+pub fn body_field<T: DeserializeOwned>(parts: &RequestParts, field: &'static str) -> Result<Option<T>, ExtractError> {
+    let value = match parts.body.as_ref().and_then(|body| body.get(field)) {
+        Some(Value::Null) | None => return Ok(None),
+        Some(value) => value.clone(),
+    };
+
+    serde_json::from_value(value)
+        .map(Some)
+        .map_err(|e| ExtractError::Invalid { field, source: Source::Body, reason: e.to_string() })
+}
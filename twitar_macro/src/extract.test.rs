@@ -0,0 +1,86 @@
+mod test_extract {
+    use std::collections::HashMap;
+    use serde_json::json;
+
+    use crate::{Extract, ExtractError, RequestParts, Source};
+
+    #[derive(Debug, Extract)]
+    struct Lookup {
+        #[extract(query)]
+        username: String,
+        #[extract(query, rename = "max_results")]
+        max: Option<u8>,
+        #[extract(header = "X-User-Id")]
+        user_id: String,
+        #[extract(query)]
+        ids: Option<Vec<u64>>,
+    }
+
+    #[derive(Debug, Extract)]
+    #[extract(body)]
+    struct Ids {
+        tweets: Vec<String>,
+        likes: Option<Vec<String>>,
+    }
+
+    fn parts(query: &[(&str, &str)], headers: &[(&str, &str)]) -> RequestParts {
+        let to_map = |pairs: &[(&str, &str)]| pairs.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string())).collect::<HashMap<_, _>>();
+
+        RequestParts {
+            query: to_map(query),
+            headers: to_map(headers),
+            ..RequestParts::default()
+        }
+    }
+
+    #[test]
+    fn extracts_query_and_header_values() {
+        let parts = parts(&[("username", "tolumide"), ("max_results", "20"), ("ids", "1,2, 3")], &[("x-user-id", "abc")]);
+        let lookup = Lookup::extract(&parts).unwrap();
+
+        assert_eq!(lookup.username, "tolumide");
+        assert_eq!(lookup.max, Some(20));
+        assert_eq!(lookup.user_id, "abc");
+        assert_eq!(lookup.ids, Some(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn optional_values_can_be_absent() {
+        let parts = parts(&[("username", "tolumide")], &[("x-user-id", "abc")]);
+        let lookup = Lookup::extract(&parts).unwrap();
+
+        assert_eq!(lookup.max, None);
+        assert_eq!(lookup.ids, None);
+    }
+
+    #[test]
+    fn reports_missing_required_values() {
+        let parts = parts(&[], &[("x-user-id", "abc")]);
+
+        assert_eq!(Lookup::extract(&parts).unwrap_err(), ExtractError::Missing { field: "username", source: Source::Query });
+    }
+
+    #[test]
+    fn reports_values_of_the_wrong_type() {
+        let parts = parts(&[("username", "tolumide"), ("max_results", "many")], &[("x-user-id", "abc")]);
+        let err = Lookup::extract(&parts).unwrap_err();
+
+        assert!(matches!(err, ExtractError::Invalid { field: "max_results", source: Source::Query, .. }));
+    }
+
+    #[test]
+    fn extracts_body_fields() {
+        let parts = RequestParts { body: Some(json!({"tweets": ["1", "2"]})), ..RequestParts::default() };
+        let ids = Ids::extract(&parts).unwrap();
+
+        assert_eq!(ids.tweets, vec!["1", "2"]);
+        assert_eq!(ids.likes, None);
+
+        let parts = RequestParts { body: Some(json!({"likes": []})), ..RequestParts::default() };
+        assert_eq!(Ids::extract(&parts).unwrap_err(), ExtractError::Missing { field: "tweets", source: Source::Body });
+
+        let parts = RequestParts { body: Some(json!({"tweets": "1"})), ..RequestParts::default() };
+        assert!(matches!(Ids::extract(&parts).unwrap_err(), ExtractError::Invalid { field: "tweets", .. }));
+    }
+}
@@ -1,3 +1,18 @@
+//! Typed request extraction and validation for twitar's controllers.
+//!
+//! Structs derive [`Extract`] to be built from a request's path, query, headers or JSON body,
+//! and [`Validate`] to check field level rules once they have been extracted.
+extern crate self as twitar_macro;
+
+mod extract;
+mod validate;
+pub mod validators;
+
+pub use extract::{body_field, parse_list, parse_param, Extract, ExtractError, RequestParts, Source};
+pub use validate::{FieldError, Validate, ValidationErrors};
+pub use twitar_macro_derive::{Extract, Validate};
+
+
 #[cfg(test)]
 mod tests {
     #[test]
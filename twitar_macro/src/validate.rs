@@ -0,0 +1,63 @@
+use std::fmt;
+use serde::Serialize;
+
+#[cfg(test)]
+#[path = "./validate.test.rs"]
+mod validate_test;
+
+
+/// A single broken rule
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldError {
+    pub field: &'static str,
+    pub rule: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.field, self.message)
+    }
+}
+
+
+/// Every broken rule of a struct, so they can all be reported at once
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct ValidationErrors(pub Vec<FieldError>);
+
+impl ValidationErrors {
+    pub fn new() -> Self {
+        Self(vec![])
+    }
+
+    pub fn add(&mut self, field: &'static str, rule: &'static str, message: String) {
+        self.0.push(FieldError { field, rule, message });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn into_result(self) -> Result<(), Self> {
+        if self.is_empty() {
+            return Ok(())
+        }
+
+        Err(self)
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let errors = self.0.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+        write!(f, "{}", errors.join(", "))
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+
+pub trait Validate {
+    fn validate(&self) -> Result<(), ValidationErrors>;
+}
@@ -0,0 +1,65 @@
+mod test_validate {
+    use crate::{Validate, ValidationErrors};
+
+    fn not_reserved(name: &str) -> Result<(), &'static str> {
+        match name {
+            "admin" => Err("is reserved"),
+            _ => Ok(()),
+        }
+    }
+
+    #[derive(Validate)]
+    struct Lookup {
+        #[validate(required, length(min = 1, max = 15), custom = "not_reserved")]
+        username: String,
+        #[validate(numeric, max_items = 3, unique)]
+        ids: Vec<String>,
+        #[extract(rename = "likes")]
+        #[validate(numeric, unique)]
+        liked: Option<Vec<String>>,
+    }
+
+    fn rules(errors: ValidationErrors) -> Vec<(&'static str, &'static str)> {
+        errors.0.iter().map(|e| (e.field, e.rule)).collect()
+    }
+
+    #[test]
+    fn valid_struct_passes() {
+        let lookup = Lookup { username: "tolumide".into(), ids: vec!["1".into(), "2".into()], liked: None };
+
+        assert!(lookup.validate().is_ok());
+    }
+
+    #[test]
+    fn reports_every_broken_rule() {
+        let lookup = Lookup {
+            username: "".into(),
+            ids: vec!["1".into(), "1".into(), "x".into(), "4".into()],
+            liked: Some(vec!["9".into(), "9".into()]),
+        };
+
+        let errors = rules(lookup.validate().unwrap_err());
+
+        assert_eq!(errors, vec![
+            ("username", "required"), ("username", "length"),
+            ("ids", "numeric"), ("ids", "max_items"), ("ids", "unique"),
+            ("likes", "unique"),
+        ]);
+    }
+
+    #[test]
+    fn runs_custom_validators() {
+        let lookup = Lookup { username: "admin".into(), ids: vec![], liked: None };
+        let errors = lookup.validate().unwrap_err();
+
+        assert_eq!(rules(errors.clone()), vec![("username", "custom")]);
+        assert_eq!(errors.to_string(), "username is reserved");
+    }
+
+    #[test]
+    fn enforces_max_length() {
+        let lookup = Lookup { username: "a_very_long_username".into(), ids: vec![], liked: None };
+
+        assert_eq!(rules(lookup.validate().unwrap_err()), vec![("username", "length")]);
+    }
+}
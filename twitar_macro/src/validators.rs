@@ -0,0 +1,91 @@
+//! The checks behind each `#[validate(...)]` rule.
+//! `Option` fields that are `None` always pass, pair them with `required` when they must be present.
+use std::{collections::HashSet, hash::Hash};
+
+
+pub trait Required {
+    fn is_present(&self) -> bool;
+}
+
+impl Required for String {
+    fn is_present(&self) -> bool {
+        !self.trim().is_empty()
+    }
+}
+
+impl<T> Required for Vec<T> {
+    fn is_present(&self) -> bool {
+        !self.is_empty()
+    }
+}
+
+impl<T: Required> Required for Option<T> {
+    fn is_present(&self) -> bool {
+        self.iter().any(Required::is_present)
+    }
+}
+
+
+pub trait Length {
+    /// `None` when there is nothing to measure
+    fn length(&self) -> Option<usize>;
+}
+
+impl Length for String {
+    fn length(&self) -> Option<usize> {
+        Some(self.chars().count())
+    }
+}
+
+impl<T> Length for Vec<T> {
+    fn length(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+impl<T: Length> Length for Option<T> {
+    fn length(&self) -> Option<usize> {
+        self.as_ref().and_then(Length::length)
+    }
+}
+
+
+pub trait Numeric {
+    fn is_numeric(&self) -> bool;
+}
+
+impl Numeric for String {
+    fn is_numeric(&self) -> bool {
+        !self.is_empty() && self.chars().all(|c| c.is_ascii_digit())
+    }
+}
+
+impl<T: Numeric> Numeric for Vec<T> {
+    fn is_numeric(&self) -> bool {
+        self.iter().all(Numeric::is_numeric)
+    }
+}
+
+impl<T: Numeric> Numeric for Option<T> {
+    fn is_numeric(&self) -> bool {
+        self.iter().all(Numeric::is_numeric)
+    }
+}
+
+
+pub trait Unique {
+    fn is_unique(&self) -> bool;
+}
+
+impl<T: Eq + Hash> Unique for Vec<T> {
+    fn is_unique(&self) -> bool {
+        let mut seen = HashSet::with_capacity(self.len());
+        self.iter().all(|item| seen.insert(item))
+    }
+}
+
+impl<T: Unique> Unique for Option<T> {
+    fn is_unique(&self) -> bool {
+        self.iter().all(Unique::is_unique)
+    }
+}
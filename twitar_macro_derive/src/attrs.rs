@@ -0,0 +1,183 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Attribute, Error, GenericArgument, Lit, Meta, NestedMeta, Path, PathArguments, Result, Type};
+
+
+/// Where a field's raw value is read from when a struct is extracted from a request
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Source {
+    Path,
+    Query,
+    Header,
+    Body,
+}
+
+impl Source {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "path" => Some(Self::Path),
+            "query" => Some(Self::Query),
+            "header" => Some(Self::Header),
+            "body" => Some(Self::Body),
+            _ => None,
+        }
+    }
+
+    pub fn variant(&self) -> TokenStream {
+        match self {
+            Self::Path => quote!(::twitar_macro::Source::Path),
+            Self::Query => quote!(::twitar_macro::Source::Query),
+            Self::Header => quote!(::twitar_macro::Source::Header),
+            Self::Body => quote!(::twitar_macro::Source::Body),
+        }
+    }
+}
+
+
+/// The content of `#[extract(...)]` on a struct or one of its fields
+#[derive(Debug, Default)]
+pub struct ExtractAttr {
+    pub source: Option<Source>,
+    pub rename: Option<String>,
+}
+
+pub fn extract_attr(attrs: &[Attribute]) -> Result<ExtractAttr> {
+    let mut parsed = ExtractAttr::default();
+
+    for nested in nested_metas(attrs, "extract")? {
+        match &nested {
+            NestedMeta::Meta(Meta::Path(path)) => {
+                let source = Source::from_name(&path_name(path))
+                    .ok_or_else(|| Error::new_spanned(path, "expected one of `path`, `query`, `header` or `body`"))?;
+                parsed.source = Some(source);
+            }
+            NestedMeta::Meta(Meta::NameValue(pair)) => {
+                let name = path_name(&pair.path);
+                let value = lit_str(&pair.lit)?.value();
+
+                match (name.as_str(), Source::from_name(&name)) {
+                    ("rename", _) => parsed.rename = Some(value),
+                    (_, Some(source)) => {
+                        parsed.source = Some(source);
+                        parsed.rename = Some(value);
+                    }
+                    _ => return Err(Error::new_spanned(&pair.path, "unknown extract option")),
+                }
+            }
+            _ => return Err(Error::new_spanned(nested, "unsupported extract option")),
+        }
+    }
+
+    Ok(parsed)
+}
+
+
+/// A single rule declared with `#[validate(...)]`
+pub enum Rule {
+    Required,
+    Length { min: Option<usize>, max: Option<usize> },
+    Numeric,
+    MaxItems(usize),
+    Unique,
+    Custom(Path),
+}
+
+pub fn validate_rules(attrs: &[Attribute]) -> Result<Vec<Rule>> {
+    let mut rules = vec![];
+
+    for nested in nested_metas(attrs, "validate")? {
+        let rule = match &nested {
+            NestedMeta::Meta(Meta::Path(path)) => match path_name(path).as_str() {
+                "required" => Rule::Required,
+                "numeric" => Rule::Numeric,
+                "unique" => Rule::Unique,
+                _ => return Err(Error::new_spanned(path, "unknown validation rule")),
+            },
+            NestedMeta::Meta(Meta::NameValue(pair)) => match path_name(&pair.path).as_str() {
+                "max_items" => Rule::MaxItems(lit_usize(&pair.lit)?),
+                "custom" => Rule::Custom(lit_str(&pair.lit)?.parse()?),
+                _ => return Err(Error::new_spanned(&pair.path, "unknown validation rule")),
+            },
+            NestedMeta::Meta(Meta::List(list)) if path_name(&list.path) == "length" => {
+                let (mut min, mut max) = (None, None);
+
+                for bound in &list.nested {
+                    match bound {
+                        NestedMeta::Meta(Meta::NameValue(pair)) if path_name(&pair.path) == "min" => {
+                            min = Some(lit_usize(&pair.lit)?);
+                        }
+                        NestedMeta::Meta(Meta::NameValue(pair)) if path_name(&pair.path) == "max" => {
+                            max = Some(lit_usize(&pair.lit)?);
+                        }
+                        _ => return Err(Error::new_spanned(bound, "expected `min = ..` or `max = ..`")),
+                    }
+                }
+
+                if min.is_none() && max.is_none() {
+                    return Err(Error::new_spanned(list, "length requires a `min` or `max` bound"));
+                }
+
+                Rule::Length { min, max }
+            }
+            _ => return Err(Error::new_spanned(nested, "unknown validation rule")),
+        };
+
+        rules.push(rule);
+    }
+
+    Ok(rules)
+}
+
+
+/// Returns `T` when `ty` is written as `Wrapper<T>`, e.g. `Option<String>` for the wrapper `Option`
+pub fn wrapped<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    let path = match ty {
+        Type::Path(type_path) if type_path.qself.is_none() => &type_path.path,
+        _ => return None,
+    };
+
+    let segment = path.segments.last()?;
+    if segment.ident != wrapper {
+        return None;
+    }
+
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 1 => match &args.args[0] {
+            GenericArgument::Type(inner) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+
+fn nested_metas(attrs: &[Attribute], name: &str) -> Result<Vec<NestedMeta>> {
+    let mut metas = vec![];
+
+    for attr in attrs.iter().filter(|a| a.path.is_ident(name)) {
+        match attr.parse_meta()? {
+            Meta::List(list) => metas.extend(list.nested),
+            other => return Err(Error::new_spanned(other, format!("expected #[{}(...)]", name))),
+        }
+    }
+
+    Ok(metas)
+}
+
+fn path_name(path: &Path) -> String {
+    path.get_ident().map(|i| i.to_string()).unwrap_or_default()
+}
+
+fn lit_str(lit: &Lit) -> Result<syn::LitStr> {
+    match lit {
+        Lit::Str(s) => Ok(s.clone()),
+        _ => Err(Error::new_spanned(lit, "expected a string literal")),
+    }
+}
+
+fn lit_usize(lit: &Lit) -> Result<usize> {
+    match lit {
+        Lit::Int(i) => i.base10_parse(),
+        _ => Err(Error::new_spanned(lit, "expected an integer literal")),
+    }
+}
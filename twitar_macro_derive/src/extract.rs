@@ -0,0 +1,94 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Error, Fields, Result};
+
+use crate::attrs::{extract_attr, wrapped, Source};
+
+
+pub fn expand(input: DeriveInput) -> Result<TokenStream> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let default_source = extract_attr(&input.attrs)?.source;
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(Error::new_spanned(name, "Extract can only be derived for structs with named fields")),
+        },
+        _ => return Err(Error::new_spanned(name, "Extract can only be derived for structs")),
+    };
+
+    let mut initializers = vec![];
+
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        let attr = extract_attr(&field.attrs)?;
+
+        let source = attr.source.or(default_source).ok_or_else(|| {
+            Error::new_spanned(ident, "missing #[extract(path | query | header | body)] on the field or its struct")
+        })?;
+
+        let mut key = attr.rename.unwrap_or_else(|| ident.to_string());
+        if source == Source::Header {
+            key = key.to_lowercase();
+        }
+
+        let value = field_value(source, &key, &field.ty);
+        initializers.push(quote!(#ident: #value));
+    }
+
+    Ok(quote! {
+        impl #impl_generics ::twitar_macro::Extract for #name #ty_generics #where_clause {
+            fn extract(parts: &::twitar_macro::RequestParts) -> ::std::result::Result<Self, ::twitar_macro::ExtractError> {
+                Ok(Self {
+                    #(#initializers,)*
+                })
+            }
+        }
+    })
+}
+
+
+fn field_value(source: Source, key: &str, ty: &syn::Type) -> TokenStream {
+    let src = source.variant();
+    let missing = quote!(::twitar_macro::ExtractError::Missing { field: #key, source: #src });
+
+    if source == Source::Body {
+        return match wrapped(ty, "Option") {
+            Some(inner) => quote!(::twitar_macro::body_field::<#inner>(parts, #key)?),
+            None => quote!(::twitar_macro::body_field::<#ty>(parts, #key)?.ok_or(#missing)?),
+        };
+    }
+
+    let map = match source {
+        Source::Path => quote!(parts.path),
+        Source::Query => quote!(parts.query),
+        _ => quote!(parts.headers),
+    };
+
+    let (optional, ty) = match wrapped(ty, "Option") {
+        Some(inner) => (true, inner),
+        None => (false, ty),
+    };
+
+    let parse = match wrapped(ty, "Vec") {
+        Some(item) => quote!(::twitar_macro::parse_list::<#item>(raw, #key, #src)?),
+        None => quote!(::twitar_macro::parse_param::<#ty>(raw, #key, #src)?),
+    };
+
+    if optional {
+        quote! {
+            match #map.get(#key) {
+                Some(raw) => Some(#parse),
+                None => None,
+            }
+        }
+    } else {
+        quote! {
+            {
+                let raw = #map.get(#key).ok_or(#missing)?;
+                #parse
+            }
+        }
+    }
+}
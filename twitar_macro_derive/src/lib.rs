@@ -1,3 +1,37 @@
+//! Derive macros for pulling typed input out of a request and validating it.
+//! The traits they implement live in `twitar_macro`, which re-exports both derives.
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+mod attrs;
+mod extract;
+mod validate;
+
+
+/// Implements `twitar_macro::Extract`.
+///
+/// Every field (or the struct as a whole) names where its value comes from with
+/// `#[extract(path | query | header | body)]`. `rename = ".."` or `header = "x-name"`
+/// reads the value under a different key. `Option<T>` fields are optional, `Vec<T>`
+/// fields read from path, query or header are comma separated.
+#[proc_macro_derive(Extract, attributes(extract))]
+pub fn derive_extract(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    extract::expand(input).unwrap_or_else(|e| e.to_compile_error()).into()
+}
+
+/// Implements `twitar_macro::Validate` from the `#[validate(...)]` rules on each field:
+/// `required`, `length(min = .., max = ..)`, `numeric`, `max_items = ..`, `unique`
+/// and `custom = "path::to::fn"`.
+#[proc_macro_derive(Validate, attributes(validate, extract))]
+pub fn derive_validate(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    validate::expand(input).unwrap_or_else(|e| e.to_compile_error()).into()
+}
+
+
 #[cfg(test)]
 mod tests {
     #[test]
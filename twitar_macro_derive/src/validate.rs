@@ -0,0 +1,96 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Error, Fields, Result};
+
+use crate::attrs::{extract_attr, validate_rules, Rule};
+
+
+pub fn expand(input: DeriveInput) -> Result<TokenStream> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(Error::new_spanned(name, "Validate can only be derived for structs with named fields")),
+        },
+        _ => return Err(Error::new_spanned(name, "Validate can only be derived for structs")),
+    };
+
+    let mut checks = vec![];
+
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        // errors are reported with the name the client sent, not the rust field name
+        let key = extract_attr(&field.attrs)?.rename.unwrap_or_else(|| ident.to_string());
+
+        for rule in validate_rules(&field.attrs)? {
+            checks.push(check(&rule, ident, &key));
+        }
+    }
+
+    Ok(quote! {
+        impl #impl_generics ::twitar_macro::Validate for #name #ty_generics #where_clause {
+            fn validate(&self) -> ::std::result::Result<(), ::twitar_macro::ValidationErrors> {
+                #[allow(unused_imports)]
+                use ::twitar_macro::validators::{Length, Numeric, Required, Unique};
+
+                let mut errors = ::twitar_macro::ValidationErrors::new();
+                #(#checks)*
+                errors.into_result()
+            }
+        }
+    })
+}
+
+
+fn check(rule: &Rule, ident: &syn::Ident, key: &str) -> TokenStream {
+    match rule {
+        Rule::Required => quote! {
+            if !Required::is_present(&self.#ident) {
+                errors.add(#key, "required", "is required".to_string());
+            }
+        },
+        Rule::Length { min, max } => {
+            let min_check = min.map(|min| quote! {
+                if len < #min {
+                    errors.add(#key, "length", format!("must have a length of at least {}", #min));
+                }
+            });
+            let max_check = max.map(|max| quote! {
+                if len > #max {
+                    errors.add(#key, "length", format!("must have a length of at most {}", #max));
+                }
+            });
+
+            quote! {
+                if let Some(len) = Length::length(&self.#ident) {
+                    #min_check
+                    #max_check
+                }
+            }
+        }
+        Rule::Numeric => quote! {
+            if !Numeric::is_numeric(&self.#ident) {
+                errors.add(#key, "numeric", "must only contain numeric strings".to_string());
+            }
+        },
+        Rule::MaxItems(max) => quote! {
+            if let Some(len) = Length::length(&self.#ident) {
+                if len > #max {
+                    errors.add(#key, "max_items", format!("cannot contain more than {} items", #max));
+                }
+            }
+        },
+        Rule::Unique => quote! {
+            if !Unique::is_unique(&self.#ident) {
+                errors.add(#key, "unique", "must not contain duplicates".to_string());
+            }
+        },
+        Rule::Custom(path) => quote! {
+            if let Err(message) = #path(&self.#ident) {
+                errors.add(#key, "custom", message.to_string());
+            }
+        },
+    }
+}